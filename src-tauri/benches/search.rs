@@ -0,0 +1,136 @@
+//! Workspace search benchmark over a generated source tree.
+//!
+//! Run with `cargo bench --bench search`. The tree mimics a mid-sized monorepo:
+//! nested packages of source files, a `node_modules` directory that must be
+//! skipped, and a sprinkling of binary and large files.
+
+#![allow(dead_code)]
+
+#[path = "../src/error.rs"]
+mod error;
+#[path = "../src/languages/registry.rs"]
+mod languages;
+// The engine detects the language of files without a known extension
+#[path = "../src/languages/detect.rs"]
+mod detect;
+#[path = "../src/search/engine.rs"]
+mod engine;
+#[path = "../src/search/index.rs"]
+mod index;
+
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+// `detect` refers to `super::FileTypes`, as it sits under `languages` in the app
+use languages::FileTypes;
+use engine::{build_pattern, search_workspace, MatchOptions, Matcher, SearchFilters, SearchScope};
+use index::{IndexHandle, TrigramQuery};
+
+const PACKAGES: usize = 40;
+const DIRS_PER_PACKAGE: usize = 10;
+const FILES_PER_DIR: usize = 25;
+
+fn source_file(package: usize, dir: usize, file: usize) -> String {
+    let mut body = String::new();
+    for function in 0..40 {
+        body.push_str(&format!(
+            "export function handler_{package}_{dir}_{file}_{function}(request) {{\n    const value = request.body?.items ?? [];\n    return value.map(item => item.id * {function});\n}}\n\n"
+        ));
+    }
+    if file.is_multiple_of(50) {
+        body.push_str("// TODO: retry the HTTP call with backoff\n");
+    }
+    body
+}
+
+fn generate_tree(root: &Path) {
+    for package in 0..PACKAGES {
+        for dir in 0..DIRS_PER_PACKAGE {
+            let dir_path = root.join(format!("packages/pkg{package}/src/module{dir}"));
+            fs::create_dir_all(&dir_path).unwrap();
+            for file in 0..FILES_PER_DIR {
+                fs::write(dir_path.join(format!("file{file}.js")), source_file(package, dir, file)).unwrap();
+            }
+        }
+        // Binary assets must be rejected without scanning them
+        let assets = root.join(format!("packages/pkg{package}/assets"));
+        fs::create_dir_all(&assets).unwrap();
+        let mut blob = vec![0u8; 64 * 1024];
+        blob[1024..1040].copy_from_slice(b"retry the HTTP c");
+        fs::write(assets.join("blob.json"), blob).unwrap();
+    }
+
+    // One large file to exercise the memory-mapped path
+    let large: String = (0..20_000).map(|i| format!("line {i} of a generated log entry\n")).collect();
+    fs::write(root.join("packages/large.log"), large).unwrap();
+
+    // Ignored directories should cost nothing
+    let node_modules = root.join("node_modules/dep/lib");
+    fs::create_dir_all(&node_modules).unwrap();
+    for file in 0..500 {
+        fs::write(node_modules.join(format!("index{file}.js")), source_file(0, 0, file)).unwrap();
+    }
+}
+
+fn bench_search(c: &mut Criterion) {
+    let tree = tempfile::tempdir().unwrap();
+    generate_tree(tree.path());
+    let scope = SearchScope::new(tree.path(), &SearchFilters::default(), Default::default()).unwrap();
+    let options = MatchOptions::default();
+    let never_cancelled = AtomicBool::new(false);
+
+    let queries = [
+        ("literal", "retry the HTTP call", false, true, false),
+        ("literal_case_insensitive", "RETRY the http CALL", false, false, false),
+        ("whole_word", "value", false, true, true),
+        ("regex", r"handler_\d+_3_\d+_7\(", true, true, false),
+    ];
+    let queries: Vec<_> = queries.iter()
+        .map(|&(name, query, use_regex, case_sensitive, whole_word)| {
+            let matcher = Matcher::new(query, use_regex, case_sensitive, whole_word).unwrap();
+            let plan = TrigramQuery::for_pattern(&build_pattern(query, use_regex, whole_word), !case_sensitive);
+            (name, matcher, plan)
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("search_workspace");
+    group.sample_size(10);
+    for (name, matcher, _) in &queries {
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                let mut files = 0;
+                search_workspace(&scope, matcher, &options, usize::MAX, &never_cancelled, &mut |_| files += 1);
+                files
+            })
+        });
+    }
+    group.finish();
+
+    // Same queries narrowed by a trigram index; the lookup is part of each iteration
+    let index = IndexHandle::start(tree.path(), Default::default(), None).unwrap();
+    while index.candidates(&TrigramQuery::All).is_none() {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    let mut group = c.benchmark_group("search_workspace_indexed");
+    group.sample_size(10);
+    for (name, matcher, plan) in &queries {
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                let candidates = index.candidates(plan).unwrap();
+                let scope = scope.clone().with_candidates(candidates);
+                let mut files = 0;
+                search_workspace(&scope, matcher, &options, usize::MAX, &never_cancelled, &mut |_| files += 1);
+                files
+            })
+        });
+    }
+    group.finish();
+    index.stop();
+}
+
+criterion_group!(benches, bench_search);
+criterion_main!(benches);
@@ -0,0 +1,29 @@
+; Scopes and local definitions for Go, for go-to-definition and references
+; without a language server. Package-level types and functions in other files
+; are left to the workspace symbol index.
+
+[
+  (source_file)
+  (function_declaration)
+  (method_declaration)
+  (func_literal)
+  (block)
+  (if_statement)
+  (for_statement)
+  (expression_switch_statement)
+  (type_switch_statement)
+] @local.scope
+
+(function_declaration
+  name: (identifier) @local.definition.function)
+
+(parameter_declaration name: (identifier) @local.definition.parameter)
+(variadic_parameter_declaration name: (identifier) @local.definition.parameter)
+
+(short_var_declaration left: (expression_list (identifier) @local.definition.var))
+(var_spec name: (identifier) @local.definition.var)
+(const_spec name: (identifier) @local.definition.constant)
+(range_clause left: (expression_list (identifier) @local.definition.var))
+(type_switch_statement alias: (expression_list (identifier) @local.definition.var))
+
+(identifier) @local.reference
@@ -0,0 +1,39 @@
+; Outline symbols for Go. `@definition.<kind>` marks a symbol and its kind,
+; `@name` its name; symbols nest inside the definitions that contain them.
+; When several patterns match the same node, the first one wins.
+
+(function_declaration
+  name: (identifier) @name) @definition.function
+
+(method_declaration
+  name: (field_identifier) @name) @definition.method
+
+(type_spec
+  name: (type_identifier) @name
+  type: (struct_type)) @definition.struct
+
+(type_spec
+  name: (type_identifier) @name
+  type: (interface_type)) @definition.interface
+
+(type_spec
+  name: (type_identifier) @name) @definition.type
+
+(type_alias
+  name: (type_identifier) @name) @definition.type
+
+(field_declaration
+  name: (field_identifier) @name) @definition.field
+
+(method_elem
+  name: (field_identifier) @name) @definition.method
+
+(const_spec
+  name: (identifier) @name) @definition.constant
+
+(var_spec
+  name: (identifier) @name) @definition.variable
+
+(short_var_declaration
+  left: (expression_list
+    (identifier) @name)) @definition.variable
@@ -0,0 +1,41 @@
+; Outline symbols for JavaScript and JSX. `@definition.<kind>` marks a symbol and
+; its kind, `@name` its name; symbols nest inside the definitions that contain
+; them. When several patterns match the same node, the first one wins.
+
+[(function_declaration
+  name: (identifier) @name)
+ (generator_function_declaration
+  name: (identifier) @name)] @definition.function
+
+(class_declaration
+  name: (identifier) @name) @definition.class
+
+(method_definition
+  name: (property_identifier) @name
+  (#eq? @name "constructor")) @definition.constructor
+
+(method_definition
+  name: (_) @name) @definition.method
+
+(field_definition
+  property: (_) @name) @definition.field
+
+(variable_declarator
+  name: (identifier) @name
+  value: [(arrow_function) (function_expression) (generator_function)]) @definition.function
+
+(variable_declarator
+  name: (identifier) @name
+  value: (class)) @definition.class
+
+(variable_declarator
+  name: (identifier) @name) @definition.variable
+
+(assignment_expression
+  left: (member_expression
+    property: (property_identifier) @name)
+  right: [(arrow_function) (function_expression)]) @definition.function
+
+(pair
+  key: (property_identifier) @name
+  value: [(arrow_function) (function_expression)]) @definition.method
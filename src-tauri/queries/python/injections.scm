@@ -0,0 +1,18 @@
+; Embedded languages in Python. No SQL or regex grammar is bundled, so these
+; take effect once one is installed (see grammars/installed.rs).
+
+; Strings that read like a SQL statement: cursor.execute("SELECT ...")
+((string_content) @injection.content
+  (#match? @injection.content "^\\s*(?i:select|insert|update|delete|create|alter|drop|with)\\s")
+  (#set! injection.language "sql"))
+
+; Regular expressions: re.compile(r"...")
+(call
+  function: (attribute
+    object: (identifier) @_module
+    attribute: (identifier) @_function)
+  arguments: (argument_list
+    . (string (string_content) @injection.content))
+  (#eq? @_module "re")
+  (#match? @_function "^(compile|search|match|fullmatch|findall|finditer|sub|subn|split)$")
+  (#set! injection.language "regex"))
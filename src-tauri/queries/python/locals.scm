@@ -0,0 +1,47 @@
+; Scopes and local definitions for Python, for go-to-definition and references
+; without a language server. A definition is visible in the innermost
+; `@local.scope` around it; a function or class name in the scope around its
+; own declaration.
+
+[
+  (module)
+  (function_definition)
+  (class_definition)
+  (lambda)
+  (list_comprehension)
+  (set_comprehension)
+  (dictionary_comprehension)
+  (generator_expression)
+] @local.scope
+
+(function_definition
+  name: (identifier) @local.definition.function)
+
+(class_definition
+  name: (identifier) @local.definition.class)
+
+(parameters (identifier) @local.definition.parameter)
+(lambda_parameters (identifier) @local.definition.parameter)
+(default_parameter name: (identifier) @local.definition.parameter)
+(typed_parameter (identifier) @local.definition.parameter)
+(typed_default_parameter name: (identifier) @local.definition.parameter)
+(list_splat_pattern (identifier) @local.definition.parameter)
+(dictionary_splat_pattern (identifier) @local.definition.parameter)
+
+(assignment left: (identifier) @local.definition.var)
+(assignment left: (pattern_list (identifier) @local.definition.var))
+(assignment left: (tuple_pattern (identifier) @local.definition.var))
+(augmented_assignment left: (identifier) @local.definition.var)
+(for_statement left: (identifier) @local.definition.var)
+(for_statement left: (pattern_list (identifier) @local.definition.var))
+(for_in_clause left: (identifier) @local.definition.var)
+(for_in_clause left: (pattern_list (identifier) @local.definition.var))
+(as_pattern alias: (as_pattern_target (identifier) @local.definition.var))
+(named_expression name: (identifier) @local.definition.var)
+(global_statement (identifier) @local.definition.var)
+
+(import_statement name: (dotted_name . (identifier) @local.definition.import))
+(import_from_statement name: (dotted_name (identifier) @local.definition.import))
+(aliased_import alias: (identifier) @local.definition.import)
+
+(identifier) @local.reference
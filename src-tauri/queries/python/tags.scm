@@ -0,0 +1,43 @@
+; Outline symbols for Python. `@definition.<kind>` marks a symbol and its kind,
+; `@name` its name; symbols nest inside the definitions that contain them.
+; When several patterns match the same node, the first one wins.
+
+(class_definition
+  body: (block
+    (function_definition
+      name: (identifier) @name) @definition.method))
+
+(class_definition
+  body: (block
+    (decorated_definition
+      definition: (function_definition
+        name: (identifier) @name) @definition.method)))
+
+(function_definition
+  name: (identifier) @name) @definition.function
+
+(class_definition
+  name: (identifier) @name) @definition.class
+
+(module
+  (expression_statement
+    (assignment
+      left: (identifier) @name) @definition.constant)
+  (#match? @name "^[A-Z][A-Z0-9_]*$"))
+
+(module
+  (expression_statement
+    (assignment
+      left: (identifier) @name) @definition.variable))
+
+(class_definition
+  body: (block
+    (expression_statement
+      (assignment
+        left: (identifier) @name) @definition.field)))
+
+(assignment
+  left: (attribute
+    object: (identifier) @_self
+    attribute: (identifier) @name)
+  (#eq? @_self "self")) @definition.property
@@ -0,0 +1,30 @@
+; Scopes and local definitions for Rust, for go-to-definition and references
+; without a language server. Items other than functions, and anything reached
+; through a path, are left to the outline and the workspace symbol index.
+
+[
+  (block)
+  (function_item)
+  (closure_expression)
+  (for_expression)
+  (if_expression)
+  (while_expression)
+  (match_arm)
+] @local.scope
+
+(function_item
+  name: (identifier) @local.definition.function)
+
+(parameter pattern: (identifier) @local.definition.parameter)
+(parameter pattern: (mut_pattern (identifier) @local.definition.parameter))
+(closure_parameters (identifier) @local.definition.parameter)
+(closure_parameters (parameter pattern: (identifier) @local.definition.parameter))
+
+(let_declaration pattern: (identifier) @local.definition.var)
+(let_declaration pattern: (mut_pattern (identifier) @local.definition.var))
+(let_condition pattern: (_ (identifier) @local.definition.var))
+(tuple_pattern (identifier) @local.definition.var)
+(for_expression pattern: (identifier) @local.definition.var)
+(match_pattern (identifier) @local.definition.var)
+
+(identifier) @local.reference
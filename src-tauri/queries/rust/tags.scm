@@ -0,0 +1,61 @@
+; Outline symbols for Rust. `@definition.<kind>` marks a symbol and its kind,
+; `@name` its name; symbols nest inside the definitions that contain them.
+; When several patterns match the same node, the first one wins.
+
+(mod_item
+  name: (identifier) @name) @definition.module
+
+(struct_item
+  name: (type_identifier) @name) @definition.struct
+
+(union_item
+  name: (type_identifier) @name) @definition.struct
+
+(enum_item
+  name: (type_identifier) @name) @definition.enum
+
+(enum_variant
+  name: (identifier) @name) @definition.enummember
+
+(field_declaration
+  name: (field_identifier) @name) @definition.field
+
+(trait_item
+  name: (type_identifier) @name) @definition.interface
+
+(impl_item
+  type: (_) @name) @definition.class
+
+(impl_item
+  body: (declaration_list
+    (function_item
+      name: (identifier) @name) @definition.method))
+
+(trait_item
+  body: (declaration_list
+    [(function_item
+      name: (identifier) @name)
+     (function_signature_item
+      name: (identifier) @name)] @definition.method))
+
+(function_item
+  name: (identifier) @name) @definition.function
+
+(macro_definition
+  name: (identifier) @name) @definition.macro
+
+(const_item
+  name: (identifier) @name) @definition.constant
+
+(static_item
+  name: (identifier) @name) @definition.variable
+
+(type_item
+  name: (type_identifier) @name) @definition.type
+
+(let_declaration
+  pattern: (identifier) @name) @definition.variable
+
+(let_declaration
+  pattern: (mut_pattern
+    (identifier) @name)) @definition.variable
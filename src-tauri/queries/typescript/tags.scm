@@ -0,0 +1,72 @@
+; Outline symbols for TypeScript and TSX. `@definition.<kind>` marks a symbol and
+; its kind, `@name` its name; symbols nest inside the definitions that contain
+; them. When several patterns match the same node, the first one wins.
+
+[(function_declaration
+  name: (identifier) @name)
+ (generator_function_declaration
+  name: (identifier) @name)
+ (function_signature
+  name: (identifier) @name)] @definition.function
+
+[(class_declaration
+  name: (type_identifier) @name)
+ (abstract_class_declaration
+  name: (type_identifier) @name)] @definition.class
+
+(interface_declaration
+  name: (type_identifier) @name) @definition.interface
+
+(type_alias_declaration
+  name: (type_identifier) @name) @definition.type
+
+(enum_declaration
+  name: (identifier) @name) @definition.enum
+
+(enum_body
+  [(property_identifier) @name
+   (enum_assignment
+    name: (_) @name)] @definition.enummember)
+
+(internal_module
+  name: (_) @name) @definition.namespace
+
+(module
+  name: (_) @name) @definition.module
+
+(method_definition
+  name: (property_identifier) @name
+  (#eq? @name "constructor")) @definition.constructor
+
+[(method_definition
+  name: (_) @name)
+ (method_signature
+  name: (_) @name)
+ (abstract_method_signature
+  name: (_) @name)] @definition.method
+
+(public_field_definition
+  name: (_) @name) @definition.field
+
+(property_signature
+  name: (_) @name) @definition.property
+
+(variable_declarator
+  name: (identifier) @name
+  value: [(arrow_function) (function_expression) (generator_function)]) @definition.function
+
+(variable_declarator
+  name: (identifier) @name
+  value: (class)) @definition.class
+
+(variable_declarator
+  name: (identifier) @name) @definition.variable
+
+(assignment_expression
+  left: (member_expression
+    property: (property_identifier) @name)
+  right: [(arrow_function) (function_expression)]) @definition.function
+
+(pair
+  key: (property_identifier) @name
+  value: [(arrow_function) (function_expression)]) @definition.method
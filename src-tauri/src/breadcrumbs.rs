@@ -0,0 +1,64 @@
+//! The symbols enclosing a position, outermost first, for the breadcrumb bar
+//! and sticky scroll headers. Read from the open document's outline, which is
+//! computed once per parse, so a cursor move costs a walk down the symbol tree.
+
+use serde::Serialize;
+
+use crate::documents::{self, Documents};
+use crate::error::CmdResult;
+use crate::queries::{Queries, QueryCache};
+use crate::{DocumentSymbol, Range};
+
+/// A `DocumentSymbol` without its children, with the same fields.
+#[derive(Debug, Clone, Serialize)]
+pub struct Breadcrumb {
+    pub name: String,
+    pub kind: String,
+    pub symbol_kind: u8,
+    pub detail: Option<String>,
+    pub modifiers: Vec<String>,
+    pub tags: Vec<u8>,
+    pub range: Range,
+    pub selection_range: Range,
+}
+
+/// The path to the innermost symbol whose range contains the 1-based position.
+pub fn symbol_path(symbols: &[DocumentSymbol], line_number: u32, column: u32) -> Vec<Breadcrumb> {
+    let position = (line_number, column);
+    let mut path = Vec::new();
+    let mut level = symbols;
+    while let Some(symbol) = level.iter().find(|symbol| {
+        let range = &symbol.range;
+        (range.start_line_number, range.start_column) <= position && position <= (range.end_line_number, range.end_column)
+    }) {
+        path.push(Breadcrumb {
+            name: symbol.name.clone(),
+            kind: symbol.kind.clone(),
+            symbol_kind: symbol.symbol_kind,
+            detail: symbol.detail.clone(),
+            modifiers: symbol.modifiers.clone(),
+            tags: symbol.tags.clone(),
+            range: symbol.range.clone(),
+            selection_range: symbol.selection_range.clone(),
+        });
+        level = &symbol.children;
+    }
+    path
+}
+
+/// Breadcrumbs at a position of an open document (1-based, in the outline's
+/// coordinates). For sticky scroll, pass the first visible line with column 1.
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_breadcrumbs(
+    file_path: String,
+    line_number: u32,
+    column: u32,
+    documents: tauri::State<'_, Documents>,
+    query_cache: tauri::State<'_, QueryCache>,
+    app_handle: tauri::AppHandle,
+) -> CmdResult<Vec<Breadcrumb>> {
+    let queries = Queries::new(&query_cache, &app_handle);
+    documents::with_document(&documents, &file_path, |document| {
+        symbol_path(document.outline(&queries), line_number, column)
+    })
+}
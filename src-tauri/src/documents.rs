@@ -0,0 +1,236 @@
+//! Parse trees of the documents open in the editor. Each document keeps its text
+//! and last tree; edits are applied to both and the tree is re-parsed
+//! incrementally, so a keystroke in a large file costs a partial re-parse instead
+//! of a full one. Outline and other syntax queries read the cached tree.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde::Deserialize;
+use tree_sitter::{InputEdit, Parser, Point, Tree};
+
+use crate::error::{AppError, CmdResult, ErrorCode};
+use crate::injections;
+use crate::queries::{Queries, QueryCache};
+use crate::syntax_diagnostics;
+use crate::{DocumentSymbol, SymbolQuery};
+
+pub type Documents = Arc<Mutex<HashMap<String, Document>>>;
+
+/// One change as the editor reports it: replace `from..to` (UTF-16 code units, as
+/// in CodeMirror) with `text`. A batch is applied in order, each change against
+/// the text the previous ones left.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DocumentChange {
+    pub from: usize,
+    pub to: usize,
+    pub text: String,
+}
+
+pub struct Document {
+    language_id: String,
+    version: i32,
+    source: String,
+    tree: Tree,
+    parser: Parser,
+    /// Outline of the current tree without custom queries, kept until the next
+    /// re-parse for callers that run on every cursor move.
+    outline: Option<Vec<DocumentSymbol>>,
+}
+
+impl Document {
+    pub fn open(language_id: &str, source: String, version: i32) -> CmdResult<Self> {
+        let mut parser = Parser::new();
+        parser.set_language(&crate::get_language(language_id)?)
+            .map_err(|e| AppError::internal(format!("Failed to set language: {}", e)))?;
+        let tree = parser.parse(&source, None)
+            .ok_or_else(|| AppError::internal("Failed to parse source code"))?;
+        Ok(Document { language_id: language_id.to_string(), version, source, tree, parser, outline: None })
+    }
+
+    pub fn language_id(&self) -> &str {
+        &self.language_id
+    }
+
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    /// Apply an editor change batch and re-parse what it touched.
+    pub fn apply_changes(&mut self, changes: &[DocumentChange]) -> CmdResult<()> {
+        for change in changes {
+            if change.from > change.to {
+                return Err(AppError::invalid_input(format!("Invalid change range {}..{}", change.from, change.to)));
+            }
+            let start = utf16_to_byte(&self.source, change.from)?;
+            let end = start + utf16_to_byte(&self.source[start..], change.to - change.from)?;
+            self.replace(start, end, &change.text);
+        }
+        self.reparse()
+    }
+
+    /// Bring the document to `source`, re-parsing only the span between the
+    /// common prefix and suffix of the old and new text.
+    pub fn set_text(&mut self, source: &str) -> CmdResult<()> {
+        let old = self.source.as_bytes();
+        let new = source.as_bytes();
+        let mut prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+        while !self.source.is_char_boundary(prefix) || !source.is_char_boundary(prefix) {
+            prefix -= 1;
+        }
+        let max_suffix = old.len().min(new.len()) - prefix;
+        let mut suffix = old.iter().rev().zip(new.iter().rev()).take(max_suffix).take_while(|(a, b)| a == b).count();
+        while !self.source.is_char_boundary(old.len() - suffix) || !source.is_char_boundary(new.len() - suffix) {
+            suffix -= 1;
+        }
+        if prefix == old.len() && prefix == new.len() {
+            return Ok(());
+        }
+        self.replace(prefix, old.len() - suffix, &source[prefix..new.len() - suffix]);
+        self.reparse()
+    }
+
+    fn replace(&mut self, start: usize, old_end: usize, text: &str) {
+        let start_position = point_at(&self.source, start);
+        let old_end_position = point_at(&self.source, old_end);
+        self.source.replace_range(start..old_end, text);
+        let new_end = start + text.len();
+        self.tree.edit(&InputEdit {
+            start_byte: start,
+            old_end_byte: old_end,
+            new_end_byte: new_end,
+            start_position,
+            old_end_position,
+            new_end_position: point_at(&self.source, new_end),
+        });
+    }
+
+    fn reparse(&mut self) -> CmdResult<()> {
+        self.tree = self.parser.parse(&self.source, Some(&self.tree))
+            .ok_or_else(|| AppError::internal("Failed to parse source code"))?;
+        self.outline = None;
+        Ok(())
+    }
+
+    /// The outline, including the symbols of embedded code.
+    pub fn symbols(&self, queries: &Vec<SymbolQuery>, query_files: &Queries) -> Vec<DocumentSymbol> {
+        injections::combined_symbols(&self.tree, &self.source, &self.language_id, queries, query_files)
+    }
+
+    /// The outline with default queries, computed once per parse.
+    pub fn outline(&mut self, query_files: &Queries) -> &[DocumentSymbol] {
+        if self.outline.is_none() {
+            self.outline = Some(self.symbols(&Vec::new(), query_files));
+        }
+        self.outline.as_deref().unwrap_or_default()
+    }
+}
+
+/// Byte offset of the `units`-th UTF-16 code unit of `text`.
+pub fn utf16_to_byte(text: &str, units: usize) -> CmdResult<usize> {
+    let mut seen = 0;
+    for (offset, c) in text.char_indices() {
+        if seen >= units {
+            return Ok(offset);
+        }
+        seen += c.len_utf16();
+    }
+    if seen == units {
+        Ok(text.len())
+    } else {
+        Err(AppError::invalid_input(format!("Change offset {} is past the end of the document", units)))
+    }
+}
+
+/// Tree-sitter position (row, byte column) of a byte offset.
+fn point_at(text: &str, offset: usize) -> Point {
+    let before = &text.as_bytes()[..offset];
+    let row = memchr::memchr_iter(b'\n', before).count();
+    let line_start = memchr::memrchr(b'\n', before).map_or(0, |i| i + 1);
+    Point::new(row, offset - line_start)
+}
+
+/// Run `f` on an open document.
+pub fn with_document<R>(documents: &Documents, file_path: &str, f: impl FnOnce(&mut Document) -> R) -> CmdResult<R> {
+    let mut documents = documents.lock().map_err(|_| AppError::lock_poisoned("documents"))?;
+    let document = documents.get_mut(file_path).ok_or_else(|| not_open(file_path))?;
+    Ok(f(document))
+}
+
+fn not_open(file_path: &str) -> AppError {
+    AppError::new(ErrorCode::NotFound, format!("Document is not open: {}", file_path)).with_path(file_path)
+}
+
+/// Start tracking a document with its full text. Re-opening replaces the old state.
+/// Syntax errors are published after this and after every edit (see `syntax_diagnostics`).
+#[tauri::command(rename_all = "snake_case")]
+pub fn open_document(
+    file_path: String,
+    language_id: String,
+    source_code: String,
+    version: Option<i32>,
+    documents: tauri::State<'_, Documents>,
+    app_handle: tauri::AppHandle,
+) -> CmdResult<()> {
+    let document = Document::open(&language_id, source_code, version.unwrap_or(0))?;
+    syntax_diagnostics::publish(&app_handle, &file_path, &document);
+    let mut documents = documents.lock().map_err(|_| AppError::lock_poisoned("documents"))?;
+    documents.insert(file_path, document);
+    Ok(())
+}
+
+/// Apply the changes that produced `version`. Fails with `Conflict` when a batch
+/// was missed, and `NotFound` when the document is not open; the editor then
+/// re-opens it with its full text.
+#[tauri::command(rename_all = "snake_case")]
+pub fn edit_document(
+    file_path: String,
+    version: i32,
+    changes: Vec<DocumentChange>,
+    documents: tauri::State<'_, Documents>,
+    app_handle: tauri::AppHandle,
+) -> CmdResult<()> {
+    let mut documents = documents.lock().map_err(|_| AppError::lock_poisoned("documents"))?;
+    let document = documents.get_mut(&file_path).ok_or_else(|| not_open(&file_path))?;
+    if version != document.version + 1 {
+        return Err(AppError::new(
+            ErrorCode::Conflict,
+            format!("Document is at version {}, got changes for version {}", document.version, version),
+        ).with_path(&file_path));
+    }
+    if let Err(e) = document.apply_changes(&changes) {
+        // Part of the batch may already be applied; make the editor start over
+        documents.remove(&file_path);
+        return Err(e.with_path(&file_path));
+    }
+    document.version = version;
+    syntax_diagnostics::publish(&app_handle, &file_path, document);
+    Ok(())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn close_document(file_path: String, documents: tauri::State<'_, Documents>) -> CmdResult<bool> {
+    let mut documents = documents.lock().map_err(|_| AppError::lock_poisoned("documents"))?;
+    Ok(documents.remove(&file_path).is_some())
+}
+
+/// Outline of an open document from its cached tree.
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_document_symbols(
+    file_path: String,
+    queries: Option<Vec<SymbolQuery>>,
+    documents: tauri::State<'_, Documents>,
+    query_cache: tauri::State<'_, QueryCache>,
+    app_handle: tauri::AppHandle,
+) -> CmdResult<Vec<DocumentSymbol>> {
+    let query_files = Queries::new(&query_cache, &app_handle);
+    with_document(&documents, &file_path, |document| document.symbols(&queries.unwrap_or_default(), &query_files))
+}
@@ -0,0 +1,161 @@
+use serde::Serialize;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+/// Stable, machine-readable error codes shared with the frontend.
+///
+/// The serialized names are part of the command API: the frontend matches on
+/// `error.code` instead of the English `message`, so never rename a variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ErrorCode {
+    NotFound,
+    AlreadyExists,
+    PermissionDenied,
+    Conflict,
+    NotADirectory,
+    IsADirectory,
+    InvalidPattern,
+    InvalidInput,
+    Unsupported,
+    ProcessFailed,
+    NotARepository,
+    SessionNotFound,
+    Network,
+    Io,
+    Internal,
+}
+
+/// Error returned by every Tauri command.
+///
+/// Serializes to `{ code, message, path?, osError?, exitCode? }`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os_error: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+}
+
+pub type CmdResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        AppError {
+            code,
+            message: message.into(),
+            path: None,
+            os_error: None,
+            exit_code: None,
+        }
+    }
+
+    pub fn with_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.path = Some(path.as_ref().to_string_lossy().to_string());
+        self
+    }
+
+    /// Map an I/O error to its stable code, keeping the OS errno and the path involved.
+    pub fn io(err: io::Error, context: &str, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let mut error = AppError::new(
+            code_for_io_kind(err.kind()),
+            format!("{} {}: {}", context, path.display(), err),
+        )
+        .with_path(path);
+        error.os_error = err.raw_os_error();
+        error
+    }
+
+    pub fn not_found(message: impl Into<String>, path: impl AsRef<Path>) -> Self {
+        AppError::new(ErrorCode::NotFound, message).with_path(path)
+    }
+
+    pub fn already_exists(message: impl Into<String>, path: impl AsRef<Path>) -> Self {
+        AppError::new(ErrorCode::AlreadyExists, message).with_path(path)
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        AppError::new(ErrorCode::InvalidInput, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        AppError::new(ErrorCode::Internal, message)
+    }
+
+    /// A spawned process ran but exited unsuccessfully; `stderr` becomes the message.
+    pub fn process_failed(output: &std::process::Output) -> Self {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let message = if stderr.is_empty() {
+            format!("Process exited with {}", output.status)
+        } else {
+            stderr
+        };
+        let mut error = AppError::new(ErrorCode::ProcessFailed, message);
+        error.exit_code = output.status.code();
+        error
+    }
+
+    /// A process could not be spawned at all (missing binary, bad cwd, ...).
+    pub fn spawn(err: io::Error, program: &str) -> Self {
+        let mut error = AppError::new(
+            match err.kind() {
+                io::ErrorKind::NotFound => ErrorCode::NotFound,
+                io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+                _ => ErrorCode::ProcessFailed,
+            },
+            format!("Failed to execute {}: {}", program, err),
+        );
+        error.os_error = err.raw_os_error();
+        error
+    }
+
+    pub fn lock_poisoned(what: &str) -> Self {
+        AppError::internal(format!("Failed to lock {}", what))
+    }
+}
+
+fn code_for_io_kind(kind: io::ErrorKind) -> ErrorCode {
+    match kind {
+        io::ErrorKind::NotFound => ErrorCode::NotFound,
+        io::ErrorKind::AlreadyExists => ErrorCode::AlreadyExists,
+        io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+        io::ErrorKind::NotADirectory => ErrorCode::NotADirectory,
+        io::ErrorKind::IsADirectory => ErrorCode::IsADirectory,
+        io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => ErrorCode::InvalidInput,
+        _ => ErrorCode::Io,
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<io::Error> for AppError {
+    fn from(err: io::Error) -> Self {
+        let mut error = AppError::new(code_for_io_kind(err.kind()), err.to_string());
+        error.os_error = err.raw_os_error();
+        error
+    }
+}
+
+impl From<regex::Error> for AppError {
+    fn from(err: regex::Error) -> Self {
+        AppError::new(ErrorCode::InvalidPattern, format!("Invalid regex pattern: {}", err))
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(err: reqwest::Error) -> Self {
+        AppError::new(ErrorCode::Network, format!("Request failed: {}", err))
+    }
+}
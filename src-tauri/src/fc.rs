@@ -1,8 +1,9 @@
 use serde::{Deserialize, Serialize};
-use serde_json::Result;
 use std::fs;
 use std::path::Path;
 
+use crate::error::{AppError, CmdResult};
+
 // #[warn(dead_code)]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct FileInfo {
@@ -21,17 +22,19 @@ pub struct Post {
     author: String,
 }
 
-pub fn read_directory(dir_path: &str) -> String {
+pub fn read_directory(dir_path: &str) -> CmdResult<String> {
     let new_path = Path::new(dir_path);
     println!("new path {:?}", new_path);
-    let paths = fs::read_dir(new_path).unwrap();
+    let paths = fs::read_dir(new_path)
+        .map_err(|e| AppError::io(e, "Failed to read directory", new_path))?;
 
     let mut files: Vec<FileInfo> = Vec::new();
 
     for path in paths {
-        let path_unwrap = path.unwrap();
-        let meta = path_unwrap.metadata();
-        let meta_unwrap = meta.unwrap();
+        let path_unwrap = path.map_err(|e| AppError::io(e, "Failed to read directory entry in", new_path))?;
+        let meta_unwrap = path_unwrap
+            .metadata()
+            .map_err(|e| AppError::io(e, "Failed to read metadata of", path_unwrap.path()))?;
 
         let mut kind = String::from("file");
 
@@ -39,10 +42,7 @@ pub fn read_directory(dir_path: &str) -> String {
             kind = String::from("directory");
         }
 
-        let filename = match path_unwrap.file_name().into_string() {
-            Ok(str) => str,
-            Err(_) => String::from("ERROR"),
-        };
+        let filename = path_unwrap.file_name().to_string_lossy().to_string();
 
         let file_path = dir_path.to_owned() + &filename;
 
@@ -55,46 +55,33 @@ pub fn read_directory(dir_path: &str) -> String {
         files.push(new_file_info);
     }
 
-    let files_str = match serde_json::to_string(&files) {
-        Ok(str) => str,
-        Err(error) => panic!("Problem opening the file: {:?}", error),
-    };
-
     // println!("file {:?}", files_str);
 
-    files_str
+    serde_json::to_string(&files)
+        .map_err(|e| AppError::internal(format!("Failed to serialize directory listing: {}", e)))
 }
 
-pub fn read_file(path: &str) -> String {
-    let contents = fs::read_to_string(path).expect("ERROR");
-    contents
+pub fn read_file(path: &str) -> CmdResult<String> {
+    fs::read_to_string(path).map_err(|e| AppError::io(e, "Failed to read file", path))
 }
 
 // update file and create new file
-pub fn write_file(path: &str, content: &str) -> String {
+pub fn write_file(path: &str, content: &str) -> CmdResult<()> {
     let file_path = Path::new(path);
-    let result = match fs::write(file_path, content) {
-        Ok(()) => String::from("OK"),
-        Err(_err) => String::from("ERROR"),
-    };
-
-    result
+    fs::write(file_path, content).map_err(|e| AppError::io(e, "Failed to write file", file_path))
 }
 
-pub fn create_directory(path: &str) -> Result<()> {
+pub fn create_directory(path: &str) -> CmdResult<()> {
     let dir_path = Path::new(path);
-    let _ = fs::create_dir(dir_path);
-    Ok(())
+    fs::create_dir(dir_path).map_err(|e| AppError::io(e, "Failed to create directory", dir_path))
 }
 
-pub fn remove_file(path: &str) -> Result<()> {
+pub fn remove_file(path: &str) -> CmdResult<()> {
     let file_path = Path::new(path);
-    let _ = fs::remove_file(file_path);
-    Ok(())
+    fs::remove_file(file_path).map_err(|e| AppError::io(e, "Failed to delete file", file_path))
 }
 
-pub fn remove_folder(path: &str) -> Result<()> {
+pub fn remove_folder(path: &str) -> CmdResult<()> {
     let folder_path = Path::new(path);
-    let _ = fs::remove_dir_all(folder_path);
-    Ok(())
+    fs::remove_dir_all(folder_path).map_err(|e| AppError::io(e, "Failed to delete directory", folder_path))
 }
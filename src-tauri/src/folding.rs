@@ -0,0 +1,171 @@
+//! Folding ranges from the syntax tree, so code folds without a language server.
+//!
+//! A grammar's `folds.scm` decides when there is one (an override or an
+//! installed grammar's; see [`crate::queries`]): `@fold` marks a foldable node
+//! and `@fold.comment`, `@fold.imports` or `@fold.region` give it a kind.
+//! Otherwise blocks are found by shape: nodes closed by a bracket or `end`,
+//! indented bodies such as Python's, elements and sections, plus runs of
+//! comments and of imports. Embedded code folds the same way.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Node, Query, QueryCursor, Tree};
+
+use crate::documents::{self, Documents};
+use crate::error::CmdResult;
+use crate::injections;
+use crate::queries::{Queries, QueryCache};
+use crate::{node_to_range, Range};
+
+/// A foldable region. As with LSP folding ranges, the start line stays visible
+/// and the editor collapses what follows it up to the end line.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FoldingRange {
+    pub range: Range,
+    /// `comment`, `imports` or `region`; `None` for code.
+    pub kind: Option<String>,
+}
+
+/// Last tokens that close a block.
+const CLOSERS: &[&str] = &["}", "]", ")", "end", "fi", "done", "esac", "endif", "endfunction"];
+
+/// Nodes that fold as a whole without brackets.
+const BLOCK_KINDS: &[&str] = &[
+    "element", "script_element", "style_element", "section", "fenced_code_block",
+    "block_mapping_pair", "block_sequence_item", "table", "table_array_element",
+    "heredoc_body", "template_string",
+];
+
+const IMPORT_KINDS: &[&str] = &[
+    "import_statement", "import_from_statement", "import_declaration", "use_declaration",
+    "preproc_include", "using_directive", "namespace_use_declaration", "extern_crate_declaration",
+];
+
+/// The folding ranges of a parsed document, embedded code included, by start line.
+pub fn folding_ranges(tree: &Tree, source: &str, language_id: &str, queries: &Queries) -> Vec<FoldingRange> {
+    let mut folds = tree_folds(tree, source, language_id, queries);
+    for layer in injections::parse_layers(tree, source, language_id, queries, None) {
+        folds.extend(tree_folds(&layer.tree, source, &layer.language_id, queries));
+    }
+    // Nodes that take their newline along, like Rust's line comments, end at the
+    // start of the next line; that line is not part of the fold
+    let line_lengths: Vec<usize> = source.split('\n').map(str::len).collect();
+    for fold in &mut folds {
+        let range = &mut fold.range;
+        if range.end_column == 1 && range.end_line_number > range.start_line_number {
+            range.end_line_number -= 1;
+            range.end_column = line_lengths[range.end_line_number as usize - 1] as u32 + 1;
+        }
+    }
+    folds.retain(|fold| fold.range.end_line_number > fold.range.start_line_number);
+    // One fold per start line, the widest
+    folds.sort_by_key(|fold| (fold.range.start_line_number, std::cmp::Reverse(fold.range.end_line_number)));
+    folds.dedup_by_key(|fold| fold.range.start_line_number);
+    folds
+}
+
+fn tree_folds(tree: &Tree, source: &str, language_id: &str, queries: &Queries) -> Vec<FoldingRange> {
+    match queries.get(language_id, "folds.scm") {
+        Some(query) => query_folds(&query, tree.root_node(), source),
+        None => {
+            let mut folds = Vec::new();
+            heuristic_folds(tree.root_node(), &mut folds);
+            folds
+        }
+    }
+}
+
+fn query_folds(query: &Query, root: Node, source: &str) -> Vec<FoldingRange> {
+    let mut folds = Vec::new();
+    let mut seen = HashSet::new();
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, root, source.as_bytes());
+    while let Some(m) = matches.next() {
+        for capture in m.captures {
+            let Some(kind) = query.capture_names()[capture.index as usize].strip_prefix("fold") else { continue };
+            if !seen.insert(capture.node.id()) {
+                continue;
+            }
+            let kind = kind.strip_prefix('.').filter(|kind| !kind.is_empty()).map(str::to_string);
+            folds.push(FoldingRange { range: node_to_range(capture.node), kind });
+        }
+    }
+    folds
+}
+
+fn heuristic_folds(node: Node, folds: &mut Vec<FoldingRange>) {
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+
+    let mut index = 0;
+    while index < children.len() {
+        let child = children[index];
+        // Runs of line comments and of imports fold together
+        let group_kind = if child.kind().contains("comment") {
+            Some("comment")
+        } else if IMPORT_KINDS.contains(&child.kind()) {
+            Some("imports")
+        } else {
+            None
+        };
+        if let Some(group_kind) = group_kind {
+            let mut last = child;
+            while let Some(next) = children.get(index + 1) {
+                let same_group = match group_kind {
+                    "comment" => next.kind().contains("comment"),
+                    _ => IMPORT_KINDS.contains(&next.kind()),
+                };
+                if !same_group || next.start_position().row > last.end_position().row + 1 {
+                    break;
+                }
+                last = *next;
+                index += 1;
+            }
+            let mut range = node_to_range(child);
+            let end = node_to_range(last);
+            range.end_line_number = end.end_line_number;
+            range.end_column = end.end_column;
+            folds.push(FoldingRange { range, kind: Some(group_kind.to_string()) });
+        } else if let Some(range) = block_range(child) {
+            folds.push(FoldingRange { range, kind: None });
+        }
+        heuristic_folds(child, folds);
+        index += 1;
+    }
+}
+
+/// The range to fold for a block-shaped node.
+fn block_range(node: Node) -> Option<Range> {
+    let closed = node.child(node.child_count().checked_sub(1)?)
+        .is_some_and(|last| !last.is_named() && CLOSERS.contains(&last.kind()));
+    if (closed || BLOCK_KINDS.contains(&node.kind())) && node.end_position().row > node.start_position().row {
+        return Some(node_to_range(node));
+    }
+    // An indented body after a `:` folds from its header, like Python's blocks
+    let after_colon = node.prev_sibling().is_some_and(|previous| previous.kind() == ":");
+    if node.kind() == "block" && after_colon {
+        let mut range = node_to_range(node.parent()?);
+        let body = node_to_range(node);
+        range.end_line_number = body.end_line_number;
+        range.end_column = body.end_column;
+        return Some(range);
+    }
+    None
+}
+
+/// Folding ranges of an open document from its cached tree.
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_folding_ranges(
+    file_path: String,
+    documents: tauri::State<'_, Documents>,
+    query_cache: tauri::State<'_, QueryCache>,
+    app_handle: tauri::AppHandle,
+) -> CmdResult<Vec<FoldingRange>> {
+    let queries = Queries::new(&query_cache, &app_handle);
+    documents::with_document(&documents, &file_path, |document| {
+        folding_ranges(document.tree(), document.source(), document.language_id(), &queries)
+    })
+}
@@ -0,0 +1,103 @@
+//! Which tree-sitter grammar parses which language id, for features that parse
+//! many files at once and should resolve each grammar only once. Besides the
+//! bundled grammars there are the ones installed into the app data directory.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use tauri::{AppHandle, Manager};
+use tree_sitter::Language;
+
+use crate::error::{AppError, CmdResult};
+
+pub mod installed;
+
+use installed::GrammarInfo;
+
+/// Language ids with a bundled grammar, one per grammar.
+const BUNDLED_LANGUAGES: &[&str] = &[
+    "javascript", "typescript", "typescriptreact", "python", "rust", "go",
+    "c", "cpp", "java", "csharp", "ruby", "php", "shellscript", "lua",
+    "html", "css", "json", "yaml", "toml", "markdown",
+];
+
+/// Grammars that parse several language ids: `javascript` also covers JSX,
+/// `json` also covers JSON with comments.
+pub fn grammar_family(language_id: &str) -> &str {
+    match language_id {
+        "javascript" | "javascriptreact" | "jsx" => "javascript",
+        "typescriptreact" | "tsx" => "tsx",
+        "shellscript" | "bash" | "sh" => "shellscript",
+        "json" | "jsonc" => "json",
+        other => other,
+    }
+}
+
+/// Every bundled and installed grammar, loaded up front and shareable between
+/// worker threads.
+pub struct Grammars {
+    by_family: HashMap<String, Language>,
+}
+
+impl Grammars {
+    pub fn load() -> Self {
+        let mut by_family: HashMap<String, Language> = BUNDLED_LANGUAGES.iter()
+            .filter_map(|id| Some((grammar_family(id).to_string(), crate::get_language(id).ok()?)))
+            .collect();
+        by_family.extend(installed::languages());
+        Grammars { by_family }
+    }
+
+    /// The grammar for a file's language id, if one is bundled.
+    pub fn for_language(&self, language_id: &str) -> Option<&Language> {
+        self.by_family.get(grammar_family(language_id))
+    }
+}
+
+/// The grammars loaded by [`shared`], until the installed grammars change.
+static SHARED: RwLock<Option<Arc<Grammars>>> = RwLock::new(None);
+
+/// Every grammar, loaded once and reused by each scan that needs them.
+pub fn shared() -> Arc<Grammars> {
+    if let Some(grammars) = SHARED.read().ok().and_then(|shared| shared.clone()) {
+        return grammars;
+    }
+    let grammars = Arc::new(Grammars::load());
+    if let Ok(mut shared) = SHARED.write() {
+        *shared = Some(grammars.clone());
+    }
+    grammars
+}
+
+/// Drop the shared grammars so the next [`shared`] sees newly installed ones.
+fn forget_shared() {
+    if let Ok(mut shared) = SHARED.write() {
+        *shared = None;
+    }
+}
+
+/// `<app data>/grammars`, where compiled grammars are installed.
+fn grammars_dir(app_handle: &AppHandle) -> CmdResult<PathBuf> {
+    app_handle.path().app_data_dir()
+        .map(|dir| dir.join("grammars"))
+        .map_err(|e| AppError::internal(format!("Failed to resolve app data directory: {}", e)))
+}
+
+/// Load the installed grammars at startup. Problems are logged; the grammar
+/// list shows them per grammar.
+pub fn load_installed(app_handle: &AppHandle) {
+    match grammars_dir(app_handle) {
+        Ok(dir) => {
+            installed::scan(&dir);
+        }
+        Err(e) => eprintln!("[GRAMMARS DEBUG] {}", e),
+    }
+}
+
+/// Rescan the grammars directory and list what is installed, with each
+/// grammar's ABI version and whether this build can use it.
+#[tauri::command(rename_all = "snake_case")]
+pub fn list_installed_grammars(app_handle: AppHandle) -> CmdResult<Vec<GrammarInfo>> {
+    Ok(installed::scan(&grammars_dir(&app_handle)?))
+}
@@ -0,0 +1,284 @@
+//! Grammars installed at runtime: compiled tree-sitter parsers in the app data
+//! directory, so a niche language does not need a rebuild of the editor.
+//!
+//! ```text
+//! grammars/
+//!   nim/
+//!     nim.so              exports `tree_sitter_nim` (.dylib on macOS, .dll on Windows)
+//!     grammar.json        optional, see `Manifest`
+//!     queries/tags.scm    optional query files: tags, highlights, injections, ...
+//!   zig.so                a bare library works too, with its queries in `zig/queries/`
+//! ```
+//!
+//! Libraries stay loaded for the life of the process, since every tree and query
+//! built from them points into their tables. A rebuilt library therefore needs a
+//! restart; newly added ones are picked up by a rescan.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use tree_sitter::{Language, LANGUAGE_VERSION, MIN_COMPATIBLE_LANGUAGE_VERSION};
+use tree_sitter_language::LanguageFn;
+
+use crate::languages::LanguageEntry;
+
+/// `grammar.json`; every field falls back to something derived from the grammar name.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Manifest {
+    language_id: Option<String>,
+    name: Option<String>,
+    extensions: Vec<String>,
+    filenames: Vec<String>,
+    /// Exported constructor, `tree_sitter_<grammar>` by default.
+    symbol: Option<String>,
+}
+
+/// What the grammar list shows for one installed grammar.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrammarInfo {
+    pub name: String,
+    pub language_id: String,
+    pub display_name: String,
+    pub library: Option<PathBuf>,
+    pub symbol: String,
+    pub extensions: Vec<String>,
+    pub filenames: Vec<String>,
+    /// Query files found next to the library, e.g. `tags.scm`.
+    pub queries: Vec<String>,
+    pub abi_version: Option<usize>,
+    pub min_abi_version: usize,
+    pub max_abi_version: usize,
+    pub compatible: bool,
+    /// Why the grammar is not used, when it is not.
+    pub error: Option<String>,
+}
+
+struct InstalledGrammar {
+    info: GrammarInfo,
+    queries_dir: Option<PathBuf>,
+    /// Set once the library is open, even if the grammar turns out to be unusable.
+    loaded: Option<Language>,
+}
+
+impl InstalledGrammar {
+    /// The grammar to parse with: loaded, ABI compatible and not shadowed.
+    fn usable(&self) -> Option<&Language> {
+        self.loaded.as_ref().filter(|_| self.info.error.is_none())
+    }
+}
+
+static INSTALLED: RwLock<Vec<InstalledGrammar>> = RwLock::new(Vec::new());
+
+/// Scan `dir` and make what it holds the installed grammars. Libraries loaded by
+/// an earlier scan are reused rather than opened again.
+pub fn scan(dir: &Path) -> Vec<GrammarInfo> {
+    let mut found: BTreeMap<String, (Option<PathBuf>, Option<PathBuf>)> = BTreeMap::new();
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            let Some(name) = path.file_name().and_then(|name| name.to_str()).map(grammar_name) else { continue };
+            let slot = found.entry(name).or_default();
+            slot.1 = Some(path.clone());
+            if let Some(library) = library_in(&path) {
+                slot.0 = Some(library);
+            }
+        } else if is_library(&path) {
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()).map(grammar_name) else { continue };
+            found.entry(name).or_default().0.get_or_insert(path);
+        }
+    }
+
+    let mut installed = match INSTALLED.write() {
+        Ok(installed) => installed,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let grammars: Vec<InstalledGrammar> = found.into_iter()
+        .map(|(name, (library, grammar_dir))| {
+            let queries_dir = Some(grammar_dir.clone().unwrap_or_else(|| dir.join(&name)).join("queries"))
+                .filter(|queries| queries.is_dir());
+            load(name, library, grammar_dir.as_deref(), queries_dir, &installed)
+        })
+        .collect();
+    for grammar in &grammars {
+        match &grammar.info.error {
+            Some(error) => eprintln!("[GRAMMARS DEBUG] {} not loaded: {}", grammar.info.name, error),
+            None => eprintln!("[GRAMMARS DEBUG] Loaded {} for {}", grammar.info.name, grammar.info.language_id),
+        }
+    }
+    *installed = grammars;
+    let infos = installed.iter().map(|grammar| grammar.info.clone()).collect();
+    drop(installed);
+    super::forget_shared();
+    infos
+}
+
+fn load(
+    name: String,
+    library: Option<PathBuf>,
+    grammar_dir: Option<&Path>,
+    queries_dir: Option<PathBuf>,
+    previous: &[InstalledGrammar],
+) -> InstalledGrammar {
+    let mut error = None;
+    let manifest = match grammar_dir.map(|dir| dir.join("grammar.json")).filter(|path| path.is_file()) {
+        Some(path) => fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<Manifest>(&content).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| {
+                error = Some(format!("Invalid grammar.json: {}", e));
+                Manifest::default()
+            }),
+        None => Manifest::default(),
+    };
+    let language_id = manifest.language_id.unwrap_or_else(|| name.clone());
+    let symbol = manifest.symbol.unwrap_or_else(|| format!("tree_sitter_{}", name.replace('-', "_")));
+    let mut queries: Vec<String> = queries_dir.iter()
+        .flat_map(|dir| fs::read_dir(dir).into_iter().flatten().flatten())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|file| file.ends_with(".scm"))
+        .collect();
+    queries.sort();
+
+    let reused = previous.iter()
+        .find(|grammar| grammar.loaded.is_some() && grammar.info.library == library && grammar.info.symbol == symbol);
+    let loaded = match (reused, &library) {
+        (Some(grammar), _) => grammar.loaded.clone(),
+        (None, Some(path)) => match open_library(path, &symbol) {
+            Ok(language) => Some(language),
+            Err(e) => {
+                error.get_or_insert(e);
+                None
+            }
+        },
+        (None, None) => {
+            error.get_or_insert(format!("No .{} library found", std::env::consts::DLL_EXTENSION));
+            None
+        }
+    };
+
+    let abi_version = loaded.as_ref().map(Language::abi_version);
+    let compatible = abi_version.is_some_and(|abi| (MIN_COMPATIBLE_LANGUAGE_VERSION..=LANGUAGE_VERSION).contains(&abi));
+    if let (Some(abi), false) = (abi_version, compatible) {
+        error.get_or_insert(format!(
+            "ABI version {} is not supported (supported: {} to {})",
+            abi, MIN_COMPATIBLE_LANGUAGE_VERSION, LANGUAGE_VERSION
+        ));
+    }
+    if super::BUNDLED_LANGUAGES.iter().any(|bundled| super::grammar_family(bundled) == super::grammar_family(&language_id)) {
+        error.get_or_insert(format!("The bundled grammar for {} takes precedence", language_id));
+    }
+
+    InstalledGrammar {
+        info: GrammarInfo {
+            display_name: manifest.name.unwrap_or_else(|| language_id.clone()),
+            name,
+            language_id,
+            library,
+            symbol,
+            extensions: manifest.extensions,
+            filenames: manifest.filenames,
+            queries,
+            abi_version,
+            min_abi_version: MIN_COMPATIBLE_LANGUAGE_VERSION,
+            max_abi_version: LANGUAGE_VERSION,
+            compatible,
+            error,
+        },
+        queries_dir,
+        loaded,
+    }
+}
+
+/// Open a parser library and call its constructor.
+fn open_library(path: &Path, symbol: &str) -> Result<Language, String> {
+    // SAFETY: loading runs the library's initializers and the constructor is
+    // trusted to have the `const TSLanguage *(void)` signature of every
+    // tree-sitter parser; that is the contract of the grammars directory.
+    unsafe {
+        let library = libloading::Library::new(path).map_err(|e| e.to_string())?;
+        let constructor = *library
+            .get::<unsafe extern "C" fn() -> *const ()>(symbol.as_bytes())
+            .map_err(|_| format!("The library does not export {}", symbol))?;
+        if constructor().is_null() {
+            return Err(format!("{} returned no language", symbol));
+        }
+        // Trees and queries point into the library, so it is never unloaded
+        std::mem::forget(library);
+        Ok(Language::new(LanguageFn::from_raw(constructor)))
+    }
+}
+
+fn is_library(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == std::env::consts::DLL_EXTENSION)
+}
+
+/// The parser library inside a grammar directory.
+fn library_in(dir: &Path) -> Option<PathBuf> {
+    let mut libraries: Vec<PathBuf> = fs::read_dir(dir).ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_library(path))
+        .collect();
+    libraries.sort();
+    libraries.into_iter().next()
+}
+
+/// `libtree-sitter-nim` and `tree_sitter_nim` are both the `nim` grammar.
+fn grammar_name(stem: &str) -> String {
+    let stem = stem.strip_prefix("lib").unwrap_or(stem);
+    let stem = stem.strip_prefix("tree-sitter-").or_else(|| stem.strip_prefix("tree_sitter_")).unwrap_or(stem);
+    stem.to_string()
+}
+
+fn read_installed<R>(f: impl FnOnce(&[InstalledGrammar]) -> R) -> R {
+    match INSTALLED.read() {
+        Ok(installed) => f(&installed),
+        Err(poisoned) => f(&poisoned.into_inner()),
+    }
+}
+
+/// The installed grammar for a language id, if one loaded and is usable.
+pub fn language(language_id: &str) -> Option<Language> {
+    read_installed(|installed| {
+        installed.iter()
+            .find(|grammar| grammar.info.language_id == language_id)
+            .and_then(|grammar| grammar.usable().cloned())
+    })
+}
+
+/// Every usable installed grammar by language id.
+pub fn languages() -> Vec<(String, Language)> {
+    read_installed(|installed| {
+        installed.iter()
+            .filter_map(|grammar| Some((grammar.info.language_id.clone(), grammar.usable()?.clone())))
+            .collect()
+    })
+}
+
+/// A query file shipped with an installed grammar, e.g. `tags.scm`.
+pub fn query_path(language_id: &str, file_name: &str) -> Option<PathBuf> {
+    read_installed(|installed| {
+        let grammar = installed.iter().find(|grammar| grammar.info.language_id == language_id && grammar.usable().is_some())?;
+        Some(grammar.queries_dir.as_ref()?.join(file_name)).filter(|path| path.is_file())
+    })
+}
+
+/// Registry entries for the languages installed grammars add.
+pub fn language_entries() -> Vec<LanguageEntry> {
+    read_installed(|installed| {
+        installed.iter()
+            .filter(|grammar| grammar.usable().is_some())
+            .map(|grammar| LanguageEntry {
+                id: grammar.info.language_id.clone(),
+                name: grammar.info.display_name.clone(),
+                extensions: grammar.info.extensions.clone(),
+                filenames: grammar.info.filenames.clone(),
+            })
+            .collect()
+    })
+}
@@ -0,0 +1,170 @@
+//! Syntax highlighting from tree-sitter `highlights.scm` queries, for the
+//! languages the backend has grammars for. Tokens carry capture names
+//! (`keyword`, `string.special`, `function.method`, ...) and the frontend maps
+//! them to theme colors, so the editor, previews and exports color code alike.
+//!
+//! Embedded languages are highlighted with their own queries on top of the
+//! host's (see [`crate::injections`]). Where captures overlap, the narrower
+//! node wins, then the later pattern, as in tree-sitter's own highlighter.
+
+use std::cmp::Reverse;
+use std::ops::Range;
+
+use serde::Serialize;
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Parser, Query, QueryCursor, Tree};
+
+use crate::documents::{self, Documents};
+use crate::error::{AppError, CmdResult};
+use crate::injections;
+use crate::queries::{Queries, QueryCache};
+
+/// A run of text with one capture. `from` and `to` are UTF-16 offsets in the
+/// document, as in CodeMirror.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HighlightToken {
+    pub from: usize,
+    pub to: usize,
+    pub capture: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentHighlights {
+    /// The document version the tokens were computed for.
+    pub version: i32,
+    pub tokens: Vec<HighlightToken>,
+}
+
+/// Highlight `byte_range` of a parsed source. Empty when the language has no
+/// highlights query.
+pub fn highlight(tree: &Tree, source: &str, language_id: &str, queries: &Queries, byte_range: Range<usize>) -> Vec<HighlightToken> {
+    let mut painter = Painter {
+        source,
+        range: byte_range.clone(),
+        paint: vec![None; byte_range.len()],
+        captures: Vec::new(),
+    };
+    if let Some(query) = queries.get(language_id, "highlights.scm") {
+        painter.paint_layer(tree, &query);
+    }
+    for layer in injections::parse_layers(tree, source, language_id, queries, Some(byte_range)) {
+        if let Some(query) = queries.get(&layer.language_id, "highlights.scm") {
+            painter.paint_layer(&layer.tree, &query);
+        }
+    }
+    painter.tokens()
+}
+
+/// The capture of every byte in the range, painted layer by layer.
+struct Painter<'a> {
+    source: &'a str,
+    range: Range<usize>,
+    /// Index into `captures` per byte of `range`.
+    paint: Vec<Option<usize>>,
+    captures: Vec<String>,
+}
+
+impl Painter<'_> {
+    fn paint_layer(&mut self, tree: &Tree, query: &Query) {
+        let mut spans = Vec::new();
+        let mut cursor = QueryCursor::new();
+        cursor.set_byte_range(self.range.clone());
+        let mut matches = cursor.matches(query, tree.root_node(), self.source.as_bytes());
+        while let Some(m) = matches.next() {
+            for capture in m.captures {
+                let name = query.capture_names()[capture.index as usize];
+                // `_name` captures only serve predicates
+                if !name.starts_with('_') {
+                    spans.push((capture.node.byte_range(), m.pattern_index, name));
+                }
+            }
+        }
+        // Wider nodes first and later patterns last, so what is painted last wins
+        spans.sort_by_key(|(range, pattern, _)| (Reverse(range.len()), *pattern));
+        for (range, _, name) in spans {
+            let value = if name == "none" { None } else { Some(self.capture(name)) };
+            let start = range.start.clamp(self.range.start, self.range.end) - self.range.start;
+            let end = range.end.clamp(self.range.start, self.range.end) - self.range.start;
+            self.paint[start..end].fill(value);
+        }
+    }
+
+    fn capture(&mut self, name: &str) -> usize {
+        match self.captures.iter().position(|capture| capture == name) {
+            Some(index) => index,
+            None => {
+                self.captures.push(name.to_string());
+                self.captures.len() - 1
+            }
+        }
+    }
+
+    /// Runs of equal paint as tokens with UTF-16 offsets.
+    fn tokens(self) -> Vec<HighlightToken> {
+        let mut tokens: Vec<HighlightToken> = Vec::new();
+        let mut offset = self.source[..self.range.start].encode_utf16().count();
+        let mut current: Option<(usize, usize)> = None;
+        for (index, c) in self.source[self.range.clone()].char_indices() {
+            let paint = self.paint[index];
+            if current.map(|(_, capture)| capture) != paint {
+                if let Some((from, capture)) = current.take() {
+                    tokens.push(HighlightToken { from, to: offset, capture: self.captures[capture].clone() });
+                }
+                current = paint.map(|capture| (offset, capture));
+            }
+            offset += c.len_utf16();
+        }
+        if let Some((from, capture)) = current {
+            tokens.push(HighlightToken { from, to: offset, capture: self.captures[capture].clone() });
+        }
+        tokens
+    }
+}
+
+/// Highlight an open document from its cached tree, limited to `from..to`
+/// (UTF-16 offsets, the visible range) when given.
+#[tauri::command(rename_all = "snake_case")]
+pub fn highlight_document(
+    file_path: String,
+    from: Option<usize>,
+    to: Option<usize>,
+    documents: tauri::State<'_, Documents>,
+    query_cache: tauri::State<'_, QueryCache>,
+    app_handle: tauri::AppHandle,
+) -> CmdResult<DocumentHighlights> {
+    let queries = Queries::new(&query_cache, &app_handle);
+    documents::with_document(&documents, &file_path, |document| {
+        let source = document.source();
+        let start = documents::utf16_to_byte(source, from.unwrap_or(0))?;
+        let end = match to {
+            Some(to) => documents::utf16_to_byte(source, to).unwrap_or(source.len()),
+            None => source.len(),
+        };
+        if start > end {
+            return Err(AppError::invalid_input(format!("Invalid highlight range {:?}..{:?}", from, to)));
+        }
+        Ok(DocumentHighlights {
+            version: document.version(),
+            tokens: highlight(document.tree(), source, document.language_id(), &queries, start..end),
+        })
+    })?
+}
+
+/// Highlight code that is not open in the editor, e.g. for a preview or an export.
+#[tauri::command(rename_all = "snake_case")]
+pub fn highlight_source(
+    source_code: String,
+    language_id: String,
+    query_cache: tauri::State<'_, QueryCache>,
+    app_handle: tauri::AppHandle,
+) -> CmdResult<Vec<HighlightToken>> {
+    let mut parser = Parser::new();
+    parser.set_language(&crate::get_language(&language_id)?)
+        .map_err(|e| AppError::internal(format!("Failed to set language: {}", e)))?;
+    let tree = parser.parse(&source_code, None)
+        .ok_or_else(|| AppError::internal("Failed to parse source code"))?;
+    let queries = Queries::new(&query_cache, &app_handle);
+    Ok(highlight(&tree, &source_code, &language_id, &queries, 0..source_code.len()))
+}
@@ -0,0 +1,888 @@
+//! Search through git history: lines commits added or removed (pickaxe, like
+//! `git log -G`), commit messages, or every file version in a revision range.
+//!
+//! Runs the git CLI like the rest of the git integration. Matching commits are
+//! streamed as `history_search_results` events and the job ends with one
+//! `history_search_finished`. Jobs are registered in `SearchJobs`, so
+//! `cancel_workspace_search` stops them like any workspace search.
+
+use std::collections::HashSet;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::error::{AppError, CmdResult};
+use crate::search::{self, Matcher, SearchJobs};
+
+/// Commits reported when the caller gives no limit.
+const DEFAULT_MAX_COMMITS: usize = 200;
+
+/// Matched lines kept per file and per commit message.
+const MAX_LINES_PER_FILE: usize = 100;
+
+/// Longer lines (minified code, lock files) are cut in results.
+const MAX_LINE_LENGTH: usize = 2000;
+
+/// How often a running `git log` checks whether its search was cancelled.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// File versions above this size are not searched in blob mode.
+const MAX_BLOB_SIZE: usize = 4 * 1024 * 1024;
+
+/// Starts the header `git log` prints for each commit; ends the message.
+const RECORD_START: char = '\u{1e}';
+const MESSAGE_END: char = '\u{1d}';
+const FIELD: char = '\u{1f}';
+const LOG_FORMAT: &str = "--format=%x1e%H%x1f%P%x1f%an%x1f%ae%x1f%at%n%B%x1d";
+
+/// What part of history a query is matched against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HistorySearchMode {
+    /// Lines added or removed by each commit.
+    #[default]
+    Pickaxe,
+    /// Full commit messages.
+    Message,
+    /// Contents of every file version in the range, each version searched once.
+    Blobs,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistorySearchOptions {
+    pub mode: HistorySearchMode,
+    /// Anything `git log` accepts, e.g. `main`, `v1.0..v2.0`. Defaults to `HEAD`.
+    pub rev_range: Option<String>,
+    /// Restrict to these paths (relative to the repository, git pathspecs).
+    pub paths: Vec<String>,
+    pub max_commits: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoryLineKind {
+    Added,
+    Removed,
+    /// A line as it is in a file version or a commit message.
+    Context,
+}
+
+/// 1-based UTF-16 columns, as the editor counts them.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineMatch {
+    pub column: usize,
+    pub end_column: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryLine {
+    pub kind: HistoryLineKind,
+    /// Line in the new file for added and context lines, in the old file for
+    /// removed lines, in the message for message matches.
+    pub line_number: usize,
+    pub text: String,
+    pub matches: Vec<LineMatch>,
+}
+
+/// A diff hunk with at least one matching line. Blob matches are one-line hunks.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<HistoryLine>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryFile {
+    pub path: String,
+    /// Set when the file was renamed or deleted by the commit.
+    pub old_path: Option<String>,
+    pub hunks: Vec<HistoryHunk>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryCommit {
+    pub hash: String,
+    pub parents: Vec<String>,
+    pub author_name: String,
+    pub author_email: String,
+    /// Author date, seconds since the epoch.
+    pub timestamp: i64,
+    pub subject: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub message_matches: Vec<HistoryLine>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<HistoryFile>,
+}
+
+impl HistoryCommit {
+    fn match_count(&self) -> usize {
+        let lines = self.files.iter()
+            .flat_map(|f| f.hunks.iter())
+            .flat_map(|h| h.lines.iter())
+            .chain(self.message_matches.iter());
+        lines.map(|line| line.matches.len()).sum()
+    }
+}
+
+/// One commit, emitted as `history_search_results` as soon as it is complete.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryBatch {
+    pub search_id: String,
+    pub commits: Vec<HistoryCommit>,
+}
+
+/// Emitted exactly once as `history_search_finished`.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySummary {
+    pub search_id: String,
+    pub commits_scanned: usize,
+    pub commits_matched: usize,
+    pub matches: usize,
+    pub truncated: bool,
+    pub cancelled: bool,
+    pub elapsed_ms: u64,
+    /// Why git failed, e.g. an unknown revision.
+    pub error: Option<String>,
+}
+
+fn line_matches(matcher: &Matcher, text: &str) -> Vec<LineMatch> {
+    let bytes = text.as_bytes();
+    let mut matches = Vec::new();
+    let mut start = 0;
+    while start <= bytes.len() {
+        let Some((match_start, match_end)) = matcher.find_at(bytes, start) else {
+            break;
+        };
+        if match_end > match_start {
+            let column = text[..match_start].encode_utf16().count() + 1;
+            matches.push(LineMatch { column, end_column: column + text[match_start..match_end].encode_utf16().count() });
+        }
+        start = if match_end > match_start { match_end } else { match_end + 1 };
+        while start < bytes.len() && !text.is_char_boundary(start) {
+            start += 1;
+        }
+    }
+    matches
+}
+
+fn truncate_line(text: &str) -> String {
+    match text.char_indices().nth(MAX_LINE_LENGTH) {
+        Some((end, _)) => text[..end].to_string(),
+        None => text.to_string(),
+    }
+}
+
+fn history_line(matcher: &Matcher, kind: HistoryLineKind, line_number: usize, text: &str) -> Option<HistoryLine> {
+    let text = truncate_line(text.trim_end_matches('\r'));
+    let matches = line_matches(matcher, &text);
+    (!matches.is_empty()).then_some(HistoryLine { kind, line_number, text, matches })
+}
+
+/// Escape a literal for git's POSIX extended regexes.
+fn escape_ere(literal: &str) -> String {
+    let mut escaped = String::with_capacity(literal.len());
+    for c in literal.chars() {
+        if "\\.^$*+?()[]{}|".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Paths in diff headers are quoted C-style when they contain unusual characters.
+fn unquote_path(path: &str) -> String {
+    let Some(inner) = path.strip_prefix('"').and_then(|p| p.strip_suffix('"')) else {
+        return path.to_string();
+    };
+    let mut bytes = Vec::with_capacity(inner.len());
+    let mut chars = inner.bytes().peekable();
+    while let Some(b) = chars.next() {
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        match chars.next() {
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b't') => bytes.push(b'\t'),
+            Some(digit @ b'0'..=b'7') => {
+                let mut value = (digit - b'0') as u32;
+                for _ in 0..2 {
+                    if let Some(next @ b'0'..=b'7') = chars.peek().copied() {
+                        value = value * 8 + (next - b'0') as u32;
+                        chars.next();
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => bytes.push(other),
+            None => {}
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Side of a `---`/`+++` header line: `None` for `/dev/null`.
+fn diff_header_path(rest: &str, prefix: &str) -> Option<String> {
+    let path = unquote_path(rest.trim_end());
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(path.strip_prefix(prefix).map(str::to_string).unwrap_or(path))
+}
+
+/// `@@ -old_start,old_lines +new_start,new_lines @@`; a missing count means 1.
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize, usize)> {
+    let mut parts = line.strip_prefix("@@ ")?.split(' ');
+    let range = |part: &str| -> Option<(usize, usize)> {
+        let (start, count) = part.split_once(',').unwrap_or((part, "1"));
+        Some((start.parse().ok()?, count.parse().ok()?))
+    };
+    let (old_start, old_lines) = range(parts.next()?.strip_prefix('-')?)?;
+    let (new_start, new_lines) = range(parts.next()?.strip_prefix('+')?)?;
+    Some((old_start, old_lines, new_start, new_lines))
+}
+
+/// A commit from the `%H %P %an %ae %at` fields of a log header, without message.
+fn parse_commit_header(header: &str) -> Option<HistoryCommit> {
+    let mut fields = header.split(FIELD);
+    Some(HistoryCommit {
+        hash: fields.next()?.to_string(),
+        parents: fields.next()?.split_whitespace().map(str::to_string).collect(),
+        author_name: fields.next()?.to_string(),
+        author_email: fields.next()?.to_string(),
+        timestamp: fields.next()?.trim().parse().unwrap_or(0),
+        subject: String::new(),
+        message: String::new(),
+        message_matches: Vec::new(),
+        files: Vec::new(),
+    })
+}
+
+fn set_message(commit: &mut HistoryCommit, message: &str) {
+    let message = message.trim_end();
+    commit.subject = message.lines().next().unwrap_or_default().to_string();
+    commit.message = message.to_string();
+}
+
+/// Builds one commit from `git log` output as its lines arrive, keeping only
+/// hunks that contain a match.
+struct CommitParser<'a> {
+    matcher: &'a Matcher,
+    mode: HistorySearchMode,
+    commit: HistoryCommit,
+    in_message: bool,
+    file: Option<HistoryFile>,
+    old_path: Option<String>,
+    hunk: Option<HistoryHunk>,
+    hunk_matched: bool,
+    old_line: usize,
+    new_line: usize,
+}
+
+impl<'a> CommitParser<'a> {
+    fn new(matcher: &'a Matcher, mode: HistorySearchMode, header: &str) -> Option<Self> {
+        Some(CommitParser {
+            matcher,
+            mode,
+            commit: parse_commit_header(header)?,
+            in_message: true,
+            file: None,
+            old_path: None,
+            hunk: None,
+            hunk_matched: false,
+            old_line: 0,
+            new_line: 0,
+        })
+    }
+
+    fn push_line(&mut self, line: &str) {
+        if self.in_message {
+            let (text, ended) = match line.split_once(MESSAGE_END) {
+                Some((text, _)) => (text, true),
+                None => (line, false),
+            };
+            if !ended || !text.is_empty() {
+                self.commit.message.push_str(text);
+                self.commit.message.push('\n');
+            }
+            self.in_message = !ended;
+            return;
+        }
+        if self.mode != HistorySearchMode::Pickaxe {
+            return;
+        }
+
+        if line.starts_with("diff --git ") {
+            self.finish_file();
+        } else if let Some(rest) = line.strip_prefix("--- ").filter(|_| self.hunk.is_none()) {
+            // Inside a hunk this is a removed line starting with `-- `
+            self.old_path = diff_header_path(rest, "a/");
+        } else if let Some(rest) = line.strip_prefix("+++ ").filter(|_| self.hunk.is_none()) {
+            let new_path = diff_header_path(rest, "b/");
+            let old_path = self.old_path.take();
+            let (path, old_path) = match new_path {
+                Some(path) => {
+                    let renamed = old_path.filter(|old| *old != path);
+                    (path, renamed)
+                }
+                None => (old_path.clone().unwrap_or_default(), old_path),
+            };
+            self.file = Some(HistoryFile { path, old_path, hunks: Vec::new() });
+        } else if line.starts_with("@@ ") {
+            self.finish_hunk();
+            if let Some((old_start, old_lines, new_start, new_lines)) = parse_hunk_header(line) {
+                self.old_line = old_start;
+                self.new_line = new_start;
+                self.hunk = Some(HistoryHunk { old_start, old_lines, new_start, new_lines, lines: Vec::new() });
+            }
+        } else if let Some(hunk) = self.hunk.as_mut() {
+            let (kind, text, line_number) = if let Some(text) = line.strip_prefix('+') {
+                self.new_line += 1;
+                (HistoryLineKind::Added, text, self.new_line - 1)
+            } else if let Some(text) = line.strip_prefix('-') {
+                self.old_line += 1;
+                (HistoryLineKind::Removed, text, self.old_line - 1)
+            } else {
+                // `\ No newline at end of file`, or context when git is configured with some
+                return;
+            };
+            if hunk.lines.len() < MAX_LINES_PER_FILE {
+                let text = truncate_line(text.trim_end_matches('\r'));
+                let matches = line_matches(self.matcher, &text);
+                self.hunk_matched |= !matches.is_empty();
+                hunk.lines.push(HistoryLine { kind, line_number, text, matches });
+            }
+        }
+    }
+
+    fn finish_hunk(&mut self) {
+        if let (Some(hunk), Some(file)) = (self.hunk.take(), self.file.as_mut()) {
+            if self.hunk_matched {
+                file.hunks.push(hunk);
+            }
+        }
+        self.hunk_matched = false;
+    }
+
+    fn finish_file(&mut self) {
+        self.finish_hunk();
+        if let Some(file) = self.file.take().filter(|file| !file.hunks.is_empty()) {
+            self.commit.files.push(file);
+        }
+        self.old_path = None;
+    }
+
+    /// The finished commit, if anything in it matched.
+    fn finish(mut self) -> Option<HistoryCommit> {
+        self.finish_file();
+        let message = std::mem::take(&mut self.commit.message);
+        set_message(&mut self.commit, &message);
+        if self.mode == HistorySearchMode::Message {
+            self.commit.message_matches = self.commit.message.lines()
+                .enumerate()
+                .filter_map(|(i, line)| history_line(self.matcher, HistoryLineKind::Context, i + 1, line))
+                .take(MAX_LINES_PER_FILE)
+                .collect();
+        }
+        let matched = !self.commit.files.is_empty() || !self.commit.message_matches.is_empty();
+        matched.then_some(self.commit)
+    }
+}
+
+/// Parameters of one history search, checked before the job starts.
+pub struct HistoryQuery {
+    repo: String,
+    query: String,
+    use_regex: bool,
+    case_sensitive: bool,
+    matcher: Matcher,
+    options: HistorySearchOptions,
+}
+
+impl HistoryQuery {
+    pub fn new(
+        repo: &Path,
+        query: String,
+        use_regex: bool,
+        case_sensitive: bool,
+        whole_word: bool,
+        options: HistorySearchOptions,
+    ) -> CmdResult<Self> {
+        if query.is_empty() {
+            return Err(AppError::invalid_input("Search query cannot be empty"));
+        }
+        // Revisions go on git's command line; keep them from being read as options
+        if options.rev_range.as_deref().is_some_and(|range| range.trim().is_empty() || range.starts_with('-')) {
+            return Err(AppError::invalid_input("Invalid revision range"));
+        }
+        Ok(HistoryQuery {
+            repo: repo.to_string_lossy().into_owned(),
+            matcher: Matcher::new(&query, use_regex, case_sensitive, whole_word)?,
+            query,
+            use_regex,
+            case_sensitive,
+            options,
+        })
+    }
+
+    fn rev_range(&self) -> &str {
+        self.options.rev_range.as_deref().unwrap_or("HEAD")
+    }
+
+    fn git(&self) -> Command {
+        let mut command = Command::new("git");
+        command.current_dir(&self.repo)
+            .args(["-c", "core.quotePath=false"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        command
+    }
+
+    fn log_command(&self) -> Command {
+        let mut command = self.git();
+        command.args(["log", "--no-color", LOG_FORMAT]);
+        match self.options.mode {
+            HistorySearchMode::Pickaxe => {
+                command.args(["-p", "--unified=0", "--no-ext-diff", "--no-textconv"]);
+                // Git's regex dialect differs from ours, so it only narrows the
+                // commits for literal queries; the lines are matched here either way
+                if !self.use_regex {
+                    command.arg(format!("-G{}", escape_ere(&self.query)));
+                    if !self.case_sensitive {
+                        command.arg("-i");
+                    }
+                }
+            }
+            HistorySearchMode::Message => {
+                if !self.use_regex {
+                    command.args(["--fixed-strings", &format!("--grep={}", self.query)]);
+                    if !self.case_sensitive {
+                        command.arg("-i");
+                    }
+                }
+            }
+            HistorySearchMode::Blobs => {
+                command.args(["--raw", "--no-abbrev", "--no-renames"]);
+            }
+        }
+        command.arg(self.rev_range()).arg("--").args(&self.options.paths);
+        command
+    }
+}
+
+/// Where a job reports to.
+struct Reporter<'a> {
+    on_commit: &'a mut dyn FnMut(HistoryCommit),
+    cancel_flag: &'a AtomicBool,
+    max_commits: usize,
+    summary: HistorySummary,
+}
+
+impl Reporter<'_> {
+    fn cancelled(&self) -> bool {
+        self.cancel_flag.load(Ordering::Relaxed)
+    }
+
+    /// Report a matching commit; `false` once the limit is reached.
+    fn emit(&mut self, commit: HistoryCommit) -> bool {
+        if self.summary.commits_matched >= self.max_commits {
+            self.summary.truncated = true;
+            return false;
+        }
+        self.summary.commits_matched += 1;
+        self.summary.matches += commit.match_count();
+        (self.on_commit)(commit);
+        true
+    }
+}
+
+/// Stop a git process early and surface what it wrote to stderr if it failed.
+fn finish_git(mut child: Child, stopped_early: bool) -> Result<(), String> {
+    if stopped_early {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Ok(());
+    }
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        let _ = pipe.read_to_string(&mut stderr);
+    }
+    match child.wait() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) if stderr.trim().is_empty() => Err(format!("git exited with {}", status)),
+        Ok(_) => Err(stderr.trim().to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Run `git log` and feed each commit's lines to `on_commit` once it is complete.
+/// `on_commit` gets the commit header and its remaining lines; returning `false` stops.
+fn for_each_logged_commit(
+    query: &HistoryQuery,
+    reporter: &mut Reporter,
+    on_commit: &mut dyn FnMut(&str, &[String], &mut Reporter) -> bool,
+) -> Result<(), String> {
+    let mut child = query.log_command().spawn().map_err(|e| AppError::spawn(e, "git").message)?;
+    let stdout = child.stdout.take().ok_or("git produced no output")?;
+    let mut reader = BufReader::new(stdout);
+
+    let cancel_flag = reporter.cancel_flag;
+    let (child, read) = kill_on_cancel(child, cancel_flag, || {
+        let mut header: Option<String> = None;
+        let mut lines: Vec<String> = Vec::new();
+        let mut buffer = Vec::new();
+        loop {
+            if reporter.cancelled() {
+                return Ok(true);
+            }
+            buffer.clear();
+            let read = reader.read_until(b'\n', &mut buffer).map_err(|e| e.to_string())?;
+            let line = String::from_utf8_lossy(&buffer);
+            let line = line.strip_suffix('\n').unwrap_or(&line);
+            if read == 0 || line.starts_with(RECORD_START) {
+                if let Some(previous) = header.take() {
+                    reporter.summary.commits_scanned += 1;
+                    if !on_commit(&previous, &lines, reporter) {
+                        return Ok(true);
+                    }
+                }
+                lines.clear();
+                if read == 0 {
+                    // Killed by a cancel: the output just ends
+                    return Ok(reporter.cancelled());
+                }
+                header = Some(line[RECORD_START.len_utf8()..].to_string());
+            } else {
+                lines.push(line.to_string());
+            }
+        }
+    });
+    match read {
+        Ok(stopped) => finish_git(child, stopped),
+        Err(e) => {
+            let _ = finish_git(child, true);
+            Err(e)
+        }
+    }
+}
+
+/// Run `read` while a watcher thread kills `child` as soon as `cancel_flag` is
+/// set. `git log -G` can go a long time without printing anything, and a read
+/// blocked on its output only returns once the process is gone.
+fn kill_on_cancel<T>(child: Child, cancel_flag: &AtomicBool, read: impl FnOnce() -> T) -> (Child, T) {
+    let child = Mutex::new(child);
+    let done = AtomicBool::new(false);
+    let result = std::thread::scope(|scope| {
+        scope.spawn(|| {
+            while !done.load(Ordering::Relaxed) {
+                if cancel_flag.load(Ordering::Relaxed) {
+                    if let Ok(mut child) = child.lock() {
+                        let _ = child.kill();
+                    }
+                    return;
+                }
+                std::thread::sleep(CANCEL_POLL_INTERVAL);
+            }
+        });
+        let result = read();
+        done.store(true, Ordering::Relaxed);
+        result
+    });
+    (child.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner()), result)
+}
+
+fn search_log(query: &HistoryQuery, reporter: &mut Reporter) -> Result<(), String> {
+    for_each_logged_commit(query, reporter, &mut |header, lines, reporter| {
+        let Some(mut parser) = CommitParser::new(&query.matcher, query.options.mode, header) else {
+            return true;
+        };
+        for line in lines {
+            parser.push_line(line);
+        }
+        match parser.finish() {
+            Some(commit) => reporter.emit(commit),
+            None => true,
+        }
+    })
+}
+
+/// Reads objects through one `git cat-file --batch` process.
+struct BlobReader {
+    child: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+}
+
+impl BlobReader {
+    fn start(query: &HistoryQuery) -> io::Result<Self> {
+        let mut command = query.git();
+        let mut child = command.args(["cat-file", "--batch"]).stdin(Stdio::piped()).spawn()?;
+        let input = child.stdin.take().ok_or_else(|| io::Error::other("no stdin"))?;
+        let output = BufReader::new(child.stdout.take().ok_or_else(|| io::Error::other("no stdout"))?);
+        Ok(BlobReader { child, input, output })
+    }
+
+    /// Contents of a blob, or `None` for missing objects and blobs over the size limit.
+    fn read(&mut self, object: &str) -> io::Result<Option<Vec<u8>>> {
+        writeln!(self.input, "{}", object)?;
+        self.input.flush()?;
+        let mut header = String::new();
+        self.output.read_line(&mut header)?;
+        let mut fields = header.split_whitespace();
+        let (Some(_), Some(kind), Some(size)) = (fields.next(), fields.next(), fields.next()) else {
+            return Ok(None);
+        };
+        let size: usize = size.parse().map_err(|_| io::Error::other("bad cat-file header"))?;
+        let mut contents = vec![0u8; size];
+        self.output.read_exact(&mut contents)?;
+        let mut newline = [0u8; 1];
+        self.output.read_exact(&mut newline)?;
+        Ok((kind == "blob" && size <= MAX_BLOB_SIZE).then_some(contents))
+    }
+}
+
+impl Drop for BlobReader {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Matching lines of one file version.
+fn search_blob(matcher: &Matcher, contents: &[u8]) -> Vec<HistoryHunk> {
+    if memchr::memchr(0, &contents[..contents.len().min(8192)]).is_some() {
+        return Vec::new();
+    }
+    String::from_utf8_lossy(contents)
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| history_line(matcher, HistoryLineKind::Context, i + 1, line))
+        .take(MAX_LINES_PER_FILE)
+        .map(|line| HistoryHunk {
+            old_start: line.line_number,
+            old_lines: 1,
+            new_start: line.line_number,
+            new_lines: 1,
+            lines: vec![line],
+        })
+        .collect()
+}
+
+/// `:100644 100644 <old> <new> M\tpath` from `--raw`; the new object unless deleted.
+fn parse_raw_line(line: &str) -> Option<(String, String)> {
+    let (meta, path) = line.strip_prefix(':')?.split_once('\t')?;
+    let object = meta.split(' ').nth(3)?;
+    if object.bytes().all(|b| b == b'0') || meta.starts_with("160000") || meta.split(' ').nth(1) == Some("160000") {
+        return None;
+    }
+    Some((object.to_string(), unquote_path(path)))
+}
+
+/// Search the versions a commit brought in that were not searched yet.
+fn search_versions(
+    query: &HistoryQuery,
+    blobs: &mut BlobReader,
+    seen: &mut HashSet<(String, String)>,
+    mut commit: HistoryCommit,
+    versions: Vec<(String, String)>,
+    reporter: &mut Reporter,
+) -> Result<bool, String> {
+    for (object, path) in versions {
+        if reporter.cancelled() {
+            return Ok(false);
+        }
+        if !seen.insert((object.clone(), path.clone())) {
+            continue;
+        }
+        let Some(contents) = blobs.read(&object).map_err(|e| e.to_string())? else {
+            continue;
+        };
+        let hunks = search_blob(&query.matcher, &contents);
+        if !hunks.is_empty() {
+            commit.files.push(HistoryFile { path, old_path: None, hunks });
+        }
+    }
+    Ok(commit.files.is_empty() || reporter.emit(commit))
+}
+
+/// Search every file version in the range. Each version is reported once, under
+/// the commit that introduced it; versions that predate the range are reported
+/// under its oldest commit.
+fn search_blobs(query: &HistoryQuery, reporter: &mut Reporter) -> Result<(), String> {
+    let mut blobs = BlobReader::start(query).map_err(|e| AppError::spawn(e, "git").message)?;
+    let mut seen = HashSet::new();
+    let mut oldest: Option<HistoryCommit> = None;
+    let mut failure = None;
+
+    for_each_logged_commit(query, reporter, &mut |header, lines, reporter| {
+        let Some(mut commit) = parse_commit_header(header) else {
+            return true;
+        };
+        let message_end = lines.iter().position(|line| line.contains(MESSAGE_END)).unwrap_or(lines.len());
+        let mut message = lines[..message_end].join("\n");
+        if let Some((last, _)) = lines.get(message_end).and_then(|line| line.split_once(MESSAGE_END)) {
+            message.push('\n');
+            message.push_str(last);
+        }
+        set_message(&mut commit, &message);
+        let versions = lines.iter().skip(message_end + 1).filter_map(|line| parse_raw_line(line)).collect();
+        oldest = Some(commit.clone());
+        search_versions(query, &mut blobs, &mut seen, commit, versions, reporter)
+            .unwrap_or_else(|e| {
+                failure = Some(e);
+                false
+            })
+    })?;
+    if let Some(e) = failure {
+        return Err(e);
+    }
+    let Some(oldest) = oldest.filter(|_| !reporter.cancelled() && !reporter.summary.truncated) else {
+        return Ok(());
+    };
+
+    // Files the range never touched are in it too, as of its oldest commit
+    let mut command = query.git();
+    command.args(["ls-tree", "-r", "-z", &oldest.hash, "--"]).args(&query.options.paths);
+    let output = command.output().map_err(|e| AppError::spawn(e, "git").message)?;
+    if !output.status.success() {
+        return Err(AppError::process_failed(&output).message);
+    }
+    let versions = output.stdout.split(|b| *b == 0)
+        .filter_map(|entry| {
+            let (meta, path) = std::str::from_utf8(entry).ok()?.split_once('\t')?;
+            let mut fields = meta.split(' ');
+            let (_, kind, object) = (fields.next()?, fields.next()?, fields.next()?);
+            (kind == "blob").then(|| (object.to_string(), path.to_string()))
+        })
+        .collect();
+    let mut commit = oldest;
+    commit.files.clear();
+    search_versions(query, &mut blobs, &mut seen, commit, versions, reporter)?;
+    Ok(())
+}
+
+/// Run a history search to the end, passing each matching commit to `on_commit`
+/// as soon as it is complete. Stops early when `cancel_flag` is set.
+pub fn search_history(query: &HistoryQuery, cancel_flag: &AtomicBool, on_commit: &mut dyn FnMut(HistoryCommit)) -> HistorySummary {
+    let mut reporter = Reporter {
+        on_commit,
+        cancel_flag,
+        max_commits: query.options.max_commits.unwrap_or(DEFAULT_MAX_COMMITS),
+        summary: HistorySummary::default(),
+    };
+    let result = match query.options.mode {
+        HistorySearchMode::Blobs => search_blobs(query, &mut reporter),
+        HistorySearchMode::Pickaxe | HistorySearchMode::Message => search_log(query, &mut reporter),
+    };
+    let mut summary = reporter.summary;
+    summary.cancelled = cancel_flag.load(Ordering::Relaxed);
+    summary.error = result.err();
+    summary
+}
+
+/// Search commit history for a string or regex and stream matching commits as
+/// `history_search_results`, followed by one `history_search_finished`. Running
+/// it again with the same id cancels the earlier job.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command(rename_all = "snake_case")]
+pub fn start_history_search(
+    search_id: String,
+    repo_path: String,
+    query: String,
+    use_regex: bool,
+    case_sensitive: bool,
+    whole_word: bool,
+    options: Option<HistorySearchOptions>,
+    jobs: tauri::State<'_, SearchJobs>,
+    app_handle: AppHandle,
+) -> CmdResult<String> {
+    let repo = search::validate_workspace(&repo_path)?;
+    let query = HistoryQuery::new(repo, query, use_regex, case_sensitive, whole_word, options.unwrap_or_default())?;
+
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    {
+        let mut jobs = jobs.lock().map_err(|_| AppError::lock_poisoned("search jobs"))?;
+        if let Some(previous) = jobs.insert(search_id.clone(), cancel_flag.clone()) {
+            previous.store(true, Ordering::Relaxed);
+        }
+    }
+
+    let jobs = jobs.inner().clone();
+    let job_id = search_id.clone();
+    std::thread::spawn(move || {
+        let started = Instant::now();
+        let mut summary = search_history(&query, &cancel_flag, &mut |commit| {
+            let _ = app_handle.emit("history_search_results", HistoryBatch {
+                search_id: job_id.clone(),
+                commits: vec![commit],
+            });
+        });
+        summary.search_id = job_id.clone();
+        summary.elapsed_ms = started.elapsed().as_millis() as u64;
+
+        // Only drop our own entry; a restarted job with the same id owns a different flag
+        if let Ok(mut jobs) = jobs.lock() {
+            if jobs.get(&job_id).is_some_and(|flag| Arc::ptr_eq(flag, &cancel_flag)) {
+                jobs.remove(&job_id);
+            }
+        }
+        let _ = app_handle.emit("history_search_finished", summary);
+    });
+
+    Ok(search_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_removed_lines_that_look_like_file_headers() {
+        let matcher = Matcher::new("comment", false, false, false).unwrap();
+        let header = "abc123\u{1f}\u{1f}Ann\u{1f}ann@example.com\u{1f}1700000000";
+        let mut parser = CommitParser::new(&matcher, HistorySearchMode::Pickaxe, header).unwrap();
+        let log = [
+            "Drop stale comments\u{1d}",
+            "diff --git a/schema.sql b/schema.sql",
+            "--- a/schema.sql",
+            "+++ b/schema.sql",
+            "@@ -10,3 +10,1 @@",
+            "--- old comment",
+            "-SELECT 1;",
+            "+-- new comment",
+        ];
+        for line in log {
+            parser.push_line(line);
+        }
+        let commit = parser.finish().unwrap();
+        assert_eq!(commit.subject, "Drop stale comments");
+        assert_eq!(commit.files.len(), 1);
+        assert_eq!(commit.files[0].path, "schema.sql");
+        assert_eq!(commit.files[0].old_path, None);
+
+        let lines: Vec<_> = commit.files[0].hunks[0].lines.iter()
+            .map(|line| (line.line_number, line.text.as_str(), line.matches.len()))
+            .collect();
+        assert_eq!(lines, vec![(10, "-- old comment", 1), (11, "SELECT 1;", 0), (10, "-- new comment", 1)]);
+    }
+}
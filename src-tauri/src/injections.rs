@@ -0,0 +1,234 @@
+//! Languages embedded in a document: `<script>` and `<style>` in HTML, fenced
+//! code in Markdown, tagged template literals in JavaScript. The grammar's
+//! `injections.scm` marks the embedded ranges, and each is parsed with its own
+//! grammar as a layer over the document's tree.
+//!
+//! Injection queries follow the tree-sitter conventions: `@injection.content`
+//! is the embedded code, its language comes from `@injection.language` or
+//! `(#set! injection.language "...")`, and `injection.combined` /
+//! `injection.include-children` behave as in tree-sitter's highlighter.
+//!
+//! Layers feed highlighting (see [`crate::highlight`]), the outline, where
+//! embedded symbols nest under the host symbols that contain them, and the
+//! language at a position, which decides e.g. which comment syntax applies.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Language, Node, Parser, Query, QueryCursor, Tree};
+
+use crate::documents::{self, Documents};
+use crate::error::CmdResult;
+use crate::languages;
+use crate::queries::{Queries, QueryCache};
+use crate::tags;
+use crate::{DocumentSymbol, SymbolQuery};
+
+/// Injections inside injections are followed this deep.
+const MAX_DEPTH: usize = 3;
+
+/// An embedded language parsed over part of a document.
+pub struct Layer {
+    pub language_id: String,
+    /// Parsed over the document's source, limited to the embedded ranges.
+    pub tree: Tree,
+}
+
+/// Parse the injections of `tree` that touch `byte_range` (all of them for
+/// `None`), and their injections in turn. Outer layers come before the layers
+/// nested in them.
+pub fn parse_layers(tree: &Tree, source: &str, language_id: &str, queries: &Queries, byte_range: Option<Range<usize>>) -> Vec<Layer> {
+    let mut layers = Vec::new();
+    let mut resolver = Resolver::default();
+    collect(tree, source, language_id, queries, byte_range.as_ref(), 1, &mut resolver, &mut layers);
+    layers
+}
+
+#[allow(clippy::too_many_arguments)]
+fn collect(
+    tree: &Tree,
+    source: &str,
+    language_id: &str,
+    queries: &Queries,
+    byte_range: Option<&Range<usize>>,
+    depth: usize,
+    resolver: &mut Resolver,
+    layers: &mut Vec<Layer>,
+) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    let Some(query) = queries.get(language_id, "injections.scm") else { return };
+    for (name, ranges) in injected_ranges(&query, tree.root_node(), source, byte_range) {
+        let Some((injected_id, language)) = resolver.resolve(&name) else { continue };
+        let mut parser = Parser::new();
+        if parser.set_language(&language).is_err() || parser.set_included_ranges(&ranges).is_err() {
+            continue;
+        }
+        let Some(injected) = parser.parse(source, None) else { continue };
+        let mut nested = Vec::new();
+        collect(&injected, source, &injected_id, queries, byte_range, depth + 1, resolver, &mut nested);
+        layers.push(Layer { language_id: injected_id, tree: injected });
+        layers.append(&mut nested);
+    }
+}
+
+/// The language name and ranges of every injection the query finds.
+fn injected_ranges(query: &Query, root: Node, source: &str, byte_range: Option<&Range<usize>>) -> Vec<(String, Vec<tree_sitter::Range>)> {
+    let content_index = query.capture_index_for_name("injection.content");
+    let language_index = query.capture_index_for_name("injection.language");
+    let mut injections: Vec<(String, Vec<tree_sitter::Range>)> = Vec::new();
+    // Combined injections collect the ranges of every match of their pattern
+    let mut combined: HashMap<(usize, String), usize> = HashMap::new();
+
+    let mut cursor = QueryCursor::new();
+    if let Some(range) = byte_range {
+        cursor.set_byte_range(range.clone());
+    }
+    let mut matches = cursor.matches(query, root, source.as_bytes());
+    while let Some(m) = matches.next() {
+        let mut name = None;
+        let mut is_combined = false;
+        let mut include_children = false;
+        for property in query.property_settings(m.pattern_index) {
+            match property.key.as_ref() {
+                "injection.language" => name = property.value.as_deref().map(str::to_string),
+                "injection.combined" => is_combined = true,
+                "injection.include-children" => include_children = true,
+                _ => {}
+            }
+        }
+        let mut ranges = Vec::new();
+        for capture in m.captures {
+            if Some(capture.index) == language_index {
+                name = capture.node.utf8_text(source.as_bytes()).ok().map(|text| text.trim().to_string());
+            } else if Some(capture.index) == content_index {
+                push_content_ranges(capture.node, include_children, &mut ranges);
+            }
+        }
+        let Some(name) = name.filter(|name| !name.is_empty()) else { continue };
+        if ranges.is_empty() {
+            continue;
+        }
+        if is_combined {
+            match combined.get(&(m.pattern_index, name.clone())) {
+                Some(&index) => injections[index].1.extend(ranges),
+                None => {
+                    combined.insert((m.pattern_index, name.clone()), injections.len());
+                    injections.push((name, ranges));
+                }
+            }
+        } else {
+            injections.push((name, ranges));
+        }
+    }
+
+    for (_, ranges) in &mut injections {
+        ranges.sort_by_key(|range| range.start_byte);
+        ranges.dedup_by(|next, previous| next.start_byte < previous.end_byte);
+    }
+    injections
+}
+
+/// The node's range, without its named children unless `include_children`: a
+/// template literal's `${}` substitutions are not part of the embedded CSS.
+/// Anonymous children stay, since grammars like Markdown's leave punctuation
+/// tokens inside the text they hand to another grammar.
+fn push_content_ranges(node: Node, include_children: bool, ranges: &mut Vec<tree_sitter::Range>) {
+    let mut range = node.range();
+    if !include_children {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.start_byte() > range.start_byte {
+                ranges.push(tree_sitter::Range { end_byte: child.start_byte(), end_point: child.start_position(), ..range });
+            }
+            range.start_byte = range.start_byte.max(child.end_byte());
+            range.start_point = range.start_point.max(child.end_position());
+        }
+    }
+    if range.end_byte > range.start_byte {
+        ranges.push(range);
+    }
+}
+
+/// Injection language names to language ids and grammars, each name looked up
+/// once per pass.
+#[derive(Default)]
+struct Resolver {
+    resolved: HashMap<String, Option<(String, Language)>>,
+}
+
+impl Resolver {
+    fn resolve(&mut self, name: &str) -> Option<(String, Language)> {
+        self.resolved.entry(name.to_string())
+            .or_insert_with(|| {
+                // `js` and `py` in code fences; ids such as `markdown_inline` are used as they are
+                let candidates = [languages::language_for_name(name).map(str::to_string), Some(name.to_lowercase())];
+                candidates.into_iter().flatten()
+                    .find_map(|id| crate::get_language(&id).ok().map(|language| (id, language)))
+            })
+            .clone()
+    }
+}
+
+/// The outline of a document including its embedded code: a `<script>`'s
+/// functions appear under the `script` element, a code fence's under its heading.
+pub fn combined_symbols(tree: &Tree, source: &str, language_id: &str, symbol_queries: &Vec<SymbolQuery>, queries: &Queries) -> Vec<DocumentSymbol> {
+    let tags = tags::tag_query(queries, language_id);
+    let mut symbols = crate::extract_symbols_from_tree(tree, source, language_id, symbol_queries, tags.as_ref());
+    for layer in parse_layers(tree, source, language_id, queries, None) {
+        let tags = tags::tag_query(queries, &layer.language_id);
+        for symbol in crate::extract_layer_symbols(&layer.tree, source, &layer.language_id, tags.as_ref()) {
+            insert_symbol(&mut symbols, symbol);
+        }
+    }
+    symbols
+}
+
+/// Insert `symbol` under the innermost symbol that encloses it, in document order.
+fn insert_symbol(symbols: &mut Vec<DocumentSymbol>, symbol: DocumentSymbol) {
+    let start = (symbol.range.start_line_number, symbol.range.start_column);
+    let end = (symbol.range.end_line_number, symbol.range.end_column);
+    let container = symbols.iter_mut().find(|container| {
+        (container.range.start_line_number, container.range.start_column) <= start
+            && end <= (container.range.end_line_number, container.range.end_column)
+    });
+    match container {
+        Some(container) => insert_symbol(&mut container.children, symbol),
+        None => {
+            let index = symbols.partition_point(|sibling| (sibling.range.start_line_number, sibling.range.start_column) <= start);
+            symbols.insert(index, symbol);
+        }
+    }
+}
+
+/// The language at a byte offset: the innermost embedded language whose ranges
+/// contain it, else the document's own.
+pub fn language_at(tree: &Tree, source: &str, language_id: &str, queries: &Queries, offset: usize) -> String {
+    let around = offset.saturating_sub(1)..(offset + 1).min(source.len());
+    // Nested layers come after the layers that hold them, so the last match is the innermost
+    parse_layers(tree, source, language_id, queries, Some(around))
+        .into_iter()
+        .rev()
+        // Markdown's inline text is Markdown as far as the editor is concerned
+        .filter(|layer| layer.language_id != "markdown_inline")
+        .find(|layer| layer.tree.included_ranges().iter().any(|range| range.start_byte <= offset && offset <= range.end_byte))
+        .map_or_else(|| language_id.to_string(), |layer| layer.language_id)
+}
+
+/// The language at `offset` (UTF-16, as in CodeMirror) of an open document.
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_language_at_position(
+    file_path: String,
+    offset: usize,
+    documents: tauri::State<'_, Documents>,
+    query_cache: tauri::State<'_, QueryCache>,
+    app_handle: tauri::AppHandle,
+) -> CmdResult<String> {
+    let queries = Queries::new(&query_cache, &app_handle);
+    documents::with_document(&documents, &file_path, |document| {
+        let offset = documents::utf16_to_byte(document.source(), offset)?;
+        Ok(language_at(document.tree(), document.source(), document.language_id(), &queries, offset))
+    })?
+}
@@ -0,0 +1,56 @@
+use std::fs;
+use std::path::Path;
+
+use tauri::{AppHandle, Manager};
+
+use crate::grammars;
+
+mod detect;
+mod registry;
+
+pub use detect::{read_head, LanguageDetection};
+pub use registry::{language_for_name, FileTypeSettings, FileTypes, ForcedKind, LanguageEntry, LANGUAGES};
+
+/// File-type overrides from the `files` section of settings.json. The frontend owns
+/// that file, so it is re-read on demand; a missing or broken file means no overrides.
+pub fn load_file_types(app_handle: &AppHandle) -> FileTypes {
+    let Ok(config_dir) = app_handle.path().app_config_dir() else {
+        return FileTypes::default();
+    };
+    let settings = fs::read_to_string(config_dir.join("settings.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|settings| settings.get("files").cloned())
+        .and_then(|files| serde_json::from_value::<FileTypeSettings>(files).ok())
+        .unwrap_or_default();
+    FileTypes::from_settings(&settings).with_languages(grammars::installed::language_entries())
+}
+
+/// Every language the registry knows, including those of installed grammars,
+/// for the frontend's language pickers.
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_language_registry() -> Vec<LanguageEntry> {
+    let mut languages: Vec<LanguageEntry> = LANGUAGES.iter().map(LanguageEntry::from).collect();
+    for language in grammars::installed::language_entries() {
+        if !languages.iter().any(|known| known.id == language.id) {
+            languages.push(language);
+        }
+    }
+    languages
+}
+
+/// Language id for a file name or path, or `None` for unknown files.
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_language_for_path(file_path: String, app_handle: AppHandle) -> Option<String> {
+    load_file_types(&app_handle).language_for_path(Path::new(&file_path))
+}
+
+/// Language of a file from its path and contents: settings, modelines, the file
+/// name, shebangs and content sniffing, in that order. Without `content` the start
+/// of the file is read from disk. Never fails; unknown files are `plaintext`.
+#[tauri::command(rename_all = "snake_case")]
+pub fn detect_language(file_path: String, content: Option<String>, app_handle: AppHandle) -> LanguageDetection {
+    let path = Path::new(&file_path);
+    let content = content.or_else(|| read_head(path));
+    load_file_types(&app_handle).detect(path, content.as_deref())
+}
@@ -0,0 +1,213 @@
+//! Language detection from a file's contents as well as its name: Vim and Emacs
+//! modelines, shebang lines, and a few unmistakable first lines.
+//!
+//! In order, the first that applies wins:
+//! 1. `files.associations` in settings
+//! 2. a modeline in the first or last five lines (`vim: ft=python`, `-*- mode: ruby -*-`)
+//! 3. the file name or extension
+//! 4. a shebang (`#!/usr/bin/env python3`)
+//! 5. the content: `<?php`, `<?xml`, an HTML doctype, a diff, JSON, ...
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::FileTypes;
+
+/// How much of a file is read to detect its language when only the path is known.
+const HEAD_BYTES: u64 = 64 * 1024;
+
+/// Lines at each end of a file where modelines are looked for, as Vim does.
+const MODELINE_LINES: usize = 5;
+
+/// Interpreters whose name is not a language name, extension or alias.
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("node", "javascript"),
+    ("nodejs", "javascript"),
+    ("deno", "javascript"),
+    ("bun", "javascript"),
+    ("ts-node", "typescript"),
+    ("tsx", "typescript"),
+    ("sh", "shellscript"),
+    ("dash", "shellscript"),
+    ("ash", "shellscript"),
+    ("pwsh", "powershell"),
+    ("rscript", "r"),
+    ("escript", "erlang"),
+    ("runghc", "haskell"),
+    ("runhaskell", "haskell"),
+    ("make", "makefile"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DetectionSource {
+    Settings,
+    Modeline,
+    Path,
+    Shebang,
+    Content,
+    /// Nothing matched; the language is `plaintext`.
+    Default,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageDetection {
+    pub language_id: String,
+    pub source: DetectionSource,
+}
+
+impl FileTypes {
+    /// The language of a file from its path and, when given, its contents (or the
+    /// start of them).
+    pub fn detect(&self, path: &Path, content: Option<&str>) -> LanguageDetection {
+        let detected = |language_id: String, source| LanguageDetection { language_id, source };
+        if let Some(language) = self.associated_language(path) {
+            return detected(language, DetectionSource::Settings);
+        }
+        if let Some(language) = content.and_then(|content| self.modeline_language(content)) {
+            return detected(language, DetectionSource::Modeline);
+        }
+        if let Some(language) = self.registered_language(path) {
+            // `.h` is shared by C and C++
+            let is_header = path.extension().is_some_and(|extension| extension == "h");
+            if language == "c" && is_header && content.is_some_and(looks_like_cpp) {
+                return detected("cpp".to_string(), DetectionSource::Content);
+            }
+            return detected(language, DetectionSource::Path);
+        }
+        if let Some(language) = content.and_then(|content| self.shebang_language(content)) {
+            return detected(language, DetectionSource::Shebang);
+        }
+        if let Some(language) = content.and_then(content_language) {
+            return detected(language.to_string(), DetectionSource::Content);
+        }
+        detected("plaintext".to_string(), DetectionSource::Default)
+    }
+
+    /// Language id for a file on disk: by path, else by the start of its contents.
+    /// Cheaper than [`FileTypes::detect`] for files with a known extension, which
+    /// are not read at all.
+    pub fn language_for_file(&self, path: &Path) -> Option<String> {
+        self.language_for_path(path).or_else(|| {
+            let head = read_head(path)?;
+            let detection = self.detect(path, Some(&head));
+            (detection.source != DetectionSource::Default).then_some(detection.language_id)
+        })
+    }
+
+    fn modeline_language(&self, content: &str) -> Option<String> {
+        let lines: Vec<&str> = content.lines().collect();
+        let tail = lines.len().saturating_sub(MODELINE_LINES).max(MODELINE_LINES.min(lines.len()));
+        lines[..MODELINE_LINES.min(lines.len())].iter()
+            .chain(&lines[tail..])
+            .find_map(|line| vim_modeline(line).or_else(|| emacs_modeline(line)))
+            .and_then(|name| self.language_for_name(name))
+    }
+
+    fn shebang_language(&self, content: &str) -> Option<String> {
+        let line = content.lines().next()?.strip_prefix("#!")?;
+        let mut words = line.split_whitespace();
+        let mut interpreter = basename(words.next()?);
+        if interpreter == "env" {
+            // `env -S python3 -u`, `env FOO=1 node`
+            interpreter = basename(words.find(|word| !word.starts_with('-') && !word.contains('='))?);
+        }
+        // `python3.12` is `python`
+        let name = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.').to_lowercase();
+        match INTERPRETERS.iter().find(|(known, _)| *known == name) {
+            Some((_, language)) => Some(language.to_string()),
+            None => self.language_for_name(&name),
+        }
+    }
+}
+
+/// The start of a file, as text, or `None` when it is unreadable or binary.
+pub fn read_head(path: &Path) -> Option<String> {
+    let mut head = Vec::new();
+    File::open(path).ok()?.take(HEAD_BYTES).read_to_end(&mut head).ok()?;
+    if memchr::memchr(0, &head).is_some() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&head).into_owned())
+}
+
+fn basename(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// `vim: set ft=python:`, `vi: filetype=sh`, `# vim: syntax=yaml ts=2`.
+fn vim_modeline(line: &str) -> Option<&str> {
+    let start = ["vim:", "vi:", "ex:"].iter()
+        .filter_map(|marker| {
+            let index = line.find(marker)?;
+            // The marker starts the line or follows whitespace: not `evi:` or `nvim:`
+            let follows_space = index == 0 || line[..index].ends_with(char::is_whitespace);
+            follows_space.then_some(index + marker.len())
+        })
+        .min()?;
+    line[start..].split(|c: char| c.is_whitespace() || c == ':')
+        .find_map(|option| {
+            let (key, value) = option.split_once('=')?;
+            matches!(key, "ft" | "filetype" | "syn" | "syntax").then_some(value)
+        })
+        .filter(|value| !value.is_empty())
+}
+
+/// `-*- mode: python; coding: utf-8 -*-` or just `-*- python -*-`.
+fn emacs_modeline(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once("-*-")?;
+    let (variables, _) = rest.split_once("-*-")?;
+    let variables = variables.trim();
+    if !variables.contains(':') {
+        return Some(variables).filter(|mode| !mode.is_empty());
+    }
+    variables.split(';')
+        .find_map(|variable| {
+            let (key, value) = variable.split_once(':')?;
+            key.trim().eq_ignore_ascii_case("mode").then(|| value.trim())
+        })
+}
+
+/// Languages recognizable from how a file starts, whatever it is called.
+fn content_language(content: &str) -> Option<&'static str> {
+    let trimmed = content.trim_start_matches('\u{feff}').trim_start();
+    let mut lines = trimmed.lines();
+    let first = lines.next()?.trim_end();
+    let lowercase = first.to_lowercase();
+
+    if first.starts_with("<?php") {
+        Some("php")
+    } else if first.starts_with("<?xml") {
+        Some("xml")
+    } else if lowercase.starts_with("<!doctype html") || lowercase.starts_with("<html") {
+        Some("html")
+    } else if first.starts_with("diff --git ") || (first.starts_with("--- ") && lines.next().is_some_and(|line| line.starts_with("+++ "))) {
+        Some("diff")
+    } else if first.starts_with("%YAML") {
+        Some("yaml")
+    } else if first.starts_with("FROM ") && first.split_whitespace().count() >= 2 {
+        Some("dockerfile")
+    } else if (trimmed.starts_with('{') || trimmed.starts_with('[')) && serde_json::from_str::<serde_json::Value>(trimmed).is_ok() {
+        Some("json")
+    } else {
+        None
+    }
+}
+
+/// C++-only constructs in a `.h` file.
+fn looks_like_cpp(content: &str) -> bool {
+    content.lines().any(|line| {
+        let line = line.trim_start();
+        line.starts_with("class ")
+            || line.starts_with("namespace ")
+            || line.starts_with("template <")
+            || line.starts_with("template<")
+            || line.starts_with("public:")
+            || line.starts_with("private:")
+            || line.contains("std::")
+    })
+}
@@ -0,0 +1,298 @@
+//! Built-in language table and user file-type overrides.
+//!
+//! Kept free of Tauri types, like the search engine that depends on it.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::Path;
+
+use globset::{Glob, GlobMatcher, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+
+/// One entry of the language registry. Ids follow VS Code's language identifiers,
+/// which is also what language servers expect in `textDocument/didOpen`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageDef {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub extensions: &'static [&'static str],
+    pub filenames: &'static [&'static str],
+}
+
+/// A registry entry that is not known at compile time, such as the language of an
+/// installed grammar. Serializes like [`LanguageDef`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageEntry {
+    pub id: String,
+    pub name: String,
+    pub extensions: Vec<String>,
+    pub filenames: Vec<String>,
+}
+
+impl From<&LanguageDef> for LanguageEntry {
+    fn from(language: &LanguageDef) -> Self {
+        LanguageEntry {
+            id: language.id.to_string(),
+            name: language.name.to_string(),
+            extensions: language.extensions.iter().map(|ext| ext.to_string()).collect(),
+            filenames: language.filenames.iter().map(|file| file.to_string()).collect(),
+        }
+    }
+}
+
+macro_rules! language {
+    ($id:expr, $name:expr, [$($ext:expr),*], [$($file:expr),*]) => {
+        LanguageDef { id: $id, name: $name, extensions: &[$($ext),*], filenames: &[$($file),*] }
+    };
+}
+
+pub static LANGUAGES: &[LanguageDef] = &[
+    language!("javascript", "JavaScript", ["js", "mjs", "cjs"], []),
+    language!("javascriptreact", "JavaScript React", ["jsx"], []),
+    language!("typescript", "TypeScript", ["ts", "mts", "cts"], []),
+    language!("typescriptreact", "TypeScript React", ["tsx"], []),
+    language!("python", "Python", ["py", "pyw", "pyi"], ["SConstruct", "SConscript"]),
+    language!("rust", "Rust", ["rs"], []),
+    language!("go", "Go", ["go"], []),
+    language!("c", "C", ["c", "h"], []),
+    language!("cpp", "C++", ["cpp", "cc", "cxx", "c++", "hpp", "hh", "hxx", "h++", "ipp"], []),
+    language!("csharp", "C#", ["cs", "csx"], []),
+    language!("java", "Java", ["java"], []),
+    language!("kotlin", "Kotlin", ["kt", "kts"], []),
+    language!("scala", "Scala", ["scala", "sc", "sbt"], []),
+    language!("swift", "Swift", ["swift"], []),
+    language!("objective-c", "Objective-C", ["m", "mm"], []),
+    language!("dart", "Dart", ["dart"], []),
+    language!("ruby", "Ruby", ["rb", "rake", "gemspec", "ru"], ["Gemfile", "Rakefile", "Guardfile", "Podfile"]),
+    language!("php", "PHP", ["php", "phtml"], []),
+    language!("perl", "Perl", ["pl", "pm", "t"], []),
+    language!("lua", "Lua", ["lua"], []),
+    language!("zig", "Zig", ["zig", "zon"], []),
+    language!("haskell", "Haskell", ["hs", "lhs"], []),
+    language!("elixir", "Elixir", ["ex", "exs"], []),
+    language!("erlang", "Erlang", ["erl", "hrl"], []),
+    language!("clojure", "Clojure", ["clj", "cljs", "cljc", "edn"], []),
+    language!("ocaml", "OCaml", ["ml", "mli"], []),
+    language!("r", "R", ["r"], []),
+    language!("julia", "Julia", ["jl"], []),
+    language!("nix", "Nix", ["nix"], []),
+    language!("groovy", "Groovy", ["groovy", "gradle"], ["Jenkinsfile"]),
+    language!("shellscript", "Shell Script", ["sh", "bash", "zsh", "ksh"], [".bashrc", ".bash_profile", ".zshrc", ".zprofile", ".profile", "PKGBUILD"]),
+    language!("fish", "Fish", ["fish"], []),
+    language!("powershell", "PowerShell", ["ps1", "psm1", "psd1"], []),
+    language!("bat", "Batch", ["bat", "cmd"], []),
+    language!("html", "HTML", ["html", "htm", "xhtml"], []),
+    language!("vue", "Vue", ["vue"], []),
+    language!("svelte", "Svelte", ["svelte"], []),
+    language!("css", "CSS", ["css"], []),
+    language!("scss", "SCSS", ["scss", "sass"], []),
+    language!("less", "Less", ["less"], []),
+    language!("json", "JSON", ["json", "webmanifest"], [".prettierrc", ".babelrc"]),
+    language!("jsonc", "JSON with Comments", ["jsonc"], ["tsconfig.json", "jsconfig.json", ".eslintrc", ".eslintrc.json"]),
+    language!("yaml", "YAML", ["yaml", "yml"], []),
+    language!("toml", "TOML", ["toml"], ["Cargo.lock", "Pipfile"]),
+    language!("xml", "XML", ["xml", "xsd", "xsl", "svg", "plist", "csproj"], []),
+    language!("markdown", "Markdown", ["md", "markdown", "mdx"], []),
+    language!("latex", "LaTeX", ["tex", "sty", "cls"], []),
+    language!("sql", "SQL", ["sql"], []),
+    language!("graphql", "GraphQL", ["graphql", "gql"], []),
+    language!("proto", "Protocol Buffers", ["proto"], []),
+    language!("terraform", "Terraform", ["tf", "tfvars"], []),
+    language!("dockerfile", "Dockerfile", ["dockerfile"], ["Dockerfile", "Containerfile"]),
+    language!("makefile", "Makefile", ["mk", "mak"], ["Makefile", "makefile", "GNUmakefile"]),
+    language!("cmake", "CMake", ["cmake"], ["CMakeLists.txt"]),
+    language!("ini", "Ini", ["ini", "cfg", "conf", "properties"], [".editorconfig", ".gitconfig", ".npmrc"]),
+    language!("dotenv", "Dotenv", ["env"], [".env", ".env.local", ".env.example"]),
+    language!("ignore", "Ignore", [], [".gitignore", ".dockerignore", ".npmignore", ".prettierignore", ".ignore"]),
+    language!("diff", "Diff", ["diff", "patch"], []),
+    language!("csv", "CSV", ["csv", "tsv"], []),
+    language!("plaintext", "Plain Text", ["txt", "text", "log"], ["LICENSE", "README", "AUTHORS", "CHANGELOG"]),
+];
+
+/// Extensions that are never text, skipped without reading them.
+/// `files.textPatterns` in settings wins over this list.
+const BINARY_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "bmp", "ico", "icns", "webp", "tif", "tiff", "psd",
+    "mp3", "mp4", "m4a", "mov", "avi", "mkv", "webm", "wav", "flac", "ogg",
+    "zip", "gz", "tgz", "bz2", "xz", "zst", "7z", "rar", "tar", "jar", "war",
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx",
+    "exe", "dll", "so", "dylib", "a", "lib", "o", "obj", "class", "pyc", "wasm", "rlib",
+    "woff", "woff2", "ttf", "otf", "eot", "sqlite", "db",
+];
+
+/// Names for languages that are neither their id nor an extension.
+const LANGUAGE_ALIASES: &[(&str, &str)] = &[
+    ("python3", "python"),
+    ("golang", "go"),
+    ("node", "javascript"),
+    ("c#", "csharp"),
+    ("shell", "shellscript"),
+    ("shell-script", "shellscript"),
+    ("objc", "objective-c"),
+    ("make", "makefile"),
+    ("js2", "javascript"),
+];
+
+/// Language id for a name as written after a Markdown code fence, in an
+/// injection query or in a modeline: an id, an extension or a common alias
+/// (case-insensitive).
+pub fn language_for_name(name: &str) -> Option<&'static str> {
+    let name = name.trim().to_lowercase();
+    if let Some(language) = LANGUAGES.iter().find(|language| language.id == name) {
+        return Some(language.id);
+    }
+    if let Some(language) = LANGUAGES.iter().find(|language| language.extensions.contains(&name.as_str())) {
+        return Some(language.id);
+    }
+    LANGUAGE_ALIASES.iter().find(|(alias, _)| *alias == name).map(|(_, id)| *id)
+}
+
+/// Registry lookup by file name first, then by extension (case-insensitive).
+pub fn builtin_language_for_path(path: &Path) -> Option<&'static LanguageDef> {
+    let file_name = path.file_name()?.to_str()?;
+    if let Some(language) = LANGUAGES.iter().find(|language| language.filenames.contains(&file_name)) {
+        return Some(language);
+    }
+
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    LANGUAGES.iter().find(|language| language.extensions.contains(&extension.as_str()))
+}
+
+/// The `files` section of settings.json:
+///
+/// ```json
+/// "files": {
+///   "associations": { "*.mdx": "markdown", "Justfile": "makefile" },
+///   "binaryPatterns": ["*.dat"],
+///   "textPatterns": ["*.pdf.txt", "fixtures/**"]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FileTypeSettings {
+    pub associations: HashMap<String, String>,
+    pub binary_patterns: Vec<String>,
+    pub text_patterns: Vec<String>,
+}
+
+/// How a file should be treated before its contents are looked at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForcedKind {
+    Text,
+    Binary,
+}
+
+/// [`FileTypeSettings`] with the globs compiled, plus the built-in registry and
+/// any languages added at runtime.
+#[derive(Debug, Clone, Default)]
+pub struct FileTypes {
+    associations: Vec<(GlobMatcher, String)>,
+    binary: Option<GlobSet>,
+    text: Option<GlobSet>,
+    extra: Vec<LanguageEntry>,
+}
+
+/// Settings patterns match at any depth (`*.mdx`, `fixtures/**`) unless they are
+/// absolute, like VS Code's `files.associations`.
+fn settings_glob(pattern: &str) -> Option<Glob> {
+    let pattern = if pattern.starts_with('/') || pattern.starts_with("**") {
+        pattern.to_string()
+    } else {
+        format!("**/{}", pattern)
+    };
+    match Glob::new(&pattern) {
+        Ok(glob) => Some(glob),
+        Err(e) => {
+            // A typo in settings should not break search or the outline
+            eprintln!("[LANGUAGES] Ignoring invalid file pattern {}: {}", pattern, e);
+            None
+        }
+    }
+}
+
+fn settings_glob_set(patterns: &[String]) -> Option<GlobSet> {
+    if patterns.is_empty() {
+        return None;
+    }
+    let mut builder = GlobSetBuilder::new();
+    for glob in patterns.iter().filter_map(|p| settings_glob(p)) {
+        builder.add(glob);
+    }
+    builder.build().ok()
+}
+
+impl FileTypes {
+    pub fn from_settings(settings: &FileTypeSettings) -> Self {
+        let mut associations: Vec<(GlobMatcher, String)> = settings.associations.iter()
+            .filter_map(|(pattern, language)| Some((settings_glob(pattern)?.compile_matcher(), language.clone())))
+            .collect();
+        // Longer patterns are more specific; also keeps lookups independent of map order
+        associations.sort_by_key(|(glob, _)| Reverse(glob.glob().glob().len()));
+
+        FileTypes {
+            associations,
+            binary: settings_glob_set(&settings.binary_patterns),
+            text: settings_glob_set(&settings.text_patterns),
+            extra: Vec::new(),
+        }
+    }
+
+    /// Also recognize `languages`, ahead of the built-in registry.
+    pub fn with_languages(mut self, languages: Vec<LanguageEntry>) -> Self {
+        self.extra = languages;
+        self
+    }
+
+    /// Language id for `path`: settings associations first, then languages added
+    /// at runtime, then the built-in registry.
+    pub fn language_for_path(&self, path: &Path) -> Option<String> {
+        self.associated_language(path).or_else(|| self.registered_language(path))
+    }
+
+    /// The language `files.associations` in settings gives `path`.
+    pub fn associated_language(&self, path: &Path) -> Option<String> {
+        self.associations.iter().find(|(glob, _)| glob.is_match(path)).map(|(_, language)| language.clone())
+    }
+
+    /// The language of `path` by file name and extension, ignoring settings.
+    pub fn registered_language(&self, path: &Path) -> Option<String> {
+        if let Some(language) = self.extra_language_for_path(path) {
+            return Some(language.id.clone());
+        }
+        builtin_language_for_path(path).map(|language| language.id.to_string())
+    }
+
+    /// Like [`language_for_name`], also knowing the languages added at runtime.
+    pub fn language_for_name(&self, name: &str) -> Option<String> {
+        if let Some(id) = language_for_name(name) {
+            return Some(id.to_string());
+        }
+        let name = name.trim().to_lowercase();
+        self.extra.iter()
+            .find(|language| language.id == name || language.extensions.iter().any(|ext| ext.eq_ignore_ascii_case(&name)))
+            .map(|language| language.id.clone())
+    }
+
+    fn extra_language_for_path(&self, path: &Path) -> Option<&LanguageEntry> {
+        let file_name = path.file_name()?.to_str()?;
+        if let Some(language) = self.extra.iter().find(|language| language.filenames.iter().any(|f| f == file_name)) {
+            return Some(language);
+        }
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        self.extra.iter().find(|language| language.extensions.iter().any(|ext| ext.eq_ignore_ascii_case(&extension)))
+    }
+
+    /// Settings overrides and known binary extensions; `None` means sniff the content.
+    pub fn forced_kind(&self, path: &Path) -> Option<ForcedKind> {
+        if self.text.as_ref().is_some_and(|set| set.is_match(path)) {
+            return Some(ForcedKind::Text);
+        }
+        if self.binary.as_ref().is_some_and(|set| set.is_match(path)) {
+            return Some(ForcedKind::Binary);
+        }
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        BINARY_EXTENSIONS.contains(&extension.as_str()).then_some(ForcedKind::Binary)
+    }
+}
@@ -15,26 +15,59 @@ use serde::{Deserialize, Serialize};
 use chrono::Utc;
 use tree_sitter::{Language, Parser, Node, Tree};
 
+mod breadcrumbs;
+mod documents;
+mod error;
+mod folding;
+mod grammars;
+mod highlight;
+mod history;
+mod injections;
+mod languages;
+mod navigation;
+mod outline;
+mod queries;
+mod replace;
+mod search;
+mod selection;
+mod semantic;
+mod structural;
+mod symbol_details;
+mod syntax_diagnostics;
+mod tags;
+mod todos;
+mod workspace_symbols;
+
+use documents::Documents;
+use error::{AppError, CmdResult, ErrorCode};
+use replace::ReplaceSessions;
+use search::{SearchIndexes, SearchJobs};
+use semantic::SemanticIndexes;
+use queries::{Queries, QueryCache};
+use tags::TagQuery;
+use todos::TodoCache;
+use workspace_symbols::SymbolIndexes;
+
 #[tauri::command(rename_all = "snake_case")]
-fn get_app_support_dir(app_handle: tauri::AppHandle) -> Result<String, String> {
+fn get_app_support_dir(app_handle: tauri::AppHandle) -> CmdResult<String> {
     match app_handle.path().app_data_dir() {
         Ok(dir) => Ok(dir.to_string_lossy().to_string()),
-        Err(e) => Err(format!("Failed to get app support directory: {}", e)),
+        Err(e) => Err(AppError::internal(format!("Failed to get app support directory: {}", e))),
     }
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn run_command(command: String, args: Vec<String>, cwd: String) -> Result<String, String> {
-    let output = Command::new(command)
+fn run_command(command: String, args: Vec<String>, cwd: String) -> CmdResult<String> {
+    let output = Command::new(&command)
         .args(args)
         .current_dir(cwd)
         .output()
-        .map_err(|e| format!("Failed to execute command: {}", e))?;
+        .map_err(|e| AppError::spawn(e, &command))?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     } else {
-        Err(String::from_utf8_lossy(&output.stderr).to_string())
+        Err(AppError::process_failed(&output))
     }
 }
 
@@ -57,31 +90,31 @@ struct GitStatus {
 
 // Git commands
 #[tauri::command]
-async fn git_is_repository(path: String) -> Result<bool, String> {
+async fn git_is_repository(path: String) -> CmdResult<bool> {
     let git_dir = Path::new(&path).join(".git");
     Ok(git_dir.exists())
 }
 
 #[tauri::command]
-async fn git_init(path: String) -> Result<String, String> {
+async fn git_init(path: String) -> CmdResult<String> {
     let output = Command::new("git")
         .args(&["init"])
         .current_dir(&path)
         .output()
-        .map_err(|e| format!("Failed to initialize git repository: {}", e))?;
+        .map_err(|e| AppError::spawn(e, "git"))?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     } else {
-        Err(String::from_utf8_lossy(&output.stderr).to_string())
+        Err(AppError::process_failed(&output))
     }
 }
 
 #[tauri::command]
-async fn git_status(path: String) -> Result<GitStatus, String> {
+async fn git_status(path: String) -> CmdResult<GitStatus> {
     // Check if it's a git repository
     if !git_is_repository(path.clone()).await? {
-        return Err("Not a git repository".to_string());
+        return Err(AppError::new(ErrorCode::NotARepository, "Not a git repository").with_path(&path));
     }
 
     // Get branch name
@@ -89,7 +122,7 @@ async fn git_status(path: String) -> Result<GitStatus, String> {
         .args(&["rev-parse", "--abbrev-ref", "HEAD"])
         .current_dir(&path)
         .output()
-        .map_err(|e| format!("Failed to get branch name: {}", e))?;
+        .map_err(|e| AppError::spawn(e, "git"))?;
 
     let branch = if branch_output.status.success() {
         let branch_name = String::from_utf8_lossy(&branch_output.stdout).trim().to_string();
@@ -107,10 +140,10 @@ async fn git_status(path: String) -> Result<GitStatus, String> {
         .args(&["status", "--porcelain"])
         .current_dir(&path)
         .output()
-        .map_err(|e| format!("Failed to get git status: {}", e))?;
+        .map_err(|e| AppError::spawn(e, "git"))?;
 
     if !status_output.status.success() {
-        return Err(String::from_utf8_lossy(&status_output.stderr).to_string());
+        return Err(AppError::process_failed(&status_output));
     }
 
     let status_text = String::from_utf8_lossy(&status_output.stdout);
@@ -195,56 +228,56 @@ async fn git_status(path: String) -> Result<GitStatus, String> {
 }
 
 #[tauri::command]
-async fn git_add(path: String, file_path: String) -> Result<String, String> {
+async fn git_add(path: String, file_path: String) -> CmdResult<String> {
     let output = Command::new("git")
         .args(&["add", &file_path])
         .current_dir(&path)
         .output()
-        .map_err(|e| format!("Failed to add file: {}", e))?;
+        .map_err(|e| AppError::spawn(e, "git"))?;
 
     if output.status.success() {
         Ok("File added successfully".to_string())
     } else {
-        Err(String::from_utf8_lossy(&output.stderr).to_string())
+        Err(AppError::process_failed(&output))
     }
 }
 
 #[tauri::command]
-async fn git_reset(path: String, file_path: String) -> Result<String, String> {
+async fn git_reset(path: String, file_path: String) -> CmdResult<String> {
     let output = Command::new("git")
         .args(&["reset", "HEAD", &file_path])
         .current_dir(&path)
         .output()
-        .map_err(|e| format!("Failed to reset file: {}", e))?;
+        .map_err(|e| AppError::spawn(e, "git"))?;
 
     if output.status.success() {
         Ok("File unstaged successfully".to_string())
     } else {
-        Err(String::from_utf8_lossy(&output.stderr).to_string())
+        Err(AppError::process_failed(&output))
     }
 }
 
 #[tauri::command]
-async fn git_commit(path: String, message: String) -> Result<String, String> {
+async fn git_commit(path: String, message: String) -> CmdResult<String> {
     if message.trim().is_empty() {
-        return Err("Commit message cannot be empty".to_string());
+        return Err(AppError::invalid_input("Commit message cannot be empty"));
     }
 
     let output = Command::new("git")
         .args(&["commit", "-m", &message])
         .current_dir(&path)
         .output()
-        .map_err(|e| format!("Failed to commit: {}", e))?;
+        .map_err(|e| AppError::spawn(e, "git"))?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     } else {
-        Err(String::from_utf8_lossy(&output.stderr).to_string())
+        Err(AppError::process_failed(&output))
     }
 }
 
 #[tauri::command]
-async fn git_diff(path: String, file_path: Option<String>) -> Result<String, String> {
+async fn git_diff(path: String, file_path: Option<String>) -> CmdResult<String> {
     let mut args = vec!["diff"];
     
     if let Some(file) = &file_path {
@@ -255,17 +288,17 @@ async fn git_diff(path: String, file_path: Option<String>) -> Result<String, Str
         .args(&args)
         .current_dir(&path)
         .output()
-        .map_err(|e| format!("Failed to get diff: {}", e))?;
+        .map_err(|e| AppError::spawn(e, "git"))?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     } else {
-        Err(String::from_utf8_lossy(&output.stderr).to_string())
+        Err(AppError::process_failed(&output))
     }
 }
 
 #[tauri::command]
-async fn git_push(path: String, remote: Option<String>, branch: Option<String>) -> Result<String, String> {
+async fn git_push(path: String, remote: Option<String>, branch: Option<String>) -> CmdResult<String> {
     let mut args = vec!["push"];
     
     if let Some(remote_name) = &remote {
@@ -279,17 +312,17 @@ async fn git_push(path: String, remote: Option<String>, branch: Option<String>)
         .args(&args)
         .current_dir(&path)
         .output()
-        .map_err(|e| format!("Failed to push: {}", e))?;
+        .map_err(|e| AppError::spawn(e, "git"))?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     } else {
-        Err(String::from_utf8_lossy(&output.stderr).to_string())
+        Err(AppError::process_failed(&output))
     }
 }
 
 #[tauri::command]
-async fn git_pull(path: String, remote: Option<String>, branch: Option<String>) -> Result<String, String> {
+async fn git_pull(path: String, remote: Option<String>, branch: Option<String>) -> CmdResult<String> {
     let mut args = vec!["pull"];
     
     if let Some(remote_name) = &remote {
@@ -303,17 +336,17 @@ async fn git_pull(path: String, remote: Option<String>, branch: Option<String>)
         .args(&args)
         .current_dir(&path)
         .output()
-        .map_err(|e| format!("Failed to pull: {}", e))?;
+        .map_err(|e| AppError::spawn(e, "git"))?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     } else {
-        Err(String::from_utf8_lossy(&output.stderr).to_string())
+        Err(AppError::process_failed(&output))
     }
 }
 
 #[tauri::command]
-async fn git_fetch(path: String, remote: Option<String>) -> Result<String, String> {
+async fn git_fetch(path: String, remote: Option<String>) -> CmdResult<String> {
     let mut args = vec!["fetch"];
     
     if let Some(remote_name) = &remote {
@@ -324,12 +357,12 @@ async fn git_fetch(path: String, remote: Option<String>) -> Result<String, Strin
         .args(&args)
         .current_dir(&path)
         .output()
-        .map_err(|e| format!("Failed to fetch: {}", e))?;
+        .map_err(|e| AppError::spawn(e, "git"))?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     } else {
-        Err(String::from_utf8_lossy(&output.stderr).to_string())
+        Err(AppError::process_failed(&output))
     }
 }
 
@@ -345,11 +378,11 @@ struct LanguageServerProcess {
 type LanguageServerMap = Arc<Mutex<HashMap<String, Child>>>;
 
 // Tree-sitter language functions
-fn get_language(language_id: &str) -> Result<Language, String> {
+fn get_language(language_id: &str) -> CmdResult<Language> {
     eprintln!("[OUTLINE DEBUG] Attempting to load language: {}", language_id);
     
     let result = match language_id {
-        "javascript" | "jsx" => {
+        "javascript" | "javascriptreact" | "jsx" => {
             eprintln!("[OUTLINE DEBUG] Loading JavaScript language");
             Ok(tree_sitter_javascript::LANGUAGE.into())
         },
@@ -357,7 +390,7 @@ fn get_language(language_id: &str) -> Result<Language, String> {
             eprintln!("[OUTLINE DEBUG] Loading TypeScript language");
             Ok(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
         },
-        "tsx" => {
+        "typescriptreact" | "tsx" => {
             eprintln!("[OUTLINE DEBUG] Loading TSX language");
             Ok(tree_sitter_typescript::LANGUAGE_TSX.into())
         },
@@ -373,9 +406,76 @@ fn get_language(language_id: &str) -> Result<Language, String> {
             eprintln!("[OUTLINE DEBUG] Loading Go language");
             Ok(tree_sitter_go::LANGUAGE.into())
         },
-        _ => {
-            eprintln!("[OUTLINE DEBUG] Unsupported language: {}", language_id);
-            Err(format!("Unsupported language: {}", language_id))
+        "c" => {
+            eprintln!("[OUTLINE DEBUG] Loading C language");
+            Ok(tree_sitter_c::LANGUAGE.into())
+        },
+        "cpp" => {
+            eprintln!("[OUTLINE DEBUG] Loading C++ language");
+            Ok(tree_sitter_cpp::LANGUAGE.into())
+        },
+        "java" => {
+            eprintln!("[OUTLINE DEBUG] Loading Java language");
+            Ok(tree_sitter_java::LANGUAGE.into())
+        },
+        "csharp" => {
+            eprintln!("[OUTLINE DEBUG] Loading C# language");
+            Ok(tree_sitter_c_sharp::LANGUAGE.into())
+        },
+        "ruby" => {
+            eprintln!("[OUTLINE DEBUG] Loading Ruby language");
+            Ok(tree_sitter_ruby::LANGUAGE.into())
+        },
+        "php" => {
+            eprintln!("[OUTLINE DEBUG] Loading PHP language");
+            Ok(tree_sitter_php::LANGUAGE_PHP.into())
+        },
+        "shellscript" | "bash" | "sh" => {
+            eprintln!("[OUTLINE DEBUG] Loading Bash language");
+            Ok(tree_sitter_bash::LANGUAGE.into())
+        },
+        "lua" => {
+            eprintln!("[OUTLINE DEBUG] Loading Lua language");
+            Ok(tree_sitter_lua::LANGUAGE.into())
+        },
+        "html" => {
+            eprintln!("[OUTLINE DEBUG] Loading HTML language");
+            Ok(tree_sitter_html::LANGUAGE.into())
+        },
+        "css" => {
+            eprintln!("[OUTLINE DEBUG] Loading CSS language");
+            Ok(tree_sitter_css::LANGUAGE.into())
+        },
+        "json" | "jsonc" => {
+            eprintln!("[OUTLINE DEBUG] Loading JSON language");
+            Ok(tree_sitter_json::LANGUAGE.into())
+        },
+        "yaml" => {
+            eprintln!("[OUTLINE DEBUG] Loading YAML language");
+            Ok(tree_sitter_yaml::LANGUAGE.into())
+        },
+        "toml" => {
+            eprintln!("[OUTLINE DEBUG] Loading TOML language");
+            Ok(tree_sitter_toml_ng::LANGUAGE.into())
+        },
+        "markdown" => {
+            eprintln!("[OUTLINE DEBUG] Loading Markdown language");
+            Ok(tree_sitter_md::LANGUAGE.into())
+        },
+        // Emphasis, links and code spans; only reached through Markdown's injections
+        "markdown_inline" => {
+            eprintln!("[OUTLINE DEBUG] Loading Markdown inline language");
+            Ok(tree_sitter_md::INLINE_LANGUAGE.into())
+        },
+        _ => match grammars::installed::language(language_id) {
+            Some(language) => {
+                eprintln!("[OUTLINE DEBUG] Loading installed grammar for {}", language_id);
+                Ok(language)
+            },
+            None => {
+                eprintln!("[OUTLINE DEBUG] Unsupported language: {}", language_id);
+                Err(AppError::new(ErrorCode::Unsupported, format!("Unsupported language: {}", language_id)))
+            },
         },
     };
     
@@ -387,17 +487,21 @@ fn get_language(language_id: &str) -> Result<Language, String> {
     result
 }
 
-// Symbol information structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// Symbol information structure, shaped like LSP's `DocumentSymbol` (see `symbol_details`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct DocumentSymbol {
     name: String,
-    kind: String, // SymbolKind as number
+    kind: String, // SymbolKind name in lowercase, e.g. "function"
+    symbol_kind: u8, // SymbolKind as number
+    detail: Option<String>, // Signature or type annotation
+    modifiers: Vec<String>, // "pub", "async", "static", "abstract", "exported"
+    tags: Vec<u8>, // SymbolTag numbers: 1 is deprecated
     range: Range,
     selection_range: Range,
     children: Vec<DocumentSymbol>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 struct Range {
     start_line_number: u32,
     start_column: u32,
@@ -415,38 +519,38 @@ struct SymbolQuery {
 
 // File system commands
 #[tauri::command(rename_all = "snake_case")]
-fn read_text_file(file_path: String) -> Result<String, String> {
+fn read_text_file(file_path: String) -> CmdResult<String> {
     match fs::read_to_string(&file_path) {
         Ok(content) => Ok(content),
-        Err(e) => Err(format!("Failed to read file {}: {}", file_path, e))
+        Err(e) => Err(AppError::io(e, "Failed to read file", &file_path))
     }
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn write_text_file(file_path: String, content: String) -> Result<String, String> {
+fn write_text_file(file_path: String, content: String) -> CmdResult<String> {
     // Create parent directories if they don't exist
     if let Some(parent) = Path::new(&file_path).parent() {
         if let Err(e) = fs::create_dir_all(parent) {
-            return Err(format!("Failed to create directories: {}", e));
+            return Err(AppError::io(e, "Failed to create directories", parent));
         }
     }
     
     match fs::write(&file_path, content) {
         Ok(_) => Ok("File written successfully".to_string()),
-        Err(e) => Err(format!("Failed to write file {}: {}", file_path, e))
+        Err(e) => Err(AppError::io(e, "Failed to write file", &file_path))
     }
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn read_directory(dir_path: String) -> Result<Value, String> {
+fn read_directory(dir_path: String) -> CmdResult<Value> {
     let path = Path::new(&dir_path);
     
     if !path.exists() {
-        return Err(format!("Directory does not exist: {}", dir_path));
+        return Err(AppError::not_found(format!("Directory does not exist: {}", dir_path), path));
     }
     
     if !path.is_dir() {
-        return Err(format!("Path is not a directory: {}", dir_path));
+        return Err(AppError::new(ErrorCode::NotADirectory, format!("Path is not a directory: {}", dir_path)).with_path(path));
     }
     
     let mut entries = Vec::new();
@@ -480,7 +584,7 @@ fn read_directory(dir_path: String) -> Result<Value, String> {
                 }
             }
         },
-        Err(e) => return Err(format!("Failed to read directory: {}", e)),
+        Err(e) => return Err(AppError::io(e, "Failed to read directory", path)),
     }
     
     // Sort entries: folders first, then files, both alphabetically
@@ -511,158 +615,158 @@ fn is_directory(path: String) -> bool {
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn get_settings_file_path(app_handle: tauri::AppHandle) -> Result<String, String> {
+fn get_settings_file_path(app_handle: tauri::AppHandle) -> CmdResult<String> {
     match app_handle.path().app_config_dir() {
         Ok(config_dir) => {
             let settings_file = config_dir.join("settings.json");
             Ok(settings_file.to_string_lossy().to_string())
         },
-        Err(e) => Err(format!("Failed to get config directory: {}", e)),
+        Err(e) => Err(AppError::internal(format!("Failed to get config directory: {}", e))),
     }
 }
 
 
 
 #[tauri::command(rename_all = "snake_case")]
-fn delete_file(file_path: String) -> Result<String, String> {
+fn delete_file(file_path: String) -> CmdResult<String> {
     let path = Path::new(&file_path);
     
     if !path.exists() {
-        return Err(format!("File does not exist: {}", file_path));
+        return Err(AppError::not_found(format!("File does not exist: {}", file_path), path));
     }
     
     if path.is_dir() {
-        return Err(format!("Path is a directory, use delete_directory instead: {}", file_path));
+        return Err(AppError::new(ErrorCode::IsADirectory, format!("Path is a directory, use delete_directory instead: {}", file_path)).with_path(path));
     }
     
     match fs::remove_file(path) {
         Ok(_) => Ok("File deleted successfully".to_string()),
-        Err(e) => Err(format!("Failed to delete file {}: {}", file_path, e))
+        Err(e) => Err(AppError::io(e, "Failed to delete file", path))
     }
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn delete_directory(dir_path: String) -> Result<String, String> {
+fn delete_directory(dir_path: String) -> CmdResult<String> {
     let path = Path::new(&dir_path);
     
     if !path.exists() {
-        return Err(format!("Directory does not exist: {}", dir_path));
+        return Err(AppError::not_found(format!("Directory does not exist: {}", dir_path), path));
     }
     
     if !path.is_dir() {
-        return Err(format!("Path is not a directory: {}", dir_path));
+        return Err(AppError::new(ErrorCode::NotADirectory, format!("Path is not a directory: {}", dir_path)).with_path(path));
     }
     
     match fs::remove_dir_all(path) {
         Ok(_) => Ok("Directory deleted successfully".to_string()),
-        Err(e) => Err(format!("Failed to delete directory {}: {}", dir_path, e))
+        Err(e) => Err(AppError::io(e, "Failed to delete directory", path))
     }
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn create_directory(dir_path: String) -> Result<String, String> {
+fn create_directory(dir_path: String) -> CmdResult<String> {
     let path = Path::new(&dir_path);
     
     if path.exists() {
-        return Err(format!("Path already exists: {}", dir_path));
+        return Err(AppError::already_exists(format!("Path already exists: {}", dir_path), path));
     }
     
     match fs::create_dir_all(path) {
         Ok(_) => Ok("Directory created successfully".to_string()),
-        Err(e) => Err(format!("Failed to create directory {}: {}", dir_path, e))
+        Err(e) => Err(AppError::io(e, "Failed to create directory", path))
     }
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn rename_file(old_path: String, new_path: String) -> Result<String, String> {
+fn rename_file(old_path: String, new_path: String) -> CmdResult<String> {
     let old = Path::new(&old_path);
     let new = Path::new(&new_path);
     
     if !old.exists() {
-        return Err(format!("Source path does not exist: {}", old_path));
+        return Err(AppError::not_found(format!("Source path does not exist: {}", old_path), old));
     }
     
     if new.exists() {
-        return Err(format!("Destination path already exists: {}", new_path));
+        return Err(AppError::already_exists(format!("Destination path already exists: {}", new_path), new));
     }
     
     // Create parent directories for new path if they don't exist
     if let Some(parent) = new.parent() {
         if !parent.exists() {
             if let Err(e) = fs::create_dir_all(parent) {
-                return Err(format!("Failed to create parent directories: {}", e));
+                return Err(AppError::io(e, "Failed to create parent directories", parent));
             }
         }
     }
     
     match fs::rename(old, new) {
         Ok(_) => Ok("File renamed successfully".to_string()),
-        Err(e) => Err(format!("Failed to rename {} to {}: {}", old_path, new_path, e))
+        Err(e) => Err(AppError::io(e, &format!("Failed to rename {} to", old_path), new))
     }
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn copy_file(source_path: String, dest_path: String) -> Result<String, String> {
+fn copy_file(source_path: String, dest_path: String) -> CmdResult<String> {
     let source = Path::new(&source_path);
     let dest = Path::new(&dest_path);
     
     if !source.exists() {
-        return Err(format!("Source file does not exist: {}", source_path));
+        return Err(AppError::not_found(format!("Source file does not exist: {}", source_path), source));
     }
     
     if !source.is_file() {
-        return Err(format!("Source is not a file: {}", source_path));
+        return Err(AppError::new(ErrorCode::IsADirectory, format!("Source is not a file: {}", source_path)).with_path(source));
     }
     
     if dest.exists() {
-        return Err(format!("Destination already exists: {}", dest_path));
+        return Err(AppError::already_exists(format!("Destination already exists: {}", dest_path), dest));
     }
     
     // Create parent directories for destination if they don't exist
     if let Some(parent) = dest.parent() {
         if !parent.exists() {
             if let Err(e) = fs::create_dir_all(parent) {
-                return Err(format!("Failed to create parent directories: {}", e));
+                return Err(AppError::io(e, "Failed to create parent directories", parent));
             }
         }
     }
     
     match fs::copy(source, dest) {
         Ok(_) => Ok("File copied successfully".to_string()),
-        Err(e) => Err(format!("Failed to copy {} to {}: {}", source_path, dest_path, e))
+        Err(e) => Err(AppError::io(e, &format!("Failed to copy {} to", source_path), dest))
     }
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn move_file(source_path: String, dest_path: String) -> Result<String, String> {
+fn move_file(source_path: String, dest_path: String) -> CmdResult<String> {
     let source = Path::new(&source_path);
     let dest = Path::new(&dest_path);
     
     if !source.exists() {
-        return Err(format!("Source path does not exist: {}", source_path));
+        return Err(AppError::not_found(format!("Source path does not exist: {}", source_path), source));
     }
     
     if dest.exists() {
-        return Err(format!("Destination already exists: {}", dest_path));
+        return Err(AppError::already_exists(format!("Destination already exists: {}", dest_path), dest));
     }
     
     // Create parent directories for destination if they don't exist
     if let Some(parent) = dest.parent() {
         if !parent.exists() {
             if let Err(e) = fs::create_dir_all(parent) {
-                return Err(format!("Failed to create parent directories: {}", e));
+                return Err(AppError::io(e, "Failed to create parent directories", parent));
             }
         }
     }
     
     match fs::rename(source, dest) {
         Ok(_) => Ok("File moved successfully".to_string()),
-        Err(e) => Err(format!("Failed to move {} to {}: {}", source_path, dest_path, e))
+        Err(e) => Err(AppError::io(e, &format!("Failed to move {} to", source_path), dest))
     }
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn get_workspace_files(workspace_path: String) -> Result<Vec<Value>, String> {
+fn get_workspace_files(workspace_path: String) -> CmdResult<Vec<Value>> {
     let mut files = Vec::new();
     
     fn scan_directory(dir: &Path, base_path: &Path, files: &mut Vec<Value>) -> Result<(), std::io::Error> {
@@ -693,7 +797,7 @@ fn get_workspace_files(workspace_path: String) -> Result<Vec<Value>, String> {
     
     let workspace = Path::new(&workspace_path);
     if workspace.exists() && workspace.is_dir() {
-        scan_directory(workspace, workspace, &mut files).map_err(|e| e.to_string())?;
+        scan_directory(workspace, workspace, &mut files).map_err(|e| AppError::io(e, "Failed to scan workspace", workspace))?;
     }
     
     // Sort files alphabetically
@@ -705,8 +809,8 @@ fn get_workspace_files(workspace_path: String) -> Result<Vec<Value>, String> {
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn clipboard_copy(file_path: String, clipboard_state: tauri::State<ClipboardState>) -> Result<String, String> {
-    let mut clipboard = clipboard_state.lock().map_err(|e| format!("Failed to lock clipboard: {}", e))?;
+fn clipboard_copy(file_path: String, clipboard_state: tauri::State<ClipboardState>) -> CmdResult<String> {
+    let mut clipboard = clipboard_state.lock().map_err(|_| AppError::lock_poisoned("clipboard"))?;
     *clipboard = Some(ClipboardItem {
         path: file_path.clone(),
         is_cut: false,
@@ -715,8 +819,8 @@ fn clipboard_copy(file_path: String, clipboard_state: tauri::State<ClipboardStat
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn clipboard_cut(file_path: String, clipboard_state: tauri::State<ClipboardState>) -> Result<String, String> {
-    let mut clipboard = clipboard_state.lock().map_err(|e| format!("Failed to lock clipboard: {}", e))?;
+fn clipboard_cut(file_path: String, clipboard_state: tauri::State<ClipboardState>) -> CmdResult<String> {
+    let mut clipboard = clipboard_state.lock().map_err(|_| AppError::lock_poisoned("clipboard"))?;
     *clipboard = Some(ClipboardItem {
         path: file_path.clone(),
         is_cut: true,
@@ -725,21 +829,21 @@ fn clipboard_cut(file_path: String, clipboard_state: tauri::State<ClipboardState
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn clipboard_paste(target_dir: String, clipboard_state: tauri::State<ClipboardState>) -> Result<String, String> {
-    let mut clipboard = clipboard_state.lock().map_err(|e| format!("Failed to lock clipboard: {}", e))?;
+fn clipboard_paste(target_dir: String, clipboard_state: tauri::State<ClipboardState>) -> CmdResult<String> {
+    let mut clipboard = clipboard_state.lock().map_err(|_| AppError::lock_poisoned("clipboard"))?;
     
     let clipboard_item = match clipboard.take() {
         Some(item) => item,
-        None => return Err("Clipboard is empty".to_string()),
+        None => return Err(AppError::invalid_input("Clipboard is empty")),
     };
     
     let source_path = Path::new(&clipboard_item.path);
     if !source_path.exists() {
-        return Err(format!("Source file no longer exists: {}", clipboard_item.path));
+        return Err(AppError::not_found(format!("Source file no longer exists: {}", clipboard_item.path), source_path));
     }
     
     let file_name = source_path.file_name()
-        .ok_or("Invalid source file name")?
+        .ok_or_else(|| AppError::invalid_input("Invalid source file name").with_path(source_path))?
         .to_string_lossy();
     
     let target_path = Path::new(&target_dir).join(&*file_name);
@@ -763,7 +867,7 @@ fn clipboard_paste(target_dir: String, clipboard_state: tauri::State<ClipboardSt
         counter += 1;
         
         if counter > 100 {
-            return Err("Too many name conflicts".to_string());
+            return Err(AppError::new(ErrorCode::Conflict, "Too many name conflicts").with_path(&target_path));
         }
     }
     
@@ -773,10 +877,10 @@ fn clipboard_paste(target_dir: String, clipboard_state: tauri::State<ClipboardSt
             // For directories, we need to recursively move
             copy_dir_recursively(source_path, &final_target_path)?;
             fs::remove_dir_all(source_path)
-                .map_err(|e| format!("Failed to remove source directory: {}", e))?;
+                .map_err(|e| AppError::io(e, "Failed to remove source directory", source_path))?;
         } else {
             fs::rename(source_path, &final_target_path)
-                .map_err(|e| format!("Failed to move file: {}", e))?;
+                .map_err(|e| AppError::io(e, "Failed to move file", source_path))?;
         }
         Ok(format!("Moved {} to {}", clipboard_item.path, final_target_path.display()))
     } else {
@@ -785,7 +889,7 @@ fn clipboard_paste(target_dir: String, clipboard_state: tauri::State<ClipboardSt
             copy_dir_recursively(source_path, &final_target_path)?;
         } else {
             fs::copy(source_path, &final_target_path)
-                .map_err(|e| format!("Failed to copy file: {}", e))?;
+                .map_err(|e| AppError::io(e, "Failed to copy file", source_path))?;
         }
         
         // For copy operations, put the item back in clipboard for multiple pastes
@@ -798,8 +902,8 @@ fn clipboard_paste(target_dir: String, clipboard_state: tauri::State<ClipboardSt
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn clipboard_get_status(clipboard_state: tauri::State<ClipboardState>) -> Result<Option<(String, bool)>, String> {
-    let clipboard = clipboard_state.lock().map_err(|e| format!("Failed to lock clipboard: {}", e))?;
+fn clipboard_get_status(clipboard_state: tauri::State<ClipboardState>) -> CmdResult<Option<(String, bool)>> {
+    let clipboard = clipboard_state.lock().map_err(|_| AppError::lock_poisoned("clipboard"))?;
     match &*clipboard {
         Some(item) => Ok(Some((item.path.clone(), item.is_cut))),
         None => Ok(None),
@@ -807,26 +911,26 @@ fn clipboard_get_status(clipboard_state: tauri::State<ClipboardState>) -> Result
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn clipboard_clear(clipboard_state: tauri::State<ClipboardState>) -> Result<String, String> {
-    let mut clipboard = clipboard_state.lock().map_err(|e| format!("Failed to lock clipboard: {}", e))?;
+fn clipboard_clear(clipboard_state: tauri::State<ClipboardState>) -> CmdResult<String> {
+    let mut clipboard = clipboard_state.lock().map_err(|_| AppError::lock_poisoned("clipboard"))?;
     *clipboard = None;
     Ok("Clipboard cleared".to_string())
 }
 
 // Helper function to recursively copy directories
-fn copy_dir_recursively(src: &Path, dst: &Path) -> Result<(), String> {
+fn copy_dir_recursively(src: &Path, dst: &Path) -> CmdResult<()> {
     if !src.is_dir() {
-        return Err(format!("Source is not a directory: {}", src.display()));
+        return Err(AppError::new(ErrorCode::NotADirectory, format!("Source is not a directory: {}", src.display())).with_path(src));
     }
     
     fs::create_dir_all(dst)
-        .map_err(|e| format!("Failed to create target directory: {}", e))?;
+        .map_err(|e| AppError::io(e, "Failed to create target directory", dst))?;
     
     let entries = fs::read_dir(src)
-        .map_err(|e| format!("Failed to read source directory: {}", e))?;
+        .map_err(|e| AppError::io(e, "Failed to read source directory", src))?;
     
     for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let entry = entry.map_err(|e| AppError::io(e, "Failed to read directory entry in", src))?;
         let src_path = entry.path();
         let dst_path = dst.join(entry.file_name());
         
@@ -834,7 +938,7 @@ fn copy_dir_recursively(src: &Path, dst: &Path) -> Result<(), String> {
             copy_dir_recursively(&src_path, &dst_path)?;
         } else {
             fs::copy(&src_path, &dst_path)
-                .map_err(|e| format!("Failed to copy file {} to {}: {}", src_path.display(), dst_path.display(), e))?;
+                .map_err(|e| AppError::io(e, &format!("Failed to copy file {} to", src_path.display()), &dst_path))?;
         }
     }
     
@@ -865,7 +969,7 @@ async fn create_terminal_session(
     working_directory: Option<String>,
     app_handle: AppHandle,
     sessions: tauri::State<'_, TerminalSessions>,
-) -> Result<String, String> {
+) -> CmdResult<String> {
     use portable_pty::{CommandBuilder, PtySize};
     
     let pty_system = portable_pty::native_pty_system();
@@ -878,7 +982,7 @@ async fn create_terminal_session(
             pixel_width: 0,
             pixel_height: 0,
         })
-        .map_err(|e| format!("Failed to create pty: {}", e))?;
+        .map_err(|e| AppError::new(ErrorCode::ProcessFailed, format!("Failed to create pty: {}", e)))?;
 
     // Determine shell based on platform
     let mut shell = if cfg!(windows) {
@@ -898,7 +1002,7 @@ async fn create_terminal_session(
     let _child = pty_pair
         .slave
         .spawn_command(shell)
-        .map_err(|e| format!("Failed to spawn shell: {}", e))?;
+        .map_err(|e| AppError::new(ErrorCode::ProcessFailed, format!("Failed to spawn shell: {}", e)))?;
 
     // Get writer before storing
     let writer = pty_pair.master.take_writer().ok();
@@ -909,7 +1013,9 @@ async fn create_terminal_session(
         writer,
     };
     let terminal_session = Arc::new(Mutex::new(terminal_session));
-    sessions.lock().unwrap().insert(session_id.clone(), terminal_session.clone());
+    sessions.lock()
+        .map_err(|_| AppError::lock_poisoned("terminal sessions"))?
+        .insert(session_id.clone(), terminal_session.clone());
 
     // Start reading from the pty and emit events
     let app_handle_clone = app_handle.clone();
@@ -943,20 +1049,20 @@ async fn write_to_terminal(
     session_id: String,
     data: String,
     sessions: tauri::State<'_, TerminalSessions>,
-) -> Result<(), String> {
+) -> CmdResult<()> {
     
-    let sessions = sessions.lock().unwrap();
+    let sessions = sessions.lock().map_err(|_| AppError::lock_poisoned("terminal sessions"))?;
     if let Some(terminal_session) = sessions.get(&session_id) {
-        let mut session = terminal_session.lock().unwrap();
+        let mut session = terminal_session.lock().map_err(|_| AppError::lock_poisoned("terminal session"))?;
         if let Some(ref mut writer) = session.writer {
-            writer.write_all(data.as_bytes()).map_err(|e| format!("Failed to write: {}", e))?;
-            writer.flush().map_err(|e| format!("Failed to flush: {}", e))?;
+            writer.write_all(data.as_bytes())?;
+            writer.flush()?;
             Ok(())
         } else {
-            Err("Terminal writer not available".to_string())
+            Err(AppError::new(ErrorCode::Io, "Terminal writer not available"))
         }
     } else {
-        Err("Terminal session not found".to_string())
+        Err(terminal_session_not_found(&session_id))
     }
 }
 
@@ -964,8 +1070,8 @@ async fn write_to_terminal(
 async fn close_terminal_session(
     session_id: String,
     sessions: tauri::State<'_, TerminalSessions>,
-) -> Result<(), String> {
-    let mut sessions = sessions.lock().unwrap();
+) -> CmdResult<()> {
+    let mut sessions = sessions.lock().map_err(|_| AppError::lock_poisoned("terminal sessions"))?;
     sessions.remove(&session_id);
     Ok(())
 }
@@ -976,207 +1082,31 @@ async fn resize_terminal(
     rows: u16,
     cols: u16,
     sessions: tauri::State<'_, TerminalSessions>,
-) -> Result<(), String> {
+) -> CmdResult<()> {
     use portable_pty::PtySize;
     
-    let sessions = sessions.lock().unwrap();
+    let sessions = sessions.lock().map_err(|_| AppError::lock_poisoned("terminal sessions"))?;
     if let Some(terminal_session) = sessions.get(&session_id) {
-        let session = terminal_session.lock().unwrap();
+        let session = terminal_session.lock().map_err(|_| AppError::lock_poisoned("terminal session"))?;
         session.pty_pair.master.resize(PtySize {
             rows,
             cols,
             pixel_width: 0,
             pixel_height: 0,
-        }).map_err(|e| format!("Failed to resize: {}", e))?;
+        }).map_err(|e| AppError::new(ErrorCode::Io, format!("Failed to resize: {}", e)))?;
         Ok(())
     } else {
-        Err("Terminal session not found".to_string())
-    }
-}
-
-// Search in files command
-#[derive(serde::Serialize)]
-struct SearchMatch {
-    #[serde(rename = "lineNumber")]
-    line_number: usize,
-    column: usize,
-    text: String,
-}
-
-#[derive(serde::Serialize)]
-struct SearchResult {
-    path: String,
-    name: String,
-    #[serde(rename = "relativePath")]
-    relative_path: String,
-    matches: Vec<SearchMatch>,
-}
-
-#[tauri::command(rename_all = "snake_case")]
-fn search_in_files(
-    workspace_path: String,
-    query: String,
-    use_regex: bool,
-    case_sensitive: bool,
-    whole_word: bool,
-    max_results: usize,
-) -> Result<Vec<SearchResult>, String> {
-    use std::path::Path;
-    use regex::Regex;
-    
-    if query.is_empty() {
-        return Ok(vec![]);
-    }
-    
-    let workspace = Path::new(&workspace_path);
-    if !workspace.exists() || !workspace.is_dir() {
-        return Err("Invalid workspace path".to_string());
-    }
-    
-    // Create regex pattern
-    let pattern = if use_regex {
-        query.clone()
-    } else {
-        // Escape regex special characters
-        let escaped = regex::escape(&query);
-        if whole_word {
-            format!(r"\b{}\b", escaped)
-        } else {
-            escaped
-        }
-    };
-    
-    let regex = match if case_sensitive {
-        Regex::new(&pattern)
-    } else {
-        regex::RegexBuilder::new(&pattern)
-            .case_insensitive(true)
-            .build()
-    } {
-        Ok(r) => r,
-        Err(e) => return Err(format!("Invalid regex pattern: {}", e)),
-    };
-    
-    let mut results = Vec::new();
-    let mut total_matches = 0;
-    
-    // Walk through directory recursively
-    if let Err(e) = walk_directory(
-        workspace,
-        workspace,
-        &regex,
-        &mut results,
-        &mut total_matches,
-        max_results,
-    ) {
-        return Err(format!("Search failed: {}", e));
-    }
-    
-    Ok(results)
-}
-
-fn walk_directory(
-    current_path: &Path,
-    workspace_root: &Path,
-    regex: &regex::Regex,
-    results: &mut Vec<SearchResult>,
-    total_matches: &mut usize,
-    max_results: usize,
-) -> Result<(), Box<dyn std::error::Error>> {
-    if *total_matches >= max_results {
-        return Ok(());
-    }
-    
-    for entry in fs::read_dir(current_path)? {
-        let entry = entry?;
-        let path = entry.path();
-        
-        if path.is_dir() {
-            // Skip hidden directories and common ignored directories
-            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                if name.starts_with('.') || 
-                   name == "node_modules" || 
-                   name == "target" || 
-                   name == "dist" || 
-                   name == "build" {
-                    continue;
-                }
-            }
-            walk_directory(&path, workspace_root, regex, results, total_matches, max_results)?;
-        } else if path.is_file() {
-            // Only search text files
-            if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
-                let text_extensions = [
-                    "txt", "md", "rs", "js", "ts", "jsx", "tsx", "py", "java", "c", "cpp", "h", "hpp",
-                    "css", "scss", "sass", "html", "htm", "xml", "json", "yaml", "yml", "toml",
-                    "go", "php", "rb", "swift", "kt", "scala", "sh", "bash", "zsh", "fish",
-                    "sql", "csv", "log", "config", "conf", "ini", "env"
-                ];
-                
-                if !text_extensions.contains(&extension.to_lowercase().as_str()) {
-                    continue;
-                }
-            } else {
-                // Skip files without extensions (likely binary)
-                continue;
-            }
-            
-            if let Ok(content) = fs::read_to_string(&path) {
-                let matches = search_in_content(&content, regex);
-                if !matches.is_empty() {
-                    let relative_path = path.strip_prefix(workspace_root)
-                        .unwrap_or(&path)
-                        .to_string_lossy()
-                        .to_string();
-                    
-                    let name = path.file_name()
-                        .unwrap_or_default()
-                        .to_string_lossy()
-                        .to_string();
-                    
-                    results.push(SearchResult {
-                        path: path.to_string_lossy().to_string(),
-                        name,
-                        relative_path,
-                        matches,
-                    });
-                    
-                    *total_matches += 1;
-                    if *total_matches >= max_results {
-                        break;
-                    }
-                }
-            }
-        }
+        Err(terminal_session_not_found(&session_id))
     }
-    
-    Ok(())
 }
 
-fn search_in_content(content: &str, regex: &regex::Regex) -> Vec<SearchMatch> {
-    let mut matches = Vec::new();
-    
-    for (line_num, line) in content.lines().enumerate() {
-        for mat in regex.find_iter(line) {
-            matches.push(SearchMatch {
-                line_number: line_num + 1,
-                column: mat.start() + 1,
-                text: line.to_string(),
-            });
-            
-            // Limit matches per file
-            if matches.len() >= 10 {
-                return matches;
-            }
-        }
-    }
-    
-    matches
+fn terminal_session_not_found(session_id: &str) -> AppError {
+    AppError::new(ErrorCode::SessionNotFound, format!("Terminal session not found: {}", session_id))
 }
 
 // Command existence check
 #[tauri::command(rename_all = "snake_case")]
-fn check_command_exists(command: String) -> Result<bool, String> {
+fn check_command_exists(command: String) -> CmdResult<bool> {
     // Cross-platform command existence check
     let cmd = if cfg!(target_os = "windows") {
         Command::new("where")
@@ -1203,15 +1133,32 @@ fn check_command_exists(command: String) -> Result<bool, String> {
 fn parse_document_symbols(
     source_code: String,
     language_id: String,
-    _file_path: String,
+    file_path: String,
     queries: Vec<SymbolQuery>,
-) -> Result<Vec<DocumentSymbol>, String> {
+    documents: tauri::State<'_, Documents>,
+    query_cache: tauri::State<'_, QueryCache>,
+    app_handle: AppHandle,
+) -> CmdResult<Vec<DocumentSymbol>> {
     // Debug logging for release builds
     eprintln!("[OUTLINE DEBUG] Starting parse_document_symbols");
     eprintln!("[OUTLINE DEBUG] Language: {}", language_id);
     eprintln!("[OUTLINE DEBUG] Source code length: {}", source_code.len());
     eprintln!("[OUTLINE DEBUG] Queries count: {}", queries.len());
 
+    let query_files = Queries::new(&query_cache, &app_handle);
+
+    // An open document is brought up to date by re-parsing just the changed span
+    let cached = documents::with_document(&documents, &file_path, |document| {
+        if document.language_id() != language_id {
+            return None;
+        }
+        Some(document.set_text(&source_code).map(|()| document.symbols(&queries, &query_files)))
+    });
+    if let Ok(Some(symbols)) = cached {
+        eprintln!("[OUTLINE DEBUG] Re-parsed open document {}", file_path);
+        return symbols;
+    }
+
     let language = match get_language(&language_id) {
         Ok(lang) => {
             eprintln!("[OUTLINE DEBUG] Successfully got language for: {}", language_id);
@@ -1230,7 +1177,7 @@ fn parse_document_symbols(
         },
         Err(e) => {
             eprintln!("[OUTLINE DEBUG] Failed to set parser language: {}", e);
-            return Err(format!("Failed to set language: {}", e));
+            return Err(AppError::internal(format!("Failed to set language: {}", e)));
         }
     }
 
@@ -1243,11 +1190,11 @@ fn parse_document_symbols(
         },
         None => {
             eprintln!("[OUTLINE DEBUG] Failed to parse source code");
-            return Err("Failed to parse source code".to_string());
+            return Err(AppError::internal("Failed to parse source code"));
         }
     };
 
-    let symbols = extract_symbols_from_tree(&tree, &source_code, &language_id, &queries);
+    let symbols = injections::combined_symbols(&tree, &source_code, &language_id, &queries, &query_files);
     eprintln!("[OUTLINE DEBUG] Extracted {} symbols", symbols.len());
     
     // Log each symbol for debugging
@@ -1258,39 +1205,54 @@ fn parse_document_symbols(
     Ok(symbols)
 }
 
-fn extract_symbols_from_tree(tree: &Tree, source_code: &str, language_id: &str, queries: &Vec<SymbolQuery>) -> Vec<DocumentSymbol> {
+// Embedded code (see `injections`) has no user queries, and a language without a
+// tags query or an extractor of its own, like Markdown's inline text, adds
+// nothing rather than generic guesses.
+fn extract_layer_symbols(tree: &Tree, source_code: &str, language_id: &str, tags: Option<&TagQuery>) -> Vec<DocumentSymbol> {
+    let has_extractor = matches!(
+        language_id,
+        "javascript" | "javascriptreact" | "jsx" | "typescript" | "typescriptreact" | "tsx" | "python" | "rust" | "go"
+    );
+    if tags.is_some() || has_extractor {
+        extract_symbols_from_tree(tree, source_code, language_id, &Vec::new(), tags)
+    } else {
+        let mut symbols = outline::extract_symbols(tree.root_node(), source_code, language_id).unwrap_or_default();
+        symbol_details::annotate(&mut symbols, tree.root_node(), source_code, language_id);
+        symbols
+    }
+}
+
+// Also runs over every file of the workspace for the symbol index, so it stays quiet.
+// A tags query, when the language has one, takes over from the built-in extractors.
+fn extract_symbols_from_tree(tree: &Tree, source_code: &str, language_id: &str, queries: &Vec<SymbolQuery>, tags: Option<&TagQuery>) -> Vec<DocumentSymbol> {
+    let mut symbols = extract_outline(tree, source_code, language_id, queries, tags);
+    symbol_details::annotate(&mut symbols, tree.root_node(), source_code, language_id);
+    symbols
+}
+
+fn extract_outline(tree: &Tree, source_code: &str, language_id: &str, queries: &Vec<SymbolQuery>, tags: Option<&TagQuery>) -> Vec<DocumentSymbol> {
     let root_node = tree.root_node();
+    if let Some(tags) = tags {
+        return tags.symbols(root_node, source_code);
+    }
     let mut symbols = Vec::new();
     
-    eprintln!("[OUTLINE DEBUG] Extracting symbols for language: {}", language_id);
-    eprintln!("[OUTLINE DEBUG] Root node kind: {}", root_node.kind());
-    eprintln!("[OUTLINE DEBUG] Root node child count: {}", root_node.child_count());
-    
     match language_id {
-        "javascript" | "jsx" | "typescript" | "tsx" => {
-            eprintln!("[OUTLINE DEBUG] Using JS symbol extraction");
+        "javascript" | "javascriptreact" | "jsx" | "typescript" | "typescriptreact" | "tsx" => {
             // Use only the hierarchical Tree-sitter approach for JS (it was working well)
             extract_js_symbols(root_node, source_code, &mut symbols, queries);
         },
-        "python" => {
-            eprintln!("[OUTLINE DEBUG] Using Python symbol extraction");
-            extract_tree_sitter_symbols(root_node, source_code, &mut symbols, language_id);
-        },
-        "rust" => {
-            eprintln!("[OUTLINE DEBUG] Using Rust symbol extraction");
-            extract_tree_sitter_symbols(root_node, source_code, &mut symbols, language_id);
-        },
-        "go" => {
-            eprintln!("[OUTLINE DEBUG] Using Go symbol extraction");
+        "python" | "rust" | "go" => {
             extract_tree_sitter_symbols(root_node, source_code, &mut symbols, language_id);
         },
         _ => {
-            eprintln!("[OUTLINE DEBUG] Using generic symbol extraction for: {}", language_id);
+            if let Some(outline) = outline::extract_symbols(root_node, source_code, language_id) {
+                return outline;
+            }
             extract_tree_sitter_symbols(root_node, source_code, &mut symbols, "generic");
         }
     }
     
-    eprintln!("[OUTLINE DEBUG] Finished extracting, found {} symbols", symbols.len());
     symbols
 }
 
@@ -1358,6 +1320,7 @@ fn create_function_symbol(node: Node, source_code: &str, queries: &Vec<SymbolQue
         range: range.clone(),
         selection_range: range,
         children,
+        ..Default::default()
     })
 }
 
@@ -1382,6 +1345,7 @@ fn create_class_symbol(node: Node, source_code: &str, queries: &Vec<SymbolQuery>
         range: range.clone(),
         selection_range: range,
         children,
+        ..Default::default()
     })
 }
 
@@ -1409,6 +1373,7 @@ fn extract_variable_declarations(node: Node, source_code: &str, symbols: &mut Ve
                                 range: range.clone(),
                                 selection_range: range,
                                 children,
+                                ..Default::default()
                             });
                         },
                         _ => {
@@ -1418,6 +1383,7 @@ fn extract_variable_declarations(node: Node, source_code: &str, symbols: &mut Ve
                                 range: range.clone(),
                                 selection_range: range,
                                 children: Vec::new(),
+                                ..Default::default()
                             });
                         }
                     }
@@ -1428,6 +1394,7 @@ fn extract_variable_declarations(node: Node, source_code: &str, symbols: &mut Ve
                         range: range.clone(),
                         selection_range: range,
                         children: Vec::new(),
+                        ..Default::default()
                     });
                 }
             }
@@ -1454,7 +1421,6 @@ fn create_control_flow_symbol(node: Node, source_code: &str, queries: &Vec<Symbo
         "if_statement" => {
             if let Some(condition) = node.child_by_field_name("condition") {
                 let condition_text = condition.utf8_text(source_code.as_bytes()).unwrap_or("").trim();
-                eprintln!("[OUTLINE DEBUG] JavaScript if condition raw text: '{}'", condition_text);
                 // Clean up the condition text - remove extra parentheses if they exist
                 let clean_condition = condition_text
                     .trim_matches(|c| c == '(' || c == ')' || c == ' ')
@@ -1462,7 +1428,6 @@ fn create_control_flow_symbol(node: Node, source_code: &str, queries: &Vec<Symbo
                     .chars()
                     .take(50) // Limit to 50 characters for readability
                     .collect::<String>();
-                eprintln!("[OUTLINE DEBUG] JavaScript if condition cleaned: '{}'", clean_condition);
                 if clean_condition.is_empty() {
                     "if".to_string()
                 } else {
@@ -1495,6 +1460,7 @@ fn create_control_flow_symbol(node: Node, source_code: &str, queries: &Vec<Symbo
         range: range.clone(),
         selection_range: range,
         children,
+        ..Default::default()
     })
 }
 
@@ -1534,6 +1500,7 @@ fn create_call_symbol(node: Node, source_code: &str) -> Option<DocumentSymbol> {
         range: range.clone(),
         selection_range: range,
         children: Vec::new(),
+        ..Default::default()
     })
 }
 
@@ -1591,6 +1558,7 @@ fn extract_python_node(node: Node, source_code: &str, symbols: &mut Vec<Document
                     range: range.clone(),
                     selection_range: range,
                     children,
+                    ..Default::default()
                 });
                 return true;
             }
@@ -1612,6 +1580,7 @@ fn extract_python_node(node: Node, source_code: &str, symbols: &mut Vec<Document
                     range: range.clone(),
                     selection_range: range,
                     children,
+                    ..Default::default()
                 });
                 return true;
             }
@@ -1639,6 +1608,7 @@ fn extract_python_node(node: Node, source_code: &str, symbols: &mut Vec<Document
                         range,
                         selection_range,
                         children: Vec::new(),
+                        ..Default::default()
                     });
                     return true;
                 }
@@ -1655,6 +1625,7 @@ fn extract_python_node(node: Node, source_code: &str, symbols: &mut Vec<Document
                             range,
                             selection_range,
                             children: Vec::new(),
+                            ..Default::default()
                         });
                         return true;
                     }
@@ -1665,7 +1636,6 @@ fn extract_python_node(node: Node, source_code: &str, symbols: &mut Vec<Document
             // Handle if statements in Python
             if let Some(condition) = node.child_by_field_name("condition") {
                 let condition_text = condition.utf8_text(source_code.as_bytes()).unwrap_or("").trim();
-                eprintln!("[OUTLINE DEBUG] Python if condition raw text: '{}'", condition_text);
                 // Clean up the condition text and limit length for readability
                 let clean_condition = condition_text
                     .trim_matches(|c| c == '(' || c == ')' || c == '+' || c == ' ')
@@ -1673,7 +1643,6 @@ fn extract_python_node(node: Node, source_code: &str, symbols: &mut Vec<Document
                     .chars()
                     .take(50) // Limit to 50 characters for readability
                     .collect::<String>();
-                eprintln!("[OUTLINE DEBUG] Python if condition cleaned: '{}'", clean_condition);
                 let name = if clean_condition.is_empty() {
                     "if".to_string()
                 } else {
@@ -1698,6 +1667,7 @@ fn extract_python_node(node: Node, source_code: &str, symbols: &mut Vec<Document
                     range: range.clone(),
                     selection_range: range,
                     children,
+                    ..Default::default()
                 });
                 return true;
             }
@@ -1723,6 +1693,7 @@ fn extract_python_node(node: Node, source_code: &str, symbols: &mut Vec<Document
                         range: range.clone(),
                         selection_range: range,
                         children,
+                        ..Default::default()
                     });
                     return true;
                 }
@@ -1748,6 +1719,7 @@ fn extract_python_node(node: Node, source_code: &str, symbols: &mut Vec<Document
                     range: range.clone(),
                     selection_range: range,
                     children,
+                    ..Default::default()
                 });
                 return true;
             }
@@ -1763,6 +1735,7 @@ fn extract_python_node(node: Node, source_code: &str, symbols: &mut Vec<Document
                         range: node_to_range(node),
                         selection_range: node_to_range(function_node),
                         children: Vec::new(),
+                        ..Default::default()
                     });
                     return true;
                 }
@@ -1792,6 +1765,7 @@ fn extract_rust_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSy
                     range: range.clone(),
                     selection_range: range,
                     children,
+                    ..Default::default()
                 });
                 return true;
             }
@@ -1813,6 +1787,7 @@ fn extract_rust_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSy
                     range: range.clone(),
                     selection_range: range,
                     children,
+                    ..Default::default()
                 });
                 return true;
             }
@@ -1828,6 +1803,7 @@ fn extract_rust_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSy
                         range: node_to_range(node),
                         selection_range: node_to_range(pattern),
                         children: Vec::new(),
+                        ..Default::default()
                     });
                     return true;
                 }
@@ -1843,6 +1819,7 @@ fn extract_rust_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSy
                                 range: node_to_range(node),
                                 selection_range: node_to_range(child),
                                 children: Vec::new(),
+                                ..Default::default()
                             });
                         }
                     }
@@ -1859,6 +1836,7 @@ fn extract_rust_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSy
                                 range: node_to_range(node),
                                 selection_range: node_to_range(inner_pattern),
                                 children: Vec::new(),
+                                ..Default::default()
                             });
                             return true;
                         }
@@ -1877,6 +1855,7 @@ fn extract_rust_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSy
                     range: node_to_range(node),
                     selection_range: node_to_range(name_node),
                     children: Vec::new(),
+                    ..Default::default()
                 });
                 return true;
             }
@@ -1892,6 +1871,7 @@ fn extract_rust_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSy
                         range: node_to_range(node),
                         selection_range: node_to_range(macro_node),
                         children: Vec::new(),
+                        ..Default::default()
                     });
                     return true;
                 }
@@ -1901,7 +1881,6 @@ fn extract_rust_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSy
             // Handle if expressions in Rust
             if let Some(condition) = node.child_by_field_name("condition") {
                 let condition_text = condition.utf8_text(source_code.as_bytes()).unwrap_or("").trim();
-                eprintln!("[OUTLINE DEBUG] Rust if condition raw text: '{}'", condition_text);
                 // Clean up the condition text and limit length for readability
                 let clean_condition = condition_text
                     .trim_matches(|c| c == '(' || c == ')' || c == '+' || c == ' ')
@@ -1909,7 +1888,6 @@ fn extract_rust_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSy
                     .chars()
                     .take(50) // Limit to 50 characters for readability
                     .collect::<String>();
-                eprintln!("[OUTLINE DEBUG] Rust if condition cleaned: '{}'", clean_condition);
                 let name = if clean_condition.is_empty() {
                     "if".to_string()
                 } else {
@@ -1934,6 +1912,7 @@ fn extract_rust_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSy
                     range: range.clone(),
                     selection_range: range,
                     children,
+                    ..Default::default()
                 });
                 return true;
             }
@@ -1959,6 +1938,7 @@ fn extract_rust_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSy
                         range: range.clone(),
                         selection_range: range,
                         children,
+                        ..Default::default()
                     });
                     return true;
                 }
@@ -1984,6 +1964,7 @@ fn extract_rust_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSy
                     range: range.clone(),
                     selection_range: range,
                     children,
+                    ..Default::default()
                 });
                 return true;
             }
@@ -2005,6 +1986,7 @@ fn extract_rust_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSy
                 range: range.clone(),
                 selection_range: range,
                 children,
+                ..Default::default()
             });
             return true;
         },
@@ -2028,6 +2010,7 @@ fn extract_rust_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSy
                     range: range.clone(),
                     selection_range: range,
                     children,
+                    ..Default::default()
                 });
                 return true;
             }
@@ -2042,6 +2025,7 @@ fn extract_rust_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSy
                     range: node_to_range(node),
                     selection_range: node_to_range(function_node),
                     children: Vec::new(),
+                    ..Default::default()
                 });
                 return true;
             }
@@ -2070,6 +2054,7 @@ fn extract_go_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSymb
                     range: range.clone(),
                     selection_range: range,
                     children,
+                    ..Default::default()
                 });
                 return true;
             }
@@ -2083,6 +2068,7 @@ fn extract_go_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSymb
                     range: node_to_range(node),
                     selection_range: node_to_range(name_node),
                     children: Vec::new(),
+                    ..Default::default()
                 });
                 return true;
             }
@@ -2100,6 +2086,7 @@ fn extract_go_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSymb
                             range: node_to_range(child),
                             selection_range: node_to_range(name_node),
                             children: Vec::new(),
+                            ..Default::default()
                         });
                     }
                 }
@@ -2116,6 +2103,7 @@ fn extract_go_node(node: Node, source_code: &str, symbols: &mut Vec<DocumentSymb
                         range: node_to_range(node),
                         selection_range: node_to_range(function_node),
                         children: Vec::new(),
+                        ..Default::default()
                     });
                     return true;
                 }
@@ -2138,6 +2126,7 @@ fn extract_generic_node(node: Node, source_code: &str, symbols: &mut Vec<Documen
                 range: node_to_range(node),
                 selection_range: node_to_range(node),
                 children: Vec::new(),
+                ..Default::default()
             });
             return true;
         }
@@ -2151,6 +2140,7 @@ fn extract_generic_node(node: Node, source_code: &str, symbols: &mut Vec<Documen
                 range: node_to_range(node),
                 selection_range: node_to_range(node),
                 children: Vec::new(),
+                ..Default::default()
             });
             return true;
         }
@@ -2179,8 +2169,8 @@ fn extract_name_from_node(node: Node, source_code: &str) -> Option<String> {
 use std::io::BufRead;
 
 #[tauri::command(rename_all = "snake_case")]
-async fn shutdown_all_language_servers(state: tauri::State<'_, LanguageServerMap>) -> Result<(), String> {
-    let mut processes = state.lock().map_err(|e| format!("Failed to lock processes: {}", e))?;
+async fn shutdown_all_language_servers(state: tauri::State<'_, LanguageServerMap>) -> CmdResult<()> {
+    let mut processes = state.lock().map_err(|_| AppError::lock_poisoned("language server processes"))?;
     for (_, mut child) in processes.drain() {
         let _ = child.kill();
     }
@@ -2194,7 +2184,7 @@ async fn start_language_server(
     language: String,
     state: tauri::State<'_, LanguageServerMap>,
     app_handle: tauri::AppHandle,
-) -> Result<String, String> {
+) -> CmdResult<String> {
     let process_id = format!("{}_{}", language, Utc::now().timestamp_millis());
 
     let mut cmd = Command::new(&command);
@@ -2205,8 +2195,8 @@ async fn start_language_server(
 
     match cmd.spawn() {
         Ok(mut child) => {
-            let stdout = child.stdout.take().ok_or("Failed to take stdout")?;
-            let stderr = child.stderr.take().ok_or("Failed to take stderr")?;
+            let stdout = child.stdout.take().ok_or_else(|| AppError::internal("Failed to take stdout"))?;
+            let stderr = child.stderr.take().ok_or_else(|| AppError::internal("Failed to take stderr"))?;
 
             let listener_app_handle = app_handle.clone();
             // Stderr listener
@@ -2261,11 +2251,11 @@ async fn start_language_server(
                 }
             });
 
-            let mut processes = state.lock().map_err(|e| format!("Failed to lock processes: {}", e))?;
+            let mut processes = state.lock().map_err(|_| AppError::lock_poisoned("language server processes"))?;
             processes.insert(process_id.clone(), child);
             Ok(process_id)
         },
-        Err(e) => Err(format!("Failed to start language server '{}': {}", command, e)),
+        Err(e) => Err(AppError::spawn(e, &format!("language server '{}'", command))),
     }
 }
 
@@ -2274,20 +2264,20 @@ async fn send_lsp_request(
     process_id: String,
     message: String,
     state: tauri::State<'_, LanguageServerMap>,
-) -> Result<(), String> {
-    let mut processes = state.lock().map_err(|e| format!("Failed to lock processes: {}", e))?;
+) -> CmdResult<()> {
+    let mut processes = state.lock().map_err(|_| AppError::lock_poisoned("language server processes"))?;
 
     if let Some(process) = processes.get_mut(&process_id) {
         if let Some(stdin) = process.stdin.as_mut() {
             let request = format!("Content-Length: {}\r\n\r\n{}", message.len(), message);
-            stdin.write_all(request.as_bytes()).map_err(|e| format!("Failed to write to process: {}", e))?;
-            stdin.flush().map_err(|e| format!("Failed to flush stdin: {}", e))?;
+            stdin.write_all(request.as_bytes()).map_err(|e| lsp_pipe_error(e, &process_id))?;
+            stdin.flush().map_err(|e| lsp_pipe_error(e, &process_id))?;
             Ok(())
         } else {
-            Err("Process stdin not available".to_string())
+            Err(AppError::new(ErrorCode::Io, "Process stdin not available"))
         }
     } else {
-        Err(format!("Language server process not found: {}", process_id))
+        Err(language_server_not_found(&process_id))
     }
 }
 
@@ -2295,16 +2285,16 @@ async fn send_lsp_request(
 async fn stop_language_server(
     process_id: String,
     state: tauri::State<'_, LanguageServerMap>
-) -> Result<String, String> {
-    let mut processes = state.lock().map_err(|e| format!("Failed to lock processes: {}", e))?;
+) -> CmdResult<String> {
+    let mut processes = state.lock().map_err(|_| AppError::lock_poisoned("language server processes"))?;
     
     if let Some(mut process) = processes.remove(&process_id) {
         match process.kill() {
             Ok(_) => Ok("Language server stopped".to_string()),
-            Err(e) => Err(format!("Failed to stop language server: {}", e))
+            Err(e) => Err(AppError::new(ErrorCode::ProcessFailed, format!("Failed to stop language server: {}", e)))
         }
     } else {
-        Err(format!("Language server process not found: {}", process_id))
+        Err(language_server_not_found(&process_id))
     }
 }
 
@@ -2313,28 +2303,42 @@ async fn send_lsp_notification(
     process_id: String,
     message: String,
     state: tauri::State<'_, LanguageServerMap>
-) -> Result<(), String> {
-    let mut processes = state.lock().map_err(|e| format!("Failed to lock processes: {}", e))?;
+) -> CmdResult<()> {
+    let mut processes = state.lock().map_err(|_| AppError::lock_poisoned("language server processes"))?;
     
     if let Some(process) = processes.get_mut(&process_id) {
         if let Some(stdin) = process.stdin.as_mut() {
             let notification = format!("Content-Length: {}\r\n\r\n{}", message.len(), message);
-            stdin.write_all(notification.as_bytes()).map_err(|e| format!("Failed to write to process: {}", e))?;
-            stdin.flush().map_err(|e| format!("Failed to flush stdin: {}", e))?;
+            stdin.write_all(notification.as_bytes()).map_err(|e| lsp_pipe_error(e, &process_id))?;
+            stdin.flush().map_err(|e| lsp_pipe_error(e, &process_id))?;
             Ok(())
         } else {
-            Err("Process stdin not available".to_string())
+            Err(AppError::new(ErrorCode::Io, "Process stdin not available"))
         }
     } else {
-        Err(format!("Language server process not found: {}", process_id))
+        Err(language_server_not_found(&process_id))
     }
 }
 
+fn language_server_not_found(process_id: &str) -> AppError {
+    AppError::new(ErrorCode::SessionNotFound, format!("Language server process not found: {}", process_id))
+}
+
+fn lsp_pipe_error(err: std::io::Error, process_id: &str) -> AppError {
+    let mut error = AppError::from(err);
+    error.message = format!("Failed to write to language server {}: {}", process_id, error.message);
+    error
+}
+
 // Test tree-sitter languages at startup
 fn test_tree_sitter_languages() {
     eprintln!("[OUTLINE DEBUG] Testing tree-sitter languages at startup...");
     
-    let languages = vec!["javascript", "typescript", "python", "rust", "go"];
+    let languages = vec![
+        "javascript", "typescript", "typescriptreact", "python", "rust", "go",
+        "c", "cpp", "java", "csharp", "ruby", "php", "shellscript", "lua",
+        "html", "css", "json", "yaml", "toml", "markdown",
+    ];
     for lang in languages {
         match get_language(lang) {
             Ok(_) => eprintln!("[OUTLINE DEBUG] ✅ {} language loaded successfully", lang),
@@ -2352,11 +2356,31 @@ fn main() {
     let terminal_sessions: TerminalSessions = Arc::new(Mutex::new(HashMap::new()));
     let clipboard_state: ClipboardState = Arc::new(Mutex::new(None));
     let language_servers: LanguageServerMap = Arc::new(Mutex::new(HashMap::new()));
+    let search_jobs: SearchJobs = Arc::new(Mutex::new(HashMap::new()));
+    let search_indexes: SearchIndexes = Arc::new(Mutex::new(HashMap::new()));
+    let todo_cache: TodoCache = Arc::new(Mutex::new(HashMap::new()));
+    let symbol_indexes: SymbolIndexes = Arc::new(Mutex::new(HashMap::new()));
+    let documents: Documents = Arc::new(Mutex::new(HashMap::new()));
+    let query_cache: QueryCache = Arc::new(Mutex::new(HashMap::new()));
+    let semantic_indexes: SemanticIndexes = Arc::new(Mutex::new(HashMap::new()));
+    let replace_sessions: ReplaceSessions = Arc::new(Mutex::new(Default::default()));
     
     tauri::Builder::default()
         .manage(terminal_sessions)
         .manage(clipboard_state)
         .manage(language_servers)
+        .manage(search_jobs)
+        .manage(search_indexes)
+        .manage(todo_cache)
+        .manage(symbol_indexes)
+        .manage(documents)
+        .manage(query_cache)
+        .manage(semantic_indexes)
+        .manage(replace_sessions)
+        .setup(|app| {
+            grammars::load_installed(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             read_text_file,
             write_text_file,
@@ -2380,7 +2404,34 @@ fn main() {
             write_to_terminal,
             close_terminal_session,
             resize_terminal,
-            search_in_files,
+            search::search_in_files,
+            search::start_workspace_search,
+            search::cancel_workspace_search,
+            search::enable_search_index,
+            search::disable_search_index,
+            search::get_search_index_status,
+            history::start_history_search,
+            structural::structural_search,
+            todos::scan_todos,
+            todos::refresh_todos_for_file,
+            workspace_symbols::start_symbol_index,
+            workspace_symbols::stop_symbol_index,
+            workspace_symbols::query_workspace_symbols,
+            workspace_symbols::update_symbol_index_for_file,
+            workspace_symbols::get_symbol_index_status,
+            semantic::start_semantic_index,
+            semantic::stop_semantic_index,
+            semantic::semantic_search,
+            semantic::update_semantic_index_for_file,
+            semantic::get_semantic_index_status,
+            replace::preview_replace,
+            replace::apply_replace,
+            replace::undo_replace,
+            replace::discard_replace_preview,
+            languages::get_language_registry,
+            languages::get_language_for_path,
+            languages::detect_language,
+            grammars::list_installed_grammars,
             start_language_server,
             send_lsp_request,
             send_lsp_notification,
@@ -2388,6 +2439,19 @@ fn main() {
             shutdown_all_language_servers,
             check_command_exists,
             parse_document_symbols,
+            documents::open_document,
+            documents::edit_document,
+            documents::close_document,
+            documents::get_document_symbols,
+            highlight::highlight_document,
+            highlight::highlight_source,
+            injections::get_language_at_position,
+            folding::get_folding_ranges,
+            selection::get_selection_ranges,
+            breadcrumbs::get_breadcrumbs,
+            syntax_diagnostics::get_syntax_diagnostics,
+            navigation::find_definition,
+            navigation::find_references,
             get_app_support_dir,
             run_command,
             git_status,
@@ -0,0 +1,317 @@
+//! Best-effort go-to-definition and find-references for when no language
+//! server is running.
+//!
+//! Inside a file, a grammar's `locals.scm` (see [`crate::queries`]) resolves a
+//! name through its scopes: `@local.scope` nodes, `@local.definition.*` names
+//! visible in the innermost scope around them, and `@local.reference` names
+//! looked up from the innermost scope out. A scope with
+//! `(#set! local.scope-inherits false)` does not see the scopes around it.
+//! Names the scopes do not resolve fall back to the file's outline, then to the
+//! workspace symbol index.
+//!
+//! Every location says how it was found, so the editor can tell a scoped match
+//! from a guess by name.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Node, Point, Query, QueryCursor, Tree};
+
+use crate::documents::{self, Documents};
+use crate::error::CmdResult;
+use crate::queries::{Queries, QueryCache};
+use crate::workspace_symbols::{self, SymbolIndexes};
+use crate::{node_to_range, DocumentSymbol, Range};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    /// Resolved through the file's scopes.
+    High,
+    /// Matched by name within the file.
+    Medium,
+    /// Matched by name in the workspace symbol index.
+    Low,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub path: String,
+    /// The name itself, not the whole declaration.
+    pub range: Range,
+    pub is_definition: bool,
+    pub confidence: Confidence,
+}
+
+struct Scope<'tree> {
+    node: Node<'tree>,
+    inherits: bool,
+}
+
+struct Definition<'tree> {
+    node: Node<'tree>,
+    name: String,
+    /// Index into `Locals::scopes`; `None` for names outside every scope.
+    scope: Option<usize>,
+}
+
+/// What a `locals.scm` query found in one tree.
+struct Locals<'tree> {
+    scopes: Vec<Scope<'tree>>,
+    definitions: Vec<Definition<'tree>>,
+    references: Vec<Node<'tree>>,
+}
+
+impl<'tree> Locals<'tree> {
+    fn new(query: &Query, root: Node<'tree>, source: &str) -> Self {
+        let mut scopes = Vec::new();
+        let mut definition_nodes = Vec::new();
+        let mut references = Vec::new();
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(query, root, source.as_bytes());
+        while let Some(m) = matches.next() {
+            for capture in m.captures {
+                let name = query.capture_names()[capture.index as usize];
+                if name == "local.scope" {
+                    let inherits = !query.property_settings(m.pattern_index).iter()
+                        .any(|property| &*property.key == "local.scope-inherits" && property.value.as_deref() == Some("false"));
+                    scopes.push(Scope { node: capture.node, inherits });
+                } else if name == "local.definition" || name.starts_with("local.definition.") {
+                    definition_nodes.push(capture.node);
+                } else if name == "local.reference" {
+                    references.push(capture.node);
+                }
+            }
+        }
+        scopes.sort_by_key(|scope| (scope.node.start_byte(), std::cmp::Reverse(scope.node.end_byte())));
+        scopes.dedup_by_key(|scope| scope.node.id());
+
+        let mut seen = HashSet::new();
+        definition_nodes.retain(|node| seen.insert(node.id()));
+        // A name that is defined is not also a reference
+        references.retain(|node| !seen.contains(&node.id()));
+
+        let mut locals = Locals { scopes, definitions: Vec::new(), references };
+        locals.definitions = definition_nodes.into_iter()
+            .map(|node| Definition {
+                name: text(node, source).to_string(),
+                scope: locals.definition_scope(node),
+                node,
+            })
+            .collect();
+        locals
+    }
+
+    /// Scopes around `node`, innermost first.
+    fn scopes_around(&self, node: Node) -> impl Iterator<Item = usize> + '_ {
+        let range = node.byte_range();
+        (0..self.scopes.len()).rev()
+            .filter(move |&index| {
+                let scope = self.scopes[index].node.byte_range();
+                scope.start <= range.start && range.end <= scope.end
+            })
+    }
+
+    /// The scope a definition belongs to. The name of a declaration that is a
+    /// scope itself, like a function's, belongs to the scope around it.
+    fn definition_scope(&self, node: Node) -> Option<usize> {
+        let mut scopes = self.scopes_around(node);
+        let innermost = scopes.next()?;
+        if Some(self.scopes[innermost].node) == node.parent() {
+            scopes.next()
+        } else {
+            Some(innermost)
+        }
+    }
+
+    /// The definition a name refers to: in the innermost scope that defines it,
+    /// the last definition before the name, else the first one.
+    fn resolve(&self, node: Node, source: &str) -> Option<usize> {
+        if let Some(index) = self.definitions.iter().position(|definition| definition.node == node) {
+            return Some(index);
+        }
+        let name = text(node, source);
+        let in_scope = |scope: Option<usize>| {
+            let candidates: Vec<usize> = (0..self.definitions.len())
+                .filter(|&index| self.definitions[index].scope == scope && self.definitions[index].name == name)
+                .collect();
+            candidates.iter().rev()
+                .find(|&&index| self.definitions[index].node.start_byte() <= node.start_byte())
+                .or(candidates.first())
+                .copied()
+        };
+        for scope in self.scopes_around(node) {
+            if let Some(index) = in_scope(Some(scope)) {
+                return Some(index);
+            }
+            if !self.scopes[scope].inherits {
+                return None;
+            }
+        }
+        in_scope(None)
+    }
+}
+
+fn text<'a>(node: Node, source: &'a str) -> &'a str {
+    node.utf8_text(source.as_bytes()).unwrap_or_default()
+}
+
+/// The name under the cursor: an identifier-like leaf at `point`, or ending at it.
+fn name_at<'tree>(tree: &'tree Tree, point: Point) -> Option<Node<'tree>> {
+    let before = Point::new(point.row, point.column.saturating_sub(1));
+    [point, before].into_iter()
+        .filter_map(|point| tree.root_node().named_descendant_for_point_range(point, point))
+        .find(|node| is_name(*node))
+}
+
+fn is_name(node: Node) -> bool {
+    let kind = node.kind();
+    node.is_named() && node.child_count() == 0 && (kind.contains("identifier") || matches!(kind, "name" | "constant"))
+}
+
+/// Name ranges of the outline symbols called `name`.
+fn outline_definitions(symbols: &[DocumentSymbol], name: &str, out: &mut Vec<Range>) {
+    for symbol in symbols {
+        if symbol.name == name {
+            out.push(symbol.selection_range.clone());
+        }
+        outline_definitions(&symbol.children, name, out);
+    }
+}
+
+fn same_start(range: &Range, node: Node) -> bool {
+    let start = node_to_range(node);
+    range.start_line_number == start.start_line_number && range.start_column == start.start_column
+}
+
+/// Definitions of the name at `point` in a parsed file.
+fn file_definitions(tree: &Tree, source: &str, locals: Option<&Query>, outline: &[DocumentSymbol], point: Point) -> (Option<String>, Vec<(Range, Confidence)>) {
+    let Some(node) = name_at(tree, point) else { return (None, Vec::new()) };
+    let name = text(node, source).to_string();
+    if let Some(query) = locals {
+        let locals = Locals::new(query, tree.root_node(), source);
+        if let Some(index) = locals.resolve(node, source) {
+            return (Some(name), vec![(node_to_range(locals.definitions[index].node), Confidence::High)]);
+        }
+    }
+    let mut ranges = Vec::new();
+    outline_definitions(outline, &name, &mut ranges);
+    (Some(name), ranges.into_iter().map(|range| (range, Confidence::Medium)).collect())
+}
+
+/// Occurrences of the name at `point` in a parsed file: those that resolve to the
+/// same local definition, else every use of the name the scopes leave unresolved.
+fn file_references(tree: &Tree, source: &str, locals: Option<&Query>, outline: &[DocumentSymbol], point: Point) -> Vec<(Range, bool, Confidence)> {
+    let Some(node) = name_at(tree, point) else { return Vec::new() };
+    let name = text(node, source);
+    let locals = locals.map(|query| Locals::new(query, tree.root_node(), source));
+
+    if let Some(locals) = &locals {
+        if let Some(definition) = locals.resolve(node, source) {
+            let mut occurrences = vec![(node_to_range(locals.definitions[definition].node), true, Confidence::High)];
+            occurrences.extend(locals.references.iter()
+                .filter(|reference| text(**reference, source) == name && locals.resolve(**reference, source) == Some(definition))
+                .map(|reference| (node_to_range(*reference), false, Confidence::High)));
+            // Other definitions of the name in the same scope, as in `x = 1; x = 2`
+            let scope = locals.definitions[definition].scope;
+            occurrences.extend(locals.definitions.iter().enumerate()
+                .filter(|(index, other)| *index != definition && other.scope == scope && other.name == name)
+                .map(|(_, other)| (node_to_range(other.node), true, Confidence::High)));
+            occurrences.sort_by_key(|(range, _, _)| (range.start_line_number, range.start_column));
+            return occurrences;
+        }
+    }
+
+    let mut definitions = Vec::new();
+    outline_definitions(outline, name, &mut definitions);
+    let mut occurrences = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(current) = stack.pop() {
+        if is_name(current) && text(current, source) == name {
+            let local = locals.as_ref().is_some_and(|locals| locals.resolve(current, source).is_some());
+            if !local {
+                let is_definition = definitions.iter().any(|range| same_start(range, current));
+                occurrences.push((node_to_range(current), is_definition, Confidence::Medium));
+            }
+        }
+        let mut cursor = current.walk();
+        stack.extend(current.children(&mut cursor));
+    }
+    occurrences.sort_by_key(|(range, _, _)| (range.start_line_number, range.start_column));
+    occurrences
+}
+
+fn point(line_number: u32, column: u32) -> Point {
+    Point::new(line_number.saturating_sub(1) as usize, column.saturating_sub(1) as usize)
+}
+
+/// Where the name at a 1-based position of an open document is defined. Falls
+/// back to the workspace symbol index of `workspace_path`, when it has one
+/// running, for names the file does not define.
+#[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)]
+pub fn find_definition(
+    file_path: String,
+    line_number: u32,
+    column: u32,
+    workspace_path: Option<String>,
+    documents: tauri::State<'_, Documents>,
+    indexes: tauri::State<'_, SymbolIndexes>,
+    query_cache: tauri::State<'_, QueryCache>,
+    app_handle: tauri::AppHandle,
+) -> CmdResult<Vec<Location>> {
+    let queries = Queries::new(&query_cache, &app_handle);
+    let (name, found) = documents::with_document(&documents, &file_path, |document| {
+        let locals = queries.get(document.language_id(), "locals.scm");
+        let outline = document.outline(&queries).to_vec();
+        file_definitions(document.tree(), document.source(), locals.as_deref(), &outline, point(line_number, column))
+    })?;
+    let mut locations: Vec<Location> = found.into_iter()
+        .map(|(range, confidence)| Location { path: file_path.clone(), range, is_definition: true, confidence })
+        .collect();
+
+    if let (Some(name), true, Some(workspace_path)) = (name, locations.is_empty(), workspace_path) {
+        if let Some(index) = workspace_symbols::find_index(&indexes, &workspace_path)? {
+            locations.extend(index.definitions(&name).into_iter().map(|symbol| Location {
+                path: symbol.path,
+                range: symbol.selection_range,
+                is_definition: true,
+                confidence: Confidence::Low,
+            }));
+        }
+    }
+    Ok(locations)
+}
+
+/// Occurrences of the name at a 1-based position of an open document, within
+/// that document. With `include_declaration` (the default) these are also what
+/// to highlight as occurrences of the symbol under the cursor.
+#[tauri::command(rename_all = "snake_case")]
+pub fn find_references(
+    file_path: String,
+    line_number: u32,
+    column: u32,
+    include_declaration: Option<bool>,
+    documents: tauri::State<'_, Documents>,
+    query_cache: tauri::State<'_, QueryCache>,
+    app_handle: tauri::AppHandle,
+) -> CmdResult<Vec<Location>> {
+    let queries = Queries::new(&query_cache, &app_handle);
+    let include_declaration = include_declaration.unwrap_or(true);
+    let found = documents::with_document(&documents, &file_path, |document| {
+        let locals = queries.get(document.language_id(), "locals.scm");
+        let outline = document.outline(&queries).to_vec();
+        file_references(document.tree(), document.source(), locals.as_deref(), &outline, point(line_number, column))
+    })?;
+    Ok(found.into_iter()
+        .filter(|(_, is_definition, _)| include_declaration || !is_definition)
+        .map(|(range, is_definition, confidence)| Location {
+            path: file_path.clone(),
+            range,
+            is_definition,
+            confidence,
+        })
+        .collect())
+}
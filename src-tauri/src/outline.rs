@@ -0,0 +1,449 @@
+//! Outline extraction for the grammars bundled beside JavaScript/TypeScript,
+//! Python, Rust and Go. Each language decides which nodes are symbols; whatever is
+//! declared inside a symbol's body becomes its children.
+
+use tree_sitter::Node;
+
+use crate::{node_to_range, DocumentSymbol};
+
+/// A node that is listed in the outline.
+struct Entry<'tree> {
+    name: String,
+    kind: &'static str,
+    /// What the editor selects when the symbol is picked.
+    name_node: Node<'tree>,
+    /// Where nested symbols are looked for; `None` lists nothing below the symbol.
+    body: Option<Node<'tree>>,
+}
+
+type Extractor = for<'tree> fn(Node<'tree>, &str) -> Option<Entry<'tree>>;
+
+fn extractor(language_id: &str) -> Option<Extractor> {
+    Some(match language_id {
+        "c" => c_node,
+        "cpp" => cpp_node,
+        "java" => java_node,
+        "csharp" => csharp_node,
+        "ruby" => ruby_node,
+        "php" => php_node,
+        "shellscript" | "bash" | "sh" => bash_node,
+        "lua" => lua_node,
+        "html" => html_node,
+        "css" => css_node,
+        "json" | "jsonc" => json_node,
+        "yaml" => yaml_node,
+        "toml" => toml_node,
+        "markdown" => markdown_node,
+        _ => return None,
+    })
+}
+
+/// Outline of a parsed file, or `None` when the language is not handled here.
+pub fn extract_symbols(root: Node, source: &str, language_id: &str) -> Option<Vec<DocumentSymbol>> {
+    let extract = extractor(language_id)?;
+    let mut symbols = Vec::new();
+    collect(root, source, extract, &mut symbols);
+    Some(symbols)
+}
+
+fn collect(node: Node, source: &str, extract: Extractor, out: &mut Vec<DocumentSymbol>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match extract(child, source) {
+            Some(entry) => {
+                let mut children = Vec::new();
+                if let Some(body) = entry.body {
+                    collect(body, source, extract, &mut children);
+                }
+                out.push(DocumentSymbol {
+                    name: entry.name,
+                    kind: entry.kind.to_string(),
+                    range: node_to_range(child),
+                    selection_range: node_to_range(entry.name_node),
+                    children,
+                    ..Default::default()
+                });
+            }
+            None => collect(child, source, extract, out),
+        }
+    }
+}
+
+/// Node text on one line: `foo,\n  bar` becomes `foo, bar`.
+fn text(node: Node, source: &str) -> String {
+    let raw = node.utf8_text(source.as_bytes()).unwrap_or_default();
+    raw.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn entry<'tree>(name: String, kind: &'static str, name_node: Node<'tree>, body: Option<Node<'tree>>) -> Option<Entry<'tree>> {
+    if name.is_empty() {
+        return None;
+    }
+    Some(Entry { name, kind, name_node, body })
+}
+
+/// The symbol named by the `name` field.
+fn named<'tree>(node: Node<'tree>, source: &str, kind: &'static str, body: Option<Node<'tree>>) -> Option<Entry<'tree>> {
+    let name = node.child_by_field_name("name")?;
+    entry(text(name, source), kind, name, body)
+}
+
+fn parent_kind(node: Node) -> &'static str {
+    node.parent().map_or("", |parent| parent.kind())
+}
+
+// C and C++
+
+/// The identifier a (possibly nested) C declarator declares: `*name`, `name[4]`,
+/// `(*name)(int)`, `Foo::name(int) const`.
+fn declarator_name(mut node: Node) -> Option<Node> {
+    loop {
+        match node.kind() {
+            "identifier" | "field_identifier" | "type_identifier" | "qualified_identifier" | "destructor_name"
+            | "operator_name" | "primitive_type" | "template_function" => return Some(node),
+            _ => {
+                node = match node.child_by_field_name("declarator") {
+                    Some(inner) => inner,
+                    // `reference_declarator` has no field for what follows the `&`
+                    None => node.named_child(node.named_child_count().checked_sub(1)?)?,
+                };
+            }
+        }
+    }
+}
+
+/// Whether a declarator declares a function rather than, say, a function pointer.
+fn declares_function(declarator: Node) -> bool {
+    declarator_name(declarator)
+        .and_then(|name| name.parent())
+        .is_some_and(|parent| parent.kind() == "function_declarator")
+}
+
+fn c_family_node<'tree>(node: Node<'tree>, source: &str, cpp: bool) -> Option<Entry<'tree>> {
+    let in_class = parent_kind(node) == "field_declaration_list";
+    match node.kind() {
+        "function_definition" => {
+            let name = declarator_name(node.child_by_field_name("declarator")?)?;
+            let kind = if in_class || name.kind() == "qualified_identifier" { "method" } else { "function" };
+            entry(text(name, source), kind, name, None)
+        }
+        "struct_specifier" | "union_specifier" | "class_specifier" | "enum_specifier" => {
+            let body = node.child_by_field_name("body")?;
+            let kind = match node.kind() {
+                "class_specifier" => "class",
+                "enum_specifier" => "enum",
+                _ => "struct",
+            };
+            match node.child_by_field_name("name") {
+                Some(name) => entry(text(name, source), kind, name, Some(body)),
+                None => entry(format!("<anonymous {}>", node.kind().trim_end_matches("_specifier")), kind, node, Some(body)),
+            }
+        }
+        "enumerator" => named(node, source, "enummember", None),
+        "field_declaration" => {
+            let declarator = node.child_by_field_name("declarator")?;
+            let name = declarator_name(declarator)?;
+            let kind = if declares_function(declarator) { "method" } else { "field" };
+            entry(text(name, source), kind, name, None)
+        }
+        "type_definition" => {
+            let name = declarator_name(node.child_by_field_name("declarator")?)?;
+            let ty = node.child_by_field_name("type")?;
+            let kind = match ty.kind() {
+                "struct_specifier" | "union_specifier" => "struct",
+                "enum_specifier" => "enum",
+                _ => "class",
+            };
+            entry(text(name, source), kind, name, ty.child_by_field_name("body"))
+        }
+        "preproc_def" => named(node, source, "constant", None),
+        "preproc_function_def" => named(node, source, "function", None),
+        "declaration" if matches!(parent_kind(node), "translation_unit" | "declaration_list") => {
+            // Globals; prototypes are left to their definitions
+            let declarator = node.child_by_field_name("declarator")?;
+            if declares_function(declarator) {
+                return None;
+            }
+            let name = declarator_name(declarator)?;
+            entry(text(name, source), "variable", name, None)
+        }
+        "namespace_definition" if cpp => match node.child_by_field_name("name") {
+            Some(name) => entry(text(name, source), "namespace", name, node.child_by_field_name("body")),
+            None => entry("<anonymous namespace>".to_string(), "namespace", node, node.child_by_field_name("body")),
+        },
+        "alias_declaration" if cpp => named(node, source, "class", None),
+        _ => None,
+    }
+}
+
+fn c_node<'tree>(node: Node<'tree>, source: &str) -> Option<Entry<'tree>> {
+    c_family_node(node, source, false)
+}
+
+fn cpp_node<'tree>(node: Node<'tree>, source: &str) -> Option<Entry<'tree>> {
+    c_family_node(node, source, true)
+}
+
+// Java and C#
+
+fn java_node<'tree>(node: Node<'tree>, source: &str) -> Option<Entry<'tree>> {
+    let body = node.child_by_field_name("body");
+    match node.kind() {
+        "class_declaration" => named(node, source, "class", body),
+        "interface_declaration" | "annotation_type_declaration" => named(node, source, "interface", body),
+        "enum_declaration" => named(node, source, "enum", body),
+        "record_declaration" => named(node, source, "struct", body),
+        "method_declaration" => named(node, source, "method", None),
+        "constructor_declaration" | "compact_constructor_declaration" => named(node, source, "constructor", None),
+        "enum_constant" => named(node, source, "enummember", body),
+        "variable_declarator" => match parent_kind(node) {
+            "field_declaration" => named(node, source, "field", None),
+            "constant_declaration" => named(node, source, "constant", None),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn csharp_node<'tree>(node: Node<'tree>, source: &str) -> Option<Entry<'tree>> {
+    let body = node.child_by_field_name("body");
+    match node.kind() {
+        "namespace_declaration" | "file_scoped_namespace_declaration" => named(node, source, "namespace", body),
+        "class_declaration" | "record_declaration" => named(node, source, "class", body),
+        "struct_declaration" => named(node, source, "struct", body),
+        "interface_declaration" => named(node, source, "interface", body),
+        "enum_declaration" => named(node, source, "enum", body),
+        "enum_member_declaration" => named(node, source, "enummember", None),
+        "method_declaration" | "destructor_declaration" => named(node, source, "method", None),
+        "constructor_declaration" => named(node, source, "constructor", None),
+        "property_declaration" | "indexer_declaration" => named(node, source, "property", None),
+        "event_declaration" => named(node, source, "event", None),
+        "delegate_declaration" => named(node, source, "function", None),
+        "variable_declarator" => {
+            let declaration = node.parent()?;
+            match parent_kind(declaration) {
+                "field_declaration" => named(node, source, "field", None),
+                "event_field_declaration" => named(node, source, "event", None),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// Scripting languages
+
+fn ruby_node<'tree>(node: Node<'tree>, source: &str) -> Option<Entry<'tree>> {
+    let body = node.child_by_field_name("body");
+    match node.kind() {
+        "class" => named(node, source, "class", body),
+        "module" => named(node, source, "module", body),
+        "method" => {
+            let kind = if text(node.child_by_field_name("name")?, source) == "initialize" { "constructor" } else { "method" };
+            named(node, source, kind, None)
+        }
+        "singleton_method" => {
+            let name = node.child_by_field_name("name")?;
+            let object = text(node.child_by_field_name("object")?, source);
+            entry(format!("{}.{}", object, text(name, source)), "method", name, None)
+        }
+        "assignment" => {
+            let left = node.child_by_field_name("left")?;
+            let at_top = matches!(parent_kind(node), "program" | "body_statement");
+            (at_top && left.kind() == "constant").then(|| entry(text(left, source), "constant", left, None))?
+        }
+        _ => None,
+    }
+}
+
+fn php_node<'tree>(node: Node<'tree>, source: &str) -> Option<Entry<'tree>> {
+    let body = node.child_by_field_name("body");
+    match node.kind() {
+        "namespace_definition" => named(node, source, "namespace", body),
+        "class_declaration" | "trait_declaration" => named(node, source, "class", body),
+        "interface_declaration" => named(node, source, "interface", body),
+        "enum_declaration" => named(node, source, "enum", body),
+        "enum_case" => named(node, source, "enummember", None),
+        "function_definition" => named(node, source, "function", None),
+        "method_declaration" => {
+            let kind = if text(node.child_by_field_name("name")?, source) == "__construct" { "constructor" } else { "method" };
+            named(node, source, kind, None)
+        }
+        "property_element" => named(node, source, "property", None),
+        "const_element" => {
+            let mut cursor = node.walk();
+            let name = node.named_children(&mut cursor).find(|child| child.kind() == "name")?;
+            entry(text(name, source), "constant", name, None)
+        }
+        _ => None,
+    }
+}
+
+fn bash_node<'tree>(node: Node<'tree>, source: &str) -> Option<Entry<'tree>> {
+    match node.kind() {
+        "function_definition" => named(node, source, "function", node.child_by_field_name("body")),
+        "variable_assignment" => {
+            // Script-level variables only, plain or through `export`/`readonly`
+            let mut parent = node.parent()?;
+            if parent.kind() == "declaration_command" {
+                parent = parent.parent()?;
+            }
+            (parent.kind() == "program").then(|| named(node, source, "variable", None))?
+        }
+        _ => None,
+    }
+}
+
+fn lua_node<'tree>(node: Node<'tree>, source: &str) -> Option<Entry<'tree>> {
+    match node.kind() {
+        "function_declaration" => {
+            let name = node.child_by_field_name("name")?;
+            let kind = if name.kind() == "method_index_expression" { "method" } else { "function" };
+            entry(text(name, source), kind, name, node.child_by_field_name("body"))
+        }
+        // `M.handler = function () end`, `local f = function () end`
+        "assignment_statement" => {
+            let variables = node.named_child(0)?;
+            let values = node.named_child(1)?;
+            let function = values.named_child(0).filter(|value| value.kind() == "function_definition")?;
+            let name = variables.named_child(0)?;
+            entry(text(name, source), "function", name, function.child_by_field_name("body"))
+        }
+        "field" => {
+            let value = node.child_by_field_name("value").filter(|value| value.kind() == "function_definition")?;
+            named(node, source, "method", value.child_by_field_name("body"))
+        }
+        _ => None,
+    }
+}
+
+// Markup and data
+
+fn html_node<'tree>(node: Node<'tree>, source: &str) -> Option<Entry<'tree>> {
+    if !matches!(node.kind(), "element" | "script_element" | "style_element") {
+        return None;
+    }
+    let tag = node.named_child(0).filter(|tag| matches!(tag.kind(), "start_tag" | "self_closing_tag"))?;
+    let mut cursor = tag.walk();
+    let mut name_node = None;
+    let mut id = String::new();
+    let mut classes = String::new();
+    for child in tag.named_children(&mut cursor) {
+        match child.kind() {
+            "tag_name" => name_node = Some(child),
+            "attribute" => {
+                let Some(attribute) = child.named_child(0) else { continue };
+                let value = child.named_child(1)
+                    .map(|value| text(value, source).trim_matches(|c| c == '"' || c == '\'').to_string())
+                    .unwrap_or_default();
+                match text(attribute, source).as_str() {
+                    "id" if !value.is_empty() => id = format!("#{}", value),
+                    "class" => classes = value.split_whitespace().map(|class| format!(".{}", class)).collect(),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    let name_node = name_node?;
+    entry(format!("{}{}{}", text(name_node, source), id, classes), "field", name_node, Some(node))
+}
+
+fn css_node<'tree>(node: Node<'tree>, source: &str) -> Option<Entry<'tree>> {
+    match node.kind() {
+        "rule_set" => {
+            let selectors = node.named_child(0).filter(|child| child.kind() == "selectors")?;
+            entry(text(selectors, source), "class", selectors, node.named_child(1))
+        }
+        "media_statement" | "supports_statement" | "at_rule" | "keyframes_statement" => {
+            let mut cursor = node.walk();
+            let body = node.named_children(&mut cursor)
+                .find(|child| matches!(child.kind(), "block" | "keyframe_block_list"));
+            // The rule up to its block: `@media screen and (min-width: 600px)`
+            let header_end = body.map_or(node.end_byte(), |body| body.start_byte());
+            let header = source.get(node.start_byte()..header_end).unwrap_or_default();
+            let name = header.split_whitespace().collect::<Vec<_>>().join(" ");
+            entry(name.trim_end_matches(';').to_string(), "module", node, body)
+        }
+        _ => None,
+    }
+}
+
+/// Outline kind for a JSON/YAML/TOML value, and whether its members are listed.
+fn value_kind(value: Node) -> (&'static str, bool) {
+    match value.kind() {
+        "object" | "block_mapping" | "flow_mapping" | "inline_table" => ("object", true),
+        "array" | "block_sequence" | "flow_sequence" => ("array", true),
+        "string" | "double_quote_scalar" | "single_quote_scalar" | "block_scalar" => ("string", false),
+        "number" | "integer" | "float" => ("number", false),
+        "true" | "false" | "boolean" => ("boolean", false),
+        "null" => ("null", false),
+        _ => ("property", false),
+    }
+}
+
+fn unquote(name: String) -> String {
+    let quoted = name.len() >= 2
+        && ((name.starts_with('"') && name.ends_with('"')) || (name.starts_with('\'') && name.ends_with('\'')));
+    if quoted { name[1..name.len() - 1].to_string() } else { name }
+}
+
+fn json_node<'tree>(node: Node<'tree>, source: &str) -> Option<Entry<'tree>> {
+    if node.kind() != "pair" {
+        return None;
+    }
+    let key = node.child_by_field_name("key")?;
+    let value = node.child_by_field_name("value")?;
+    let (kind, nested) = value_kind(value);
+    entry(unquote(text(key, source)), kind, key, nested.then_some(value))
+}
+
+fn yaml_node<'tree>(node: Node<'tree>, source: &str) -> Option<Entry<'tree>> {
+    if !matches!(node.kind(), "block_mapping_pair" | "flow_pair") {
+        return None;
+    }
+    let key = node.child_by_field_name("key")?;
+    let value = node.child_by_field_name("value")
+        .map(|value| {
+            // block_node / flow_node wrap the actual value, after any anchor or tag
+            let mut cursor = value.walk();
+            let inner = value.named_children(&mut cursor).find(|child| !matches!(child.kind(), "anchor" | "tag"));
+            inner.unwrap_or(value)
+        });
+    let (kind, nested) = value.map_or(("property", false), value_kind);
+    let kind = if kind == "property" || !nested { "property" } else { kind };
+    entry(unquote(text(key, source)), kind, key, value.filter(|_| nested))
+}
+
+fn toml_node<'tree>(node: Node<'tree>, source: &str) -> Option<Entry<'tree>> {
+    let key = node.named_child(0).filter(|key| matches!(key.kind(), "bare_key" | "dotted_key" | "quoted_key"))?;
+    let name = unquote(text(key, source));
+    match node.kind() {
+        "table" => entry(name, "object", key, Some(node)),
+        "table_array_element" => entry(name, "array", key, Some(node)),
+        "pair" => {
+            let value = node.named_child(node.named_child_count().checked_sub(1)?)?;
+            let (kind, nested) = value_kind(value);
+            entry(name, kind, key, (nested && value.kind() == "inline_table").then_some(value))
+        }
+        _ => None,
+    }
+}
+
+fn markdown_node<'tree>(node: Node<'tree>, source: &str) -> Option<Entry<'tree>> {
+    match node.kind() {
+        // ATX headings open a section that runs to the next heading of the same or a
+        // higher level, so nesting follows the heading levels
+        "section" => {
+            let heading = node.named_child(0).filter(|child| matches!(child.kind(), "atx_heading" | "setext_heading"))?;
+            let content = heading.child_by_field_name("heading_content")?;
+            entry(text(content, source), "string", heading, Some(node))
+        }
+        // Setext headings do not; they are listed where they appear
+        "setext_heading" if node.prev_named_sibling().is_some() || parent_kind(node) != "section" => {
+            let content = node.child_by_field_name("heading_content")?;
+            entry(text(content, source), "string", node, None)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,143 @@
+//! Tree-sitter query files per grammar: `tags.scm` for the outline,
+//! `highlights.scm` and `injections.scm` for highlighting, `locals.scm` for
+//! navigation without a language server, and so on.
+//!
+//! A query comes from the first of these that exists and compiles:
+//! - `<config>/queries/<grammar>/<file>`, the user's override
+//! - the `queries` directory of an installed grammar
+//! - the default shipped with the app, from `queries/` or the grammar crate
+//!
+//! Compiled queries are cached per grammar and file, and recompiled when an
+//! override or installed query file changes.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use tauri::{AppHandle, Manager};
+use tree_sitter::Query;
+
+use crate::grammars::{self, grammar_family};
+
+/// Compiled queries by grammar family and file name.
+pub type QueryCache = Arc<Mutex<HashMap<(String, String), CachedQuery>>>;
+
+pub struct CachedQuery {
+    /// Modification times of the override and of the installed grammar's file
+    /// when they were read; `None` for a file that did not exist.
+    sources_modified: [Option<SystemTime>; 2],
+    query: Option<Arc<Query>>,
+}
+
+/// The default `file` query for a grammar, if one ships with the app.
+fn default_query(family: &str, file: &str) -> Option<String> {
+    let parts: &[&str] = match (file, family) {
+        ("tags.scm", "python") => &[include_str!("../queries/python/tags.scm")],
+        ("tags.scm", "rust") => &[include_str!("../queries/rust/tags.scm")],
+        ("tags.scm", "go") => &[include_str!("../queries/go/tags.scm")],
+        ("tags.scm", "javascript") => &[include_str!("../queries/javascript/tags.scm")],
+        ("tags.scm", "typescript" | "tsx") => &[include_str!("../queries/typescript/tags.scm")],
+
+        // Later patterns win, so dialects come after the base language
+        ("highlights.scm", "javascript") => &[tree_sitter_javascript::HIGHLIGHT_QUERY, tree_sitter_javascript::JSX_HIGHLIGHT_QUERY],
+        ("highlights.scm", "typescript") => &[tree_sitter_javascript::HIGHLIGHT_QUERY, tree_sitter_typescript::HIGHLIGHTS_QUERY],
+        ("highlights.scm", "tsx") => &[
+            tree_sitter_javascript::HIGHLIGHT_QUERY,
+            tree_sitter_javascript::JSX_HIGHLIGHT_QUERY,
+            tree_sitter_typescript::HIGHLIGHTS_QUERY,
+        ],
+        ("highlights.scm", "python") => &[tree_sitter_python::HIGHLIGHTS_QUERY],
+        ("highlights.scm", "rust") => &[tree_sitter_rust::HIGHLIGHTS_QUERY],
+        ("highlights.scm", "go") => &[tree_sitter_go::HIGHLIGHTS_QUERY],
+        ("highlights.scm", "c") => &[tree_sitter_c::HIGHLIGHT_QUERY],
+        ("highlights.scm", "cpp") => &[tree_sitter_c::HIGHLIGHT_QUERY, tree_sitter_cpp::HIGHLIGHT_QUERY],
+        ("highlights.scm", "java") => &[tree_sitter_java::HIGHLIGHTS_QUERY],
+        ("highlights.scm", "csharp") => &[include_str!("../queries/csharp/highlights.scm")],
+        ("highlights.scm", "ruby") => &[tree_sitter_ruby::HIGHLIGHTS_QUERY],
+        ("highlights.scm", "php") => &[tree_sitter_php::HIGHLIGHTS_QUERY],
+        ("highlights.scm", "shellscript") => &[tree_sitter_bash::HIGHLIGHT_QUERY],
+        ("highlights.scm", "html") => &[tree_sitter_html::HIGHLIGHTS_QUERY],
+        ("highlights.scm", "css") => &[tree_sitter_css::HIGHLIGHTS_QUERY],
+        ("highlights.scm", "json") => &[tree_sitter_json::HIGHLIGHTS_QUERY],
+        ("highlights.scm", "yaml") => &[tree_sitter_yaml::HIGHLIGHTS_QUERY],
+        ("highlights.scm", "toml") => &[tree_sitter_toml_ng::HIGHLIGHTS_QUERY],
+        ("highlights.scm", "markdown") => &[tree_sitter_md::HIGHLIGHT_QUERY_BLOCK],
+        ("highlights.scm", "markdown_inline") => &[tree_sitter_md::HIGHLIGHT_QUERY_INLINE],
+
+        ("injections.scm", "python") => &[include_str!("../queries/python/injections.scm")],
+        ("injections.scm", "javascript" | "typescript" | "tsx") => &[tree_sitter_javascript::INJECTIONS_QUERY],
+        ("injections.scm", "php") => &[tree_sitter_php::INJECTIONS_QUERY],
+        ("injections.scm", "html") => &[tree_sitter_html::INJECTIONS_QUERY],
+        ("injections.scm", "markdown") => &[tree_sitter_md::INJECTION_QUERY_BLOCK],
+        ("injections.scm", "markdown_inline") => &[tree_sitter_md::INJECTION_QUERY_INLINE],
+        ("locals.scm", "python") => &[include_str!("../queries/python/locals.scm")],
+        ("locals.scm", "rust") => &[include_str!("../queries/rust/locals.scm")],
+        ("locals.scm", "go") => &[include_str!("../queries/go/locals.scm")],
+        ("locals.scm", "javascript") => &[tree_sitter_javascript::LOCALS_QUERY],
+        ("locals.scm", "typescript" | "tsx") => &[tree_sitter_javascript::LOCALS_QUERY, tree_sitter_typescript::LOCALS_QUERY],
+        ("locals.scm", "ruby") => &[tree_sitter_ruby::LOCALS_QUERY],
+        _ => return None,
+    };
+    Some(parts.join("\n"))
+}
+
+/// Where user overrides live: `<config>/queries/<grammar>/<file>`.
+pub fn queries_dir(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle.path().app_config_dir().ok().map(|dir| dir.join("queries"))
+}
+
+/// The queries as seen from one app: the shared cache plus the override directory.
+#[derive(Clone)]
+pub struct Queries {
+    cache: QueryCache,
+    user_dir: Option<PathBuf>,
+}
+
+impl Queries {
+    pub fn new(cache: &QueryCache, app_handle: &AppHandle) -> Self {
+        Queries { cache: cache.clone(), user_dir: queries_dir(app_handle) }
+    }
+
+    /// The compiled `file` query for a language, or `None` when there is none.
+    pub fn get(&self, language_id: &str, file: &str) -> Option<Arc<Query>> {
+        let family = grammar_family(language_id);
+        let sources = [
+            self.user_dir.as_ref().map(|dir| dir.join(family).join(file)),
+            grammars::installed::query_path(language_id, file),
+        ];
+        let sources_modified = sources.each_ref().map(|path| {
+            path.as_ref().and_then(|path| fs::metadata(path).ok()).and_then(|metadata| metadata.modified().ok())
+        });
+
+        let key = (family.to_string(), file.to_string());
+        let mut cache = self.cache.lock().ok()?;
+        if let Some(cached) = cache.get(&key).filter(|cached| cached.sources_modified == sources_modified) {
+            return cached.query.clone();
+        }
+        let query = load(language_id, file, &sources).map(Arc::new);
+        cache.insert(key, CachedQuery { sources_modified, query: query.clone() });
+        query
+    }
+}
+
+/// The first of `sources` that exists and compiles, else the shipped default.
+fn load(language_id: &str, file: &str, sources: &[Option<PathBuf>]) -> Option<Query> {
+    let language = crate::get_language(language_id).ok()?;
+    for path in sources.iter().flatten().filter(|path| path.is_file()) {
+        match fs::read_to_string(path).map_err(|e| e.to_string()).and_then(|source| Query::new(&language, &source).map_err(|e| e.to_string())) {
+            Ok(query) => return Some(query),
+            // A broken query file should not take the feature down with it
+            Err(e) => eprintln!("[QUERIES DEBUG] Ignoring {}: {}", path.display(), e),
+        }
+    }
+    let source = default_query(grammar_family(language_id), file)?;
+    match Query::new(&language, &source) {
+        Ok(query) => Some(query),
+        Err(e) => {
+            eprintln!("[QUERIES DEBUG] Default {} for {} does not compile: {}", file, language_id, e);
+            None
+        }
+    }
+}
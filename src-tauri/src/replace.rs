@@ -0,0 +1,635 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use regex::Regex;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::error::{AppError, CmdResult, ErrorCode};
+use crate::languages;
+use crate::search::{self, MatchOptions, Matcher, SearchFilters, SearchIndexes, SearchScope};
+
+/// How many applied replacements can be undone.
+const MAX_UNDO_ENTRIES: usize = 20;
+
+/// How many previews are kept for applying. Each search replaces the panel's
+/// preview, so older ones are only held on to in case a request was in flight.
+const MAX_PREVIEWS: usize = 4;
+
+/// One proposed edit. `id` is unique within its preview and is what the
+/// frontend sends back in `excluded` to skip individual matches.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaceEdit {
+    pub id: u64,
+    pub line_number: usize,
+    pub column: usize,
+    pub original: String,
+    pub replacement: String,
+    pub line_text: String,
+    pub preview_text: String,
+    #[serde(skip)]
+    start: usize,
+    #[serde(skip)]
+    end: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileReplacePreview {
+    pub path: String,
+    pub relative_path: String,
+    pub edits: Vec<ReplaceEdit>,
+    #[serde(skip)]
+    fingerprint: u64,
+}
+
+/// A matching file the preview leaves out, and why.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedFile {
+    pub path: String,
+    pub relative_path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplacePreview {
+    pub preview_id: String,
+    pub files: Vec<FileReplacePreview>,
+    pub total_edits: usize,
+    pub truncated: bool,
+    pub skipped_files: Vec<SkippedFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaceApplied {
+    pub undo_id: String,
+    pub files_changed: usize,
+    pub edits_applied: usize,
+}
+
+struct UndoEntry {
+    id: String,
+    /// (path, content before the replace, fingerprint of the content we wrote)
+    files: Vec<(PathBuf, String, u64)>,
+}
+
+#[derive(Default)]
+pub struct ReplaceState {
+    /// Oldest first.
+    previews: Vec<ReplacePreview>,
+    undo_stack: Vec<UndoEntry>,
+    next_id: AtomicU64,
+}
+
+pub type ReplaceSessions = Arc<Mutex<ReplaceState>>;
+
+impl ReplaceState {
+    fn new_id(&self, prefix: &str) -> String {
+        let n = self.next_id.fetch_add(1, Ordering::Relaxed);
+        format!("{}_{}_{}", prefix, Utc::now().timestamp_millis(), n)
+    }
+
+    /// Keep `preview` for applying, under a new id.
+    fn add_preview(&mut self, mut preview: ReplacePreview) -> ReplacePreview {
+        preview.preview_id = self.new_id("replace");
+        self.previews.push(preview.clone());
+        if self.previews.len() > MAX_PREVIEWS {
+            self.previews.remove(0);
+        }
+        preview
+    }
+
+    fn preview(&self, preview_id: &str) -> Option<&ReplacePreview> {
+        self.previews.iter().find(|preview| preview.preview_id == preview_id)
+    }
+
+    fn remove_preview(&mut self, preview_id: &str) -> bool {
+        let before = self.previews.len();
+        self.previews.retain(|preview| preview.preview_id != preview_id);
+        self.previews.len() < before
+    }
+}
+
+fn fingerprint(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Apply the casing of `matched` to `replacement`: `FOO` -> `BAR`, `foo` -> `bar`, `Foo` -> `Bar`.
+fn preserve_case(matched: &str, replacement: &str) -> String {
+    let has_letters = matched.chars().any(|c| c.is_alphabetic());
+    if !has_letters {
+        return replacement.to_string();
+    }
+    if matched.chars().all(|c| !c.is_lowercase()) {
+        return replacement.to_uppercase();
+    }
+    if matched.chars().all(|c| !c.is_uppercase()) {
+        return replacement.to_lowercase();
+    }
+
+    let mut chars = matched.chars();
+    let first_upper = chars.next().is_some_and(|c| c.is_uppercase());
+    if first_upper && chars.all(|c| !c.is_uppercase()) {
+        let mut replacement_chars = replacement.chars();
+        return match replacement_chars.next() {
+            Some(first) => first.to_uppercase().chain(replacement_chars).collect(),
+            None => String::new(),
+        };
+    }
+
+    replacement.to_string()
+}
+
+fn line_bounds(content: &str, start: usize, end: usize) -> (usize, usize) {
+    let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = content[end..].find('\n').map_or(content.len(), |i| end + i);
+    (line_start, line_end)
+}
+
+fn trim_cr(line: &str) -> &str {
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+fn collect_edits(
+    content: &str,
+    regex: &Regex,
+    replacement: &str,
+    use_regex: bool,
+    keep_case: bool,
+    next_edit_id: &mut u64,
+) -> Vec<ReplaceEdit> {
+    let mut edits = Vec::new();
+    let mut line_number = 1;
+    let mut counted_to = 0;
+
+    for captures in regex.captures_iter(content) {
+        let whole = captures.get(0).expect("group 0 always participates");
+        let (start, end) = (whole.start(), whole.end());
+
+        let mut new_text = String::new();
+        if use_regex {
+            captures.expand(replacement, &mut new_text);
+        } else {
+            new_text.push_str(replacement);
+        }
+        if keep_case {
+            new_text = preserve_case(whole.as_str(), &new_text);
+        }
+
+        line_number += content[counted_to..start].matches('\n').count();
+        counted_to = start;
+        let (line_start, line_end) = line_bounds(content, start, end);
+        let preview_text = format!("{}{}{}", &content[line_start..start], new_text, &content[end..line_end]);
+
+        edits.push(ReplaceEdit {
+            id: *next_edit_id,
+            line_number,
+            // UTF-16 columns, to line up with search results and the editor
+            column: content[line_start..start].encode_utf16().count() + 1,
+            original: whole.as_str().to_string(),
+            replacement: new_text,
+            line_text: trim_cr(&content[line_start..line_end]).to_string(),
+            preview_text: trim_cr(&preview_text).to_string(),
+            start,
+            end,
+        });
+        *next_edit_id += 1;
+    }
+
+    edits
+}
+
+/// What to replace, as given to `preview_replace`.
+pub struct ReplaceQuery {
+    pub query: String,
+    pub replacement: String,
+    pub use_regex: bool,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    pub preserve_case: bool,
+    pub max_results: usize,
+}
+
+/// Compute every replacement in the workspace without touching any file.
+#[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)]
+pub fn preview_replace(
+    workspace_path: String,
+    query: String,
+    replacement: String,
+    use_regex: bool,
+    case_sensitive: bool,
+    whole_word: bool,
+    preserve_case: bool,
+    max_results: usize,
+    filters: Option<SearchFilters>,
+    state: tauri::State<'_, ReplaceSessions>,
+    indexes: tauri::State<'_, SearchIndexes>,
+    app_handle: AppHandle,
+) -> CmdResult<ReplacePreview> {
+    if query.is_empty() {
+        return Err(AppError::invalid_input("Search query cannot be empty"));
+    }
+
+    let workspace = search::validate_workspace(&workspace_path)?;
+    let scope = SearchScope::new(workspace, &filters.unwrap_or_default(), languages::load_file_types(&app_handle))?;
+    let (scope, _) = search::narrow_with_index(scope, &indexes, &query, use_regex, case_sensitive, whole_word);
+    let preview = build_preview(&scope, &ReplaceQuery {
+        query,
+        replacement,
+        use_regex,
+        case_sensitive,
+        whole_word,
+        preserve_case,
+        max_results,
+    })?;
+
+    let mut state = state.lock().map_err(|_| AppError::lock_poisoned("replace state"))?;
+    Ok(state.add_preview(preview))
+}
+
+/// The replacements `query` makes in `scope`, not yet kept for applying (its
+/// `preview_id` is empty).
+pub fn build_preview(scope: &SearchScope, query: &ReplaceQuery) -> CmdResult<ReplacePreview> {
+    let matcher = Matcher::new(&query.query, query.use_regex, query.case_sensitive, query.whole_word)?;
+    let regex = regex::RegexBuilder::new(&search::build_pattern(&query.query, query.use_regex, query.whole_word))
+        .case_insensitive(!query.case_sensitive)
+        .multi_line(true)
+        .crlf(true)
+        .build()?;
+
+    // The search engine narrows the tree down to candidate files in parallel
+    let mut candidates = Vec::new();
+    let never_cancelled = AtomicBool::new(false);
+    // Only which files match matters here; the edits are recomputed below
+    let count_only = MatchOptions { max_matches_per_file: Some(0), ..Default::default() };
+    let stats = search::search_workspace(scope, &matcher, &count_only, query.max_results, &never_cancelled, &mut |result| {
+        candidates.push(result)
+    });
+    candidates.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+
+    let mut next_edit_id = 0;
+    let mut files = Vec::new();
+    let mut skipped_files = Vec::new();
+    for candidate in candidates {
+        let content = match fs::read_to_string(&candidate.path) {
+            Ok(content) => content,
+            Err(e) => {
+                // Non-UTF-8 files are reported rather than rewritten lossily
+                let reason = if e.kind() == std::io::ErrorKind::InvalidData {
+                    "Not UTF-8 text".to_string()
+                } else {
+                    format!("Could not read file: {}", e)
+                };
+                skipped_files.push(SkippedFile {
+                    path: candidate.path,
+                    relative_path: candidate.relative_path,
+                    reason,
+                });
+                continue;
+            }
+        };
+        let edits = collect_edits(&content, &regex, &query.replacement, query.use_regex, query.preserve_case, &mut next_edit_id);
+        if edits.is_empty() {
+            continue;
+        }
+        files.push(FileReplacePreview {
+            path: candidate.path,
+            relative_path: candidate.relative_path,
+            edits,
+            fingerprint: fingerprint(&content),
+        });
+    }
+
+    Ok(ReplacePreview {
+        preview_id: String::new(),
+        total_edits: files.iter().map(|f| f.edits.len()).sum(),
+        files,
+        truncated: stats.truncated,
+        skipped_files,
+    })
+}
+
+/// Write `(path, new content, original content)` triples so that either every file
+/// ends up with its new content or every file keeps its original content.
+fn write_all_or_nothing(writes: &[(PathBuf, String, String)]) -> CmdResult<()> {
+    let mut staged: Vec<PathBuf> = Vec::new();
+    let discard = |staged: &[PathBuf]| {
+        for tmp in staged {
+            let _ = fs::remove_file(tmp);
+        }
+    };
+
+    // Stage every new file next to its target first, so a full disk or a
+    // read-only directory fails before anything has been replaced
+    for (path, content, _) in writes {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let tmp = path.with_file_name(format!(".{}.replace-tmp", file_name));
+        let staged_ok = fs::write(&tmp, content).and_then(|_| {
+            let permissions = fs::metadata(path)?.permissions();
+            fs::set_permissions(&tmp, permissions)
+        });
+        if let Err(e) = staged_ok {
+            let _ = fs::remove_file(&tmp);
+            discard(&staged);
+            return Err(AppError::io(e, "Failed to stage replacement for", path));
+        }
+        staged.push(tmp);
+    }
+
+    for (index, tmp) in staged.iter().enumerate() {
+        let (path, _, _) = &writes[index];
+        if let Err(e) = fs::rename(tmp, path) {
+            // Roll back the files that were already swapped in
+            for (done_path, _, original) in &writes[..index] {
+                let _ = fs::write(done_path, original);
+            }
+            discard(&staged[index..]);
+            return Err(AppError::io(e, "Failed to replace", path));
+        }
+    }
+
+    Ok(())
+}
+
+fn conflict(path: &Path, message: &str) -> AppError {
+    AppError::new(ErrorCode::Conflict, format!("{}: {}", message, path.display())).with_path(path)
+}
+
+/// Apply a preview in one step. Edits whose id is in `excluded` are skipped.
+/// Fails with `Conflict` and writes nothing if any file changed since the preview.
+#[tauri::command(rename_all = "snake_case")]
+pub fn apply_replace(
+    preview_id: String,
+    excluded: Vec<u64>,
+    state: tauri::State<'_, ReplaceSessions>,
+) -> CmdResult<ReplaceApplied> {
+    let mut state = state.lock().map_err(|_| AppError::lock_poisoned("replace state"))?;
+    state.apply(&preview_id, excluded)
+}
+
+/// Restore every file touched by one `apply_replace`, as a single step.
+/// Fails with `Conflict` if any of those files was edited after the replace.
+#[tauri::command(rename_all = "snake_case")]
+pub fn undo_replace(undo_id: String, state: tauri::State<'_, ReplaceSessions>) -> CmdResult<usize> {
+    let mut state = state.lock().map_err(|_| AppError::lock_poisoned("replace state"))?;
+    state.undo(&undo_id)
+}
+
+impl ReplaceState {
+    fn apply(&mut self, preview_id: &str, excluded: Vec<u64>) -> CmdResult<ReplaceApplied> {
+        let preview = self.preview(preview_id)
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, format!("Replace preview not found: {}", preview_id)))?;
+
+        let excluded: HashSet<u64> = excluded.into_iter().collect();
+        let mut writes = Vec::new();
+        let mut edits_applied = 0;
+        for file in &preview.files {
+            let edits: Vec<&ReplaceEdit> = file.edits.iter().filter(|e| !excluded.contains(&e.id)).collect();
+            if edits.is_empty() {
+                continue;
+            }
+
+            let path = PathBuf::from(&file.path);
+            let content = fs::read_to_string(&path).map_err(|e| AppError::io(e, "Failed to read file", &path))?;
+            if fingerprint(&content) != file.fingerprint {
+                return Err(conflict(&path, "File was modified after the replace preview"));
+            }
+
+            let mut new_content = String::with_capacity(content.len());
+            let mut copied_to = 0;
+            for edit in &edits {
+                new_content.push_str(&content[copied_to..edit.start]);
+                new_content.push_str(&edit.replacement);
+                copied_to = edit.end;
+            }
+            new_content.push_str(&content[copied_to..]);
+
+            edits_applied += edits.len();
+            writes.push((path, new_content, content));
+        }
+
+        write_all_or_nothing(&writes)?;
+
+        let undo_id = self.new_id("undo");
+        self.remove_preview(preview_id);
+        self.undo_stack.push(UndoEntry {
+            id: undo_id.clone(),
+            files: writes.iter()
+                .map(|(path, new_content, original)| (path.clone(), original.clone(), fingerprint(new_content)))
+                .collect(),
+        });
+        if self.undo_stack.len() > MAX_UNDO_ENTRIES {
+            self.undo_stack.remove(0);
+        }
+
+        Ok(ReplaceApplied {
+            undo_id,
+            files_changed: writes.len(),
+            edits_applied,
+        })
+    }
+
+    fn undo(&mut self, undo_id: &str) -> CmdResult<usize> {
+        let index = self.undo_stack.iter().position(|entry| entry.id == undo_id)
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, format!("Nothing to undo for: {}", undo_id)))?;
+
+        let mut writes = Vec::new();
+        for (path, original, written) in &self.undo_stack[index].files {
+            let current = fs::read_to_string(path).map_err(|e| AppError::io(e, "Failed to read file", path))?;
+            if fingerprint(&current) != *written {
+                return Err(conflict(path, "File was modified after the replace"));
+            }
+            writes.push((path.clone(), original.clone(), current));
+        }
+
+        write_all_or_nothing(&writes)?;
+        self.undo_stack.remove(index);
+        Ok(writes.len())
+    }
+}
+
+/// Forget a preview the user dismissed without applying it.
+#[tauri::command(rename_all = "snake_case")]
+pub fn discard_replace_preview(preview_id: String, state: tauri::State<'_, ReplaceSessions>) -> CmdResult<bool> {
+    let mut state = state.lock().map_err(|_| AppError::lock_poisoned("replace state"))?;
+    Ok(state.remove_preview(&preview_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::languages::FileTypes;
+
+    const GREETING: &str = "fn greet() {\n    println!(\"hello\");\n    println!(\"hello again\");\n}\n";
+    const FAREWELL: &str = "// hello and goodbye\nfn leave() {}\n";
+
+    fn workspace() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/greet.rs"), GREETING).unwrap();
+        fs::write(dir.path().join("src/leave.rs"), FAREWELL).unwrap();
+        dir
+    }
+
+    fn query(query: &str, replacement: &str) -> ReplaceQuery {
+        ReplaceQuery {
+            query: query.to_string(),
+            replacement: replacement.to_string(),
+            use_regex: false,
+            case_sensitive: false,
+            whole_word: false,
+            preserve_case: false,
+            max_results: 1000,
+        }
+    }
+
+    fn preview(state: &mut ReplaceState, dir: &tempfile::TempDir, query: &ReplaceQuery) -> ReplacePreview {
+        let scope = SearchScope::new(dir.path(), &SearchFilters::default(), FileTypes::default()).unwrap();
+        state.add_preview(build_preview(&scope, query).unwrap())
+    }
+
+    fn paths(dir: &tempfile::TempDir) -> Vec<PathBuf> {
+        vec![dir.path().join("src/greet.rs"), dir.path().join("src/leave.rs")]
+    }
+
+    #[test]
+    fn applies_and_undoes_a_preview() {
+        let dir = workspace();
+        let paths = paths(&dir);
+        let mut state = ReplaceState::default();
+        let preview = preview(&mut state, &dir, &query("hello", "hi"));
+        assert_eq!(preview.total_edits, 3);
+        assert_eq!(preview.files[0].edits[1].line_number, 3);
+        assert_eq!(preview.files[0].edits[1].preview_text, "    println!(\"hi again\");");
+
+        // Skipping one edit leaves that match alone
+        let skipped = preview.files[0].edits[1].id;
+        let applied = state.apply(&preview.preview_id, vec![skipped]).unwrap();
+        assert_eq!((applied.files_changed, applied.edits_applied), (2, 2));
+        assert_eq!(fs::read_to_string(&paths[0]).unwrap(), GREETING.replacen("hello", "hi", 1));
+        assert_eq!(fs::read_to_string(&paths[1]).unwrap(), FAREWELL.replace("hello", "hi"));
+        assert_eq!(state.apply(&preview.preview_id, Vec::new()).unwrap_err().code, ErrorCode::NotFound);
+
+        assert_eq!(state.undo(&applied.undo_id).unwrap(), 2);
+        assert_eq!(fs::read_to_string(&paths[0]).unwrap(), GREETING);
+        assert_eq!(fs::read_to_string(&paths[1]).unwrap(), FAREWELL);
+        assert_eq!(state.undo(&applied.undo_id).unwrap_err().code, ErrorCode::NotFound);
+    }
+
+    #[test]
+    fn replaces_what_the_search_matches() {
+        let dir = workspace();
+        fs::write(dir.path().join("src/names.txt"), "Hello helloWorld HELLO hello\n").unwrap();
+        let mut state = ReplaceState::default();
+        let edits = |preview: &ReplacePreview| -> Vec<(String, String)> {
+            preview.files.iter()
+                .flat_map(|file| file.edits.iter().map(|edit| (edit.original.clone(), edit.replacement.clone())))
+                .collect()
+        };
+
+        let whole_words = preview(&mut state, &dir, &ReplaceQuery { case_sensitive: true, whole_word: true, ..query("Hello", "Hi") });
+        assert_eq!(edits(&whole_words), vec![("Hello".to_string(), "Hi".to_string())]);
+
+        let cased = preview(&mut state, &dir, &ReplaceQuery { whole_word: true, preserve_case: true, ..query("hello", "hi") });
+        let names: Vec<_> = cased.files.iter().map(|file| file.relative_path.as_str()).collect();
+        assert_eq!(names, vec!["src/greet.rs", "src/leave.rs", "src/names.txt"]);
+        assert_eq!(edits(&cased)[3..], [
+            ("Hello".to_string(), "Hi".to_string()),
+            ("HELLO".to_string(), "HI".to_string()),
+            ("hello".to_string(), "hi".to_string()),
+        ]);
+
+        let groups = preview(&mut state, &dir, &ReplaceQuery { use_regex: true, ..query(r#"println!\("(\w+)"\)"#, "log!(\"$1\")") });
+        assert_eq!(edits(&groups), vec![(r#"println!("hello")"#.to_string(), r#"log!("hello")"#.to_string())]);
+
+        let limited = preview(&mut state, &dir, &ReplaceQuery { max_results: 1, ..query("hello", "hi") });
+        assert_eq!(limited.files.len(), 1);
+        assert!(limited.truncated);
+    }
+
+    #[test]
+    fn reports_files_that_are_not_utf8() {
+        let dir = workspace();
+        let latin1 = dir.path().join("src/latin1.txt");
+        fs::write(&latin1, b"hello caf\xe9\n").unwrap();
+        let mut state = ReplaceState::default();
+        let preview = preview(&mut state, &dir, &query("hello", "hi"));
+        let names: Vec<_> = preview.files.iter().map(|file| file.relative_path.as_str()).collect();
+        assert_eq!(names, vec!["src/greet.rs", "src/leave.rs"]);
+        assert_eq!(preview.skipped_files.len(), 1);
+        assert_eq!(preview.skipped_files[0].relative_path, "src/latin1.txt");
+        assert_eq!(preview.skipped_files[0].reason, "Not UTF-8 text");
+
+        state.apply(&preview.preview_id, Vec::new()).unwrap();
+        assert_eq!(fs::read(&latin1).unwrap(), b"hello caf\xe9\n");
+    }
+
+    #[test]
+    fn refuses_files_changed_since_the_preview() {
+        let dir = workspace();
+        let paths = paths(&dir);
+        let mut state = ReplaceState::default();
+        let stale = preview(&mut state, &dir, &query("hello", "hi"));
+        fs::write(&paths[1], "// hello, edited meanwhile\n").unwrap();
+
+        let error = state.apply(&stale.preview_id, Vec::new()).unwrap_err();
+        assert_eq!(error.code, ErrorCode::Conflict);
+        // Nothing is written, not even the file that did not change
+        assert_eq!(fs::read_to_string(&paths[0]).unwrap(), GREETING);
+
+        // Nor is an undo applied over later edits
+        let fresh = preview(&mut state, &dir, &query("hello", "hi"));
+        let applied = state.apply(&fresh.preview_id, Vec::new()).unwrap();
+        fs::write(&paths[0], "fn greet() {}\n").unwrap();
+        assert_eq!(state.undo(&applied.undo_id).unwrap_err().code, ErrorCode::Conflict);
+        assert_eq!(fs::read_to_string(&paths[1]).unwrap(), "// hi, edited meanwhile\n");
+    }
+
+    #[test]
+    fn rolls_back_when_a_rename_fails() {
+        let dir = workspace();
+        let greet = dir.path().join("src/greet.rs");
+        // A file cannot be renamed over a directory
+        let blocked = dir.path().join("src/blocked");
+        fs::create_dir(&blocked).unwrap();
+
+        let writes = vec![
+            (greet.clone(), "fn greet() {}\n".to_string(), GREETING.to_string()),
+            (blocked.clone(), "fn blocked() {}\n".to_string(), String::new()),
+        ];
+        let error = write_all_or_nothing(&writes).unwrap_err();
+        assert!(error.message.starts_with("Failed to replace"), "{}", error.message);
+        assert_eq!(fs::read_to_string(&greet).unwrap(), GREETING);
+        assert!(blocked.is_dir());
+        let leftovers: Vec<_> = fs::read_dir(dir.path().join("src")).unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".replace-tmp"))
+            .collect();
+        assert!(leftovers.is_empty(), "staged files left behind: {:?}", leftovers);
+    }
+
+    #[test]
+    fn keeps_only_recent_previews() {
+        let dir = workspace();
+        let mut state = ReplaceState::default();
+        let first = preview(&mut state, &dir, &query("hello", "hi"));
+        for _ in 0..MAX_PREVIEWS {
+            preview(&mut state, &dir, &query("hello", "hi"));
+        }
+        assert_eq!(state.previews.len(), MAX_PREVIEWS);
+        assert_eq!(state.apply(&first.preview_id, Vec::new()).unwrap_err().code, ErrorCode::NotFound);
+    }
+}
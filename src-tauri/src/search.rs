@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::{AppError, CmdResult, ErrorCode};
+use crate::languages;
+
+mod engine;
+mod index;
+
+pub use engine::{build_pattern, for_each_file, is_ignored_dir_name, search_workspace, MatchOptions, Matcher, SearchFilters, SearchResult, SearchScope};
+pub use index::{IndexHandle, IndexStatus, TrigramQuery};
+
+/// One file worth of results, emitted as `search_results` while a job runs.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchBatch {
+    pub search_id: String,
+    pub results: Vec<SearchResult>,
+}
+
+/// Emitted exactly once as `search_finished` when a job completes or is cancelled.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSummary {
+    pub search_id: String,
+    pub files_scanned: usize,
+    pub files_matched: usize,
+    pub matches: usize,
+    pub truncated: bool,
+    pub cancelled: bool,
+    pub elapsed_ms: u64,
+    /// Whether the trigram index narrowed the files that were scanned.
+    pub used_index: bool,
+}
+
+/// Running search jobs, keyed by the id the frontend picked. Each job polls its flag.
+pub type SearchJobs = Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>;
+
+/// Trigram indexes of workspaces that enabled one, keyed by workspace path.
+pub type SearchIndexes = Arc<Mutex<HashMap<PathBuf, Arc<IndexHandle>>>>;
+
+pub fn validate_workspace(workspace_path: &str) -> CmdResult<&Path> {
+    let workspace = Path::new(workspace_path);
+    if !workspace.exists() || !workspace.is_dir() {
+        return Err(AppError::new(ErrorCode::NotADirectory, "Invalid workspace path").with_path(workspace));
+    }
+    Ok(workspace)
+}
+
+/// Restrict `scope` to the files the workspace's index says can match. Without an
+/// index, while it is building or stale, or for searches the index does not cover
+/// (open files only, gitignore off), the scope is returned unchanged for a full scan.
+pub fn narrow_with_index(
+    scope: SearchScope,
+    indexes: &SearchIndexes,
+    query: &str,
+    use_regex: bool,
+    case_sensitive: bool,
+    whole_word: bool,
+) -> (SearchScope, bool) {
+    if !scope.can_use_index() {
+        return (scope, false);
+    }
+    let handle = indexes.lock().ok().and_then(|indexes| indexes.get(scope.root()).cloned());
+    let Some(handle) = handle else {
+        return (scope, false);
+    };
+
+    let plan = TrigramQuery::for_pattern(&build_pattern(query, use_regex, whole_word), !case_sensitive);
+    match handle.candidates(&plan) {
+        Some(candidates) => (scope.with_candidates(candidates), true),
+        None => (scope, false),
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)]
+pub fn search_in_files(
+    workspace_path: String,
+    query: String,
+    use_regex: bool,
+    case_sensitive: bool,
+    whole_word: bool,
+    max_results: usize,
+    filters: Option<SearchFilters>,
+    match_options: Option<MatchOptions>,
+    indexes: tauri::State<'_, SearchIndexes>,
+    app_handle: AppHandle,
+) -> CmdResult<Vec<SearchResult>> {
+    if query.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let workspace = validate_workspace(&workspace_path)?;
+    let matcher = Matcher::new(&query, use_regex, case_sensitive, whole_word)?;
+    let scope = SearchScope::new(workspace, &filters.unwrap_or_default(), languages::load_file_types(&app_handle))?;
+    let (scope, _) = narrow_with_index(scope, &indexes, &query, use_regex, case_sensitive, whole_word);
+    let match_options = match_options.unwrap_or_default();
+
+    let mut results = Vec::new();
+    let never_cancelled = AtomicBool::new(false);
+    search_workspace(&scope, &matcher, &match_options, max_results, &never_cancelled, &mut |result| {
+        results.push(result)
+    });
+
+    // Workers finish in any order; keep the one-shot command's output stable
+    results.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    Ok(results)
+}
+
+/// Start a background search. Results stream as `search_results` events and the
+/// job always ends with one `search_finished` event carrying a [`SearchSummary`].
+/// Starting a job with an id that is still running cancels the old one first.
+#[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)]
+pub fn start_workspace_search(
+    search_id: String,
+    workspace_path: String,
+    query: String,
+    use_regex: bool,
+    case_sensitive: bool,
+    whole_word: bool,
+    max_results: usize,
+    filters: Option<SearchFilters>,
+    match_options: Option<MatchOptions>,
+    jobs: tauri::State<'_, SearchJobs>,
+    indexes: tauri::State<'_, SearchIndexes>,
+    app_handle: AppHandle,
+) -> CmdResult<String> {
+    // Validate up front so bad input is reported as a command error, not an event
+    let workspace = validate_workspace(&workspace_path)?;
+    let matcher = Matcher::new(&query, use_regex, case_sensitive, whole_word)?;
+    let scope = SearchScope::new(workspace, &filters.unwrap_or_default(), languages::load_file_types(&app_handle))?;
+    let (scope, used_index) = narrow_with_index(scope, &indexes, &query, use_regex, case_sensitive, whole_word);
+    let match_options = match_options.unwrap_or_default();
+
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    {
+        let mut jobs = jobs.lock().map_err(|_| AppError::lock_poisoned("search jobs"))?;
+        if let Some(previous) = jobs.insert(search_id.clone(), cancel_flag.clone()) {
+            previous.store(true, Ordering::Relaxed);
+        }
+    }
+
+    let jobs = jobs.inner().clone();
+    let job_id = search_id.clone();
+    std::thread::spawn(move || {
+        let started = Instant::now();
+        let mut stats = engine::SearchStats::default();
+
+        if !query.is_empty() {
+            stats = search_workspace(&scope, &matcher, &match_options, max_results, &cancel_flag, &mut |result| {
+                let _ = app_handle.emit("search_results", SearchBatch {
+                    search_id: job_id.clone(),
+                    results: vec![result],
+                });
+            });
+        }
+
+        // Only drop our own entry; a restarted job with the same id owns a different flag
+        if let Ok(mut jobs) = jobs.lock() {
+            if jobs.get(&job_id).is_some_and(|flag| Arc::ptr_eq(flag, &cancel_flag)) {
+                jobs.remove(&job_id);
+            }
+        }
+
+        let _ = app_handle.emit("search_finished", SearchSummary {
+            search_id: job_id,
+            files_scanned: stats.files_scanned,
+            files_matched: stats.files_matched,
+            matches: stats.matches,
+            truncated: stats.truncated,
+            cancelled: cancel_flag.load(Ordering::Relaxed),
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            used_index,
+        });
+    });
+
+    Ok(search_id)
+}
+
+/// Cancel a running search, workspace or history. Returns `false` when no job with
+/// that id is running.
+#[tauri::command(rename_all = "snake_case")]
+pub fn cancel_workspace_search(search_id: String, jobs: tauri::State<'_, SearchJobs>) -> CmdResult<bool> {
+    let jobs = jobs.lock().map_err(|_| AppError::lock_poisoned("search jobs"))?;
+    match jobs.get(&search_id) {
+        Some(flag) => {
+            flag.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// 64-bit FNV-1a, for hashes that are persisted or sent elsewhere: unlike
+/// `DefaultHasher` it is stable across runs and builds.
+pub fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// A per-workspace file under `dir` in the app data directory, e.g. a persisted
+/// index, named by the [`stable_hash`] of the workspace path.
+pub fn workspace_data_file(workspace: &Path, dir: &str, extension: &str, app_handle: &AppHandle) -> CmdResult<PathBuf> {
+    let data_dir = app_handle.path().app_data_dir()
+        .map_err(|e| AppError::new(ErrorCode::Io, format!("Failed to get app data directory: {}", e)))?;
+    let hash = stable_hash(workspace.to_string_lossy().as_bytes());
+    Ok(data_dir.join(dir).join(format!("{:016x}.{}", hash, extension)))
+}
+
+/// Build (or load) a trigram index for the workspace and keep it current from file
+/// change notifications. Searches use it once it is ready. With `persist` the index
+/// is saved under the app data directory and reloaded next time.
+#[tauri::command(rename_all = "snake_case")]
+pub fn enable_search_index(
+    workspace_path: String,
+    persist: bool,
+    indexes: tauri::State<'_, SearchIndexes>,
+    app_handle: AppHandle,
+) -> CmdResult<IndexStatus> {
+    let workspace = validate_workspace(&workspace_path)?;
+    let mut indexes = indexes.lock().map_err(|_| AppError::lock_poisoned("search indexes"))?;
+    if let Some(handle) = indexes.get(workspace) {
+        if handle.index_file().is_some() == persist {
+            return Ok(handle.status());
+        }
+        handle.stop();
+    }
+
+    let index_file = if persist { Some(workspace_data_file(workspace, "search-index", "idx", &app_handle)?) } else { None };
+    let handle = IndexHandle::start(workspace, languages::load_file_types(&app_handle), index_file)
+        .map_err(|e| AppError::io(e, "Failed to start search index for", workspace))?;
+    let status = handle.status();
+    indexes.insert(workspace.to_path_buf(), handle);
+    Ok(status)
+}
+
+/// Stop indexing the workspace. `delete_from_disk` also removes a persisted index.
+/// Returns `false` when no index was enabled.
+#[tauri::command(rename_all = "snake_case")]
+pub fn disable_search_index(
+    workspace_path: String,
+    delete_from_disk: bool,
+    indexes: tauri::State<'_, SearchIndexes>,
+) -> CmdResult<bool> {
+    let mut indexes = indexes.lock().map_err(|_| AppError::lock_poisoned("search indexes"))?;
+    let Some(handle) = indexes.remove(Path::new(&workspace_path)) else {
+        return Ok(false);
+    };
+    handle.stop();
+    if delete_from_disk {
+        if let Some(file) = handle.index_file() {
+            if file.exists() {
+                std::fs::remove_file(file).map_err(|e| AppError::io(e, "Failed to delete search index", file))?;
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// State, size in memory and on disk, and pending updates of the workspace's index.
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_search_index_status(workspace_path: String, indexes: tauri::State<'_, SearchIndexes>) -> CmdResult<IndexStatus> {
+    let indexes = indexes.lock().map_err(|_| AppError::lock_poisoned("search indexes"))?;
+    Ok(indexes.get(Path::new(&workspace_path))
+        .map(|handle| handle.status())
+        .unwrap_or_else(IndexStatus::disabled))
+}
@@ -0,0 +1,680 @@
+//! Parallel workspace search engine.
+//!
+//! Kept free of Tauri types so `benches/search.rs` can compile it on its own.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::{DirEntry, WalkBuilder, WalkState};
+use memchr::memmem;
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, CmdResult, ErrorCode};
+use crate::languages::{FileTypes, ForcedKind};
+
+/// Files at least this large are memory-mapped instead of read into a buffer.
+const MMAP_THRESHOLD: u64 = 256 * 1024;
+
+/// How much of a file is sniffed to tell text from binary.
+const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMatch {
+    /// 1-based line the match starts on.
+    pub line_number: usize,
+    /// 1-based UTF-16 column of the match start, as the editor counts columns.
+    pub column: usize,
+    pub end_line_number: usize,
+    /// 1-based UTF-16 column just past the match end.
+    pub end_column: usize,
+    /// `column` and `end_column` counted in Unicode characters instead.
+    pub char_column: usize,
+    pub end_char_column: usize,
+    /// Every line the match touches, joined with `\n` and without line terminators.
+    pub text: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub context_before: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub context_after: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub path: String,
+    pub name: String,
+    #[serde(rename = "relativePath")]
+    pub relative_path: String,
+    /// Id from the language registry, honouring `files.associations`.
+    #[serde(rename = "languageId")]
+    pub language_id: Option<String>,
+    pub matches: Vec<SearchMatch>,
+    /// Matches in the whole file, including any beyond `max_matches_per_file`.
+    #[serde(rename = "totalMatches")]
+    pub total_matches: usize,
+}
+
+/// What each match carries and how many are kept per file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MatchOptions {
+    /// Lines of context reported before each match.
+    pub before_context: usize,
+    /// Lines of context reported after each match.
+    pub after_context: usize,
+    /// Matches returned per file; the rest are still counted. `None` keeps all.
+    pub max_matches_per_file: Option<usize>,
+}
+
+/// Which files a search looks at, mirroring the filters of VS Code's search panel.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchFilters {
+    /// "files to include": comma-separated globs, empty means every file.
+    pub include: String,
+    /// "files to exclude": comma-separated globs, applied to folders as well.
+    pub exclude: String,
+    /// Search exactly these files instead of walking the workspace.
+    pub open_files: Option<Vec<String>>,
+    /// Honour `.gitignore`/`.ignore` files and skip build output and hidden folders.
+    pub respect_gitignore: bool,
+    /// Folder inside the workspace to walk instead of the whole workspace.
+    pub scope: Option<String>,
+}
+
+impl Default for SearchFilters {
+    fn default() -> Self {
+        SearchFilters {
+            include: String::new(),
+            exclude: String::new(),
+            open_files: None,
+            respect_gitignore: true,
+            scope: None,
+        }
+    }
+}
+
+/// [`SearchFilters`] resolved against a workspace, with globs compiled.
+#[derive(Clone)]
+pub struct SearchScope {
+    root: PathBuf,
+    walk_root: PathBuf,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    open_files: Option<Vec<PathBuf>>,
+    /// Files a content index narrowed the search down to.
+    candidates: Option<Vec<PathBuf>>,
+    respect_gitignore: bool,
+    file_types: FileTypes,
+}
+
+impl SearchScope {
+    pub fn new(root: &Path, filters: &SearchFilters, file_types: FileTypes) -> CmdResult<Self> {
+        let walk_root = match filters.scope.as_deref().filter(|s| !s.is_empty()) {
+            Some(scope) => {
+                let scope = root.join(scope);
+                let inside = match (scope.canonicalize(), root.canonicalize()) {
+                    (Ok(scope), Ok(root)) => scope.starts_with(root),
+                    _ => false,
+                };
+                if !inside || !scope.is_dir() {
+                    return Err(AppError::new(ErrorCode::InvalidInput, "Search scope must be a folder inside the workspace")
+                        .with_path(&scope));
+                }
+                scope
+            }
+            None => root.to_path_buf(),
+        };
+
+        let open_files = filters.open_files.as_ref().map(|paths| {
+            let mut seen = HashSet::new();
+            paths.iter()
+                .map(PathBuf::from)
+                .filter(|path| seen.insert(path.clone()))
+                .collect()
+        });
+
+        Ok(SearchScope {
+            root: root.to_path_buf(),
+            walk_root,
+            include: compile_globs(&filters.include)?,
+            exclude: compile_globs(&filters.exclude)?,
+            open_files,
+            candidates: None,
+            respect_gitignore: filters.respect_gitignore,
+            file_types,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Whether this search walks the workspace the way a content index sees it,
+    /// so index candidates can stand in for the walk.
+    pub fn can_use_index(&self) -> bool {
+        self.open_files.is_none() && self.respect_gitignore
+    }
+
+    /// Search only `candidates` instead of walking; filters still apply to them.
+    pub fn with_candidates(mut self, candidates: Vec<PathBuf>) -> Self {
+        self.candidates = Some(candidates);
+        self
+    }
+
+    /// The same scope, walking only `dir` (which must be inside the workspace).
+    pub fn with_walk_root(mut self, dir: &Path) -> Self {
+        self.walk_root = dir.to_path_buf();
+        self
+    }
+
+    pub fn file_types(&self) -> &FileTypes {
+        &self.file_types
+    }
+
+    fn listed_files(&self) -> Option<&[PathBuf]> {
+        self.open_files.as_deref().or(self.candidates.as_deref())
+    }
+
+    /// Workspace-relative path with `/` separators, as the globs expect.
+    pub fn relative(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path).to_string_lossy();
+        if MAIN_SEPARATOR == '/' {
+            relative.into_owned()
+        } else {
+            relative.replace(MAIN_SEPARATOR, "/")
+        }
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        self.exclude.as_ref().is_some_and(|set| set.is_match(self.relative(path)))
+    }
+
+    fn is_included(&self, path: &Path) -> bool {
+        let relative = self.relative(path);
+        self.include.as_ref().is_none_or(|set| set.is_match(&relative))
+            && !self.exclude.as_ref().is_some_and(|set| set.is_match(&relative))
+    }
+}
+
+/// Split a comma-separated glob list, leaving commas inside `{a,b}` alone.
+fn split_globs(patterns: &str) -> Vec<&str> {
+    let mut globs = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in patterns.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                globs.push(&patterns[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    globs.push(&patterns[start..]);
+    globs.into_iter().map(str::trim).filter(|g| !g.is_empty()).collect()
+}
+
+/// Compile globs the way VS Code reads them: `./src` is anchored at the workspace
+/// root, anything else may match at any depth, and a folder matches its contents.
+fn compile_globs(patterns: &str) -> CmdResult<Option<GlobSet>> {
+    let globs = split_globs(patterns);
+    if globs.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        let glob = glob.trim_end_matches('/');
+        let glob = match glob.strip_prefix("./") {
+            Some(anchored) => anchored.to_string(),
+            None if glob.starts_with("**") => glob.to_string(),
+            None => format!("**/{}", glob.trim_start_matches('/')),
+        };
+        for pattern in [glob.clone(), format!("{}/**", glob)] {
+            let compiled = GlobBuilder::new(&pattern)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| AppError::new(ErrorCode::InvalidPattern, format!("Invalid glob pattern: {}", e)))?;
+            builder.add(compiled);
+        }
+    }
+    let set = builder.build()
+        .map_err(|e| AppError::new(ErrorCode::InvalidPattern, format!("Invalid glob pattern: {}", e)))?;
+    Ok(Some(set))
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SearchStats {
+    pub files_scanned: usize,
+    pub files_matched: usize,
+    pub matches: usize,
+    pub truncated: bool,
+}
+
+/// Compiled form of a search query.
+///
+/// Plain case-sensitive text skips the regex engine and uses a SIMD `memmem`
+/// finder; everything else goes through a byte regex so file contents never
+/// have to be validated or copied into a `String` first.
+pub enum Matcher {
+    Literal(Box<memmem::Finder<'static>>),
+    Regex(regex::bytes::Regex),
+}
+
+impl Matcher {
+    pub fn new(query: &str, use_regex: bool, case_sensitive: bool, whole_word: bool) -> CmdResult<Self> {
+        if !use_regex && case_sensitive && !whole_word {
+            return Ok(Matcher::Literal(Box::new(memmem::Finder::new(query.as_bytes()).into_owned())));
+        }
+
+        // The whole file is one haystack, so anchors have to work per line
+        let regex = regex::bytes::RegexBuilder::new(&build_pattern(query, use_regex, whole_word))
+            .case_insensitive(!case_sensitive)
+            .multi_line(true)
+            .crlf(true)
+            .build()?;
+        Ok(Matcher::Regex(regex))
+    }
+
+    /// Byte range of the first match starting at or after `start`.
+    pub fn find_at(&self, haystack: &[u8], start: usize) -> Option<(usize, usize)> {
+        match self {
+            Matcher::Literal(finder) => {
+                let offset = finder.find(&haystack[start..])?;
+                Some((start + offset, start + offset + finder.needle().len()))
+            }
+            Matcher::Regex(regex) => regex.find_at(haystack, start).map(|m| (m.start(), m.end())),
+        }
+    }
+}
+
+/// Regex source for a query as typed into the search box.
+pub fn build_pattern(query: &str, use_regex: bool, whole_word: bool) -> String {
+    if use_regex {
+        query.to_string()
+    } else {
+        // Escape regex special characters
+        let escaped = regex::escape(query);
+        if whole_word {
+            format!(r"\b{}\b", escaped)
+        } else {
+            escaped
+        }
+    }
+}
+
+pub enum FileContents {
+    Buffered(Vec<u8>),
+    Mapped(Mmap),
+}
+
+impl FileContents {
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            FileContents::Buffered(buffer) => buffer,
+            FileContents::Mapped(map) => map,
+        }
+    }
+}
+
+pub fn read_file(path: &Path) -> io::Result<FileContents> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    if len >= MMAP_THRESHOLD {
+        // SAFETY: the map is read-only and dropped before this search returns. If
+        // another process truncates the file meanwhile we may read stale bytes,
+        // which is acceptable for search results.
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(FileContents::Mapped(map))
+    } else {
+        let mut buffer = Vec::with_capacity(len as usize);
+        file.read_to_end(&mut buffer)?;
+        Ok(FileContents::Buffered(buffer))
+    }
+}
+
+/// Text encodings a searched file can be in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+/// Decide from the first bytes whether a file is text, and in which encoding.
+///
+/// A UTF-16 byte order mark means text; otherwise a NUL byte, a high share of
+/// control characters, or invalid UTF-8 that is mostly high bytes means binary.
+/// Invalid UTF-8 on its own is still text, so Latin-1 sources stay searchable.
+pub fn sniff_encoding(bytes: &[u8]) -> Option<TextEncoding> {
+    if bytes.starts_with(&[0xFF, 0xFE]) {
+        return Some(TextEncoding::Utf16Le);
+    }
+    if bytes.starts_with(&[0xFE, 0xFF]) {
+        return Some(TextEncoding::Utf16Be);
+    }
+
+    let sniff = &bytes[..bytes.len().min(BINARY_SNIFF_LEN)];
+    if memchr::memchr(0, sniff).is_some() {
+        return None;
+    }
+
+    let control = sniff.iter()
+        .filter(|&&b| (b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0C | 0x1B)) || b == 0x7F)
+        .count();
+    if control * 10 > sniff.len() {
+        return None;
+    }
+
+    if let Err(e) = std::str::from_utf8(sniff) {
+        // `error_len() == None` is just a character cut off by the sniff window
+        let high = sniff.iter().filter(|&&b| b >= 0x80).count();
+        if e.error_len().is_some() && high * 10 > sniff.len() * 3 {
+            return None;
+        }
+    }
+    Some(TextEncoding::Utf8)
+}
+
+/// The bytes to search: UTF-8 as-is, UTF-16 transcoded (without its BOM).
+pub fn text_bytes(bytes: &[u8], encoding: TextEncoding) -> Cow<'_, [u8]> {
+    let units = bytes.get(2..).unwrap_or_default().chunks_exact(2);
+    let units: Vec<u16> = match encoding {
+        TextEncoding::Utf8 => return Cow::Borrowed(bytes),
+        TextEncoding::Utf16Le => units.map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect(),
+        TextEncoding::Utf16Be => units.map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect(),
+    };
+    let text: String = char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect();
+    Cow::Owned(text.into_bytes())
+}
+
+/// Hidden directories and common build output, skipped unless gitignore handling is off.
+pub fn is_ignored_dir_name(name: &str) -> bool {
+    name.starts_with('.') ||
+    name == "node_modules" ||
+    name == "target" ||
+    name == "dist" ||
+    name == "build"
+}
+
+fn is_skipped_dir(entry: &DirEntry, scope: &SearchScope) -> bool {
+    if entry.depth() == 0 || !entry.file_type().is_some_and(|t| t.is_dir()) {
+        return false;
+    }
+    if scope.is_excluded(entry.path()) {
+        return true;
+    }
+    match entry.file_name().to_str() {
+        Some(".git") => true,
+        Some(name) => scope.respect_gitignore && is_ignored_dir_name(name),
+        None => false,
+    }
+}
+
+/// Search one file. `Ok(None)` means the file was binary or had no matches;
+/// otherwise the kept matches come back with the file's total match count.
+/// `forced` comes from [`FileTypes::forced_kind`] and skips content sniffing.
+pub fn search_file(
+    path: &Path,
+    matcher: &Matcher,
+    options: &MatchOptions,
+    forced: Option<ForcedKind>,
+) -> io::Result<Option<(Vec<SearchMatch>, usize)>> {
+    if forced == Some(ForcedKind::Binary) {
+        return Ok(None);
+    }
+    let contents = read_file(path)?;
+    let encoding = match (sniff_encoding(contents.as_bytes()), forced) {
+        (Some(encoding), _) => encoding,
+        (None, Some(ForcedKind::Text)) => TextEncoding::Utf8,
+        (None, _) => return Ok(None),
+    };
+    let bytes = text_bytes(contents.as_bytes(), encoding);
+
+    let (matches, total) = search_in_bytes(&bytes, matcher, options);
+    Ok(if total == 0 { None } else { Some((matches, total)) })
+}
+
+/// Byte offsets of the line containing `pos`, without its `\n`.
+fn line_bounds(haystack: &[u8], pos: usize) -> (usize, usize) {
+    let start = memchr::memrchr(b'\n', &haystack[..pos]).map_or(0, |i| i + 1);
+    let end = memchr::memchr(b'\n', &haystack[pos..]).map_or(haystack.len(), |i| pos + i);
+    (start, end)
+}
+
+fn line_text(line: &[u8]) -> String {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    String::from_utf8_lossy(line).to_string()
+}
+
+/// 1-based (UTF-16, character) columns of `prefix.len()`, decoding the same way as `text`.
+fn columns(prefix: &[u8]) -> (usize, usize) {
+    let prefix = String::from_utf8_lossy(prefix);
+    (prefix.encode_utf16().count() + 1, prefix.chars().count() + 1)
+}
+
+fn context_before(haystack: &[u8], line_start: usize, count: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut end = line_start;
+    while lines.len() < count && end > 0 {
+        // `end - 1` is the newline that terminates the previous line
+        let start = memchr::memrchr(b'\n', &haystack[..end - 1]).map_or(0, |i| i + 1);
+        lines.push(line_text(&haystack[start..end - 1]));
+        end = start;
+    }
+    lines.reverse();
+    lines
+}
+
+fn context_after(haystack: &[u8], line_end: usize, count: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut start = line_end + 1;
+    while lines.len() < count && start < haystack.len() {
+        let end = memchr::memchr(b'\n', &haystack[start..]).map_or(haystack.len(), |i| start + i);
+        lines.push(line_text(&haystack[start..end]));
+        start = end + 1;
+    }
+    lines
+}
+
+fn search_in_bytes(haystack: &[u8], matcher: &Matcher, options: &MatchOptions) -> (Vec<SearchMatch>, usize) {
+    let limit = options.max_matches_per_file.unwrap_or(usize::MAX);
+    let mut matches = Vec::new();
+    let mut total = 0;
+    let mut line_number = 1;
+    let mut counted_to = 0;
+    let mut pos = 0;
+
+    while pos <= haystack.len() {
+        let Some((start, end)) = matcher.find_at(haystack, pos) else {
+            break;
+        };
+        total += 1;
+        // Step over empty matches so patterns like `a*` still terminate
+        pos = if end > start { end } else { start + 1 };
+
+        // Past the limit matches are only counted, never materialized
+        if matches.len() >= limit {
+            continue;
+        }
+
+        // Line numbers are only computed for hits, never for every line
+        line_number += memchr::memchr_iter(b'\n', &haystack[counted_to..start]).count();
+        counted_to = start;
+        let end_line_number = line_number + memchr::memchr_iter(b'\n', &haystack[start..end]).count();
+
+        let (line_start, _) = line_bounds(haystack, start);
+        let (end_line_start, end_line_end) = line_bounds(haystack, end);
+        let (column, char_column) = columns(&haystack[line_start..start]);
+        let (end_column, end_char_column) = columns(&haystack[end_line_start..end]);
+
+        let text = haystack[line_start..end_line_end]
+            .split(|&b| b == b'\n')
+            .map(line_text)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        matches.push(SearchMatch {
+            line_number,
+            column,
+            end_line_number,
+            end_column,
+            char_column,
+            end_char_column,
+            text,
+            context_before: context_before(haystack, line_start, options.before_context),
+            context_after: context_after(haystack, end_line_end, options.after_context),
+        });
+    }
+
+    (matches, total)
+}
+
+fn make_result(scope: &SearchScope, path: &Path, matches: Vec<SearchMatch>, total_matches: usize) -> SearchResult {
+    let relative_path = path.strip_prefix(&scope.root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string();
+
+    let name = path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    SearchResult {
+        path: path.to_string_lossy().to_string(),
+        name,
+        relative_path,
+        language_id: scope.file_types.language_for_file(path),
+        matches,
+        total_matches,
+    }
+}
+
+/// Visit every searchable file of `scope` on all cores: the explicit file list if
+/// there is one, otherwise a walk of the scope root. `visit` gets the file's
+/// [`ForcedKind`] and returns `false` to stop early. Binary-forced files are skipped.
+pub fn for_each_file(scope: &SearchScope, visit: &(dyn Fn(&Path, Option<ForcedKind>) -> bool + Sync)) {
+    let visit_path = |path: &Path| -> bool {
+        match scope.file_types.forced_kind(path) {
+            Some(ForcedKind::Binary) => true,
+            forced => visit(path, forced),
+        }
+    };
+
+    if let Some(paths) = scope.listed_files() {
+        let next = AtomicUsize::new(0);
+        let quit = AtomicBool::new(false);
+        let workers = std::thread::available_parallelism().map_or(4, |n| n.get()).min(paths.len().max(1));
+        std::thread::scope(|threads| {
+            for _ in 0..workers {
+                threads.spawn(|| {
+                    while !quit.load(Ordering::Relaxed) {
+                        let Some(path) = paths.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                            break;
+                        };
+                        if !path.starts_with(&scope.walk_root) || !path.is_file() || !scope.is_included(path) {
+                            continue;
+                        }
+                        if !visit_path(path) {
+                            quit.store(true, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+        return;
+    }
+
+    let respect = scope.respect_gitignore;
+    let dir_scope = scope.clone();
+    let walker = WalkBuilder::new(&scope.walk_root)
+        .hidden(false)
+        .parents(respect)
+        .ignore(respect)
+        .git_ignore(respect)
+        .git_global(respect)
+        .git_exclude(respect)
+        .require_git(false)
+        .filter_entry(move |entry| !is_skipped_dir(entry, &dir_scope))
+        .build_parallel();
+
+    let visit_path = &visit_path;
+    walker.run(|| {
+        Box::new(move |entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(_) => return WalkState::Continue,
+            };
+            if !entry.file_type().is_some_and(|t| t.is_file()) || !scope.is_included(entry.path()) {
+                return WalkState::Continue;
+            }
+            if visit_path(entry.path()) {
+                WalkState::Continue
+            } else {
+                WalkState::Quit
+            }
+        })
+    });
+}
+
+/// Search every file of `scope` on all cores and report each match to `on_result`.
+///
+/// `on_result` runs on the calling thread, in completion order. The search stops
+/// early once `max_results` files have matched or `cancelled` is set.
+pub fn search_workspace(
+    scope: &SearchScope,
+    matcher: &Matcher,
+    options: &MatchOptions,
+    max_results: usize,
+    cancelled: &AtomicBool,
+    on_result: &mut dyn FnMut(SearchResult),
+) -> SearchStats {
+    let files_scanned = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let mut stats = SearchStats::default();
+
+    let (tx, rx) = mpsc::channel::<SearchResult>();
+    std::thread::scope(|threads| {
+        let files_scanned = &files_scanned;
+        let stop = &stop;
+        threads.spawn(move || {
+            for_each_file(scope, &|path, forced| {
+                if stop.load(Ordering::Relaxed) || cancelled.load(Ordering::Relaxed) {
+                    return false;
+                }
+                files_scanned.fetch_add(1, Ordering::Relaxed);
+                match search_file(path, matcher, options, forced) {
+                    Ok(Some((matches, total))) => tx.send(make_result(scope, path, matches, total)).is_ok(),
+                    _ => true,
+                }
+            });
+        });
+
+        for result in rx {
+            if stats.files_matched >= max_results {
+                stats.truncated = true;
+                stop.store(true, Ordering::Relaxed);
+                break;
+            }
+            stats.files_matched += 1;
+            stats.matches += result.total_matches;
+            on_result(result);
+        }
+    });
+
+    stats.files_scanned = files_scanned.load(Ordering::Relaxed);
+    stats
+}
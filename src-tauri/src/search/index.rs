@@ -0,0 +1,916 @@
+//! Optional trigram index of workspace contents, in the spirit of codesearch and Zoekt.
+//!
+//! Every text file is reduced to the set of (ASCII-lowercased) byte trigrams it
+//! contains. A query is turned into an AND/OR tree of trigrams that any match must
+//! contain, and only files whose posting lists satisfy the tree are searched.
+//! The index is a pre-filter only: the normal engine still verifies every match.
+//!
+//! Kept free of Tauri types, like the rest of the search engine.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use regex_syntax::hir::{Class, Hir, HirKind};
+use serde::Serialize;
+
+use super::engine::{for_each_file, is_ignored_dir_name, read_file, sniff_encoding, text_bytes, SearchFilters, SearchScope};
+use crate::languages::{FileTypes, ForcedKind};
+
+/// Larger files are not broken into trigrams; they are always searched directly.
+const MAX_INDEXED_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Beyond this many unapplied changes queries fall back to a full scan.
+const MAX_PENDING_CHANGES: usize = 5_000;
+
+/// How often queued file changes are folded into the index.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Persisted indexes are rewritten at most this often while files change.
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+const INDEX_MAGIC: &[u8; 4] = b"CTIX";
+const INDEX_VERSION: u32 = 1;
+
+type Trigram = u32;
+
+fn trigram(a: u8, b: u8, c: u8) -> Trigram {
+    (a.to_ascii_lowercase() as u32) << 16 | (b.to_ascii_lowercase() as u32) << 8 | c.to_ascii_lowercase() as u32
+}
+
+/// Distinct trigrams of `bytes`, sorted.
+fn trigrams_of(bytes: &[u8]) -> Vec<Trigram> {
+    let mut trigrams: Vec<Trigram> = bytes.windows(3).map(|w| trigram(w[0], w[1], w[2])).collect();
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    trigrams
+}
+
+fn mtime_of(metadata: &fs::Metadata) -> (u64, u32) {
+    metadata.modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or((0, 0), |d| (d.as_secs(), d.subsec_nanos()))
+}
+
+#[derive(Debug, Clone)]
+struct IndexedFile {
+    path: PathBuf,
+    mtime: (u64, u32),
+    size: u64,
+    /// Too large to index; searched on every query.
+    always_scan: bool,
+}
+
+/// What a query needs from a file, as trigrams.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrigramQuery {
+    /// No constraint: every file is a candidate.
+    All,
+    Trigrams(Vec<Trigram>),
+    And(Vec<TrigramQuery>),
+    Or(Vec<TrigramQuery>),
+}
+
+impl TrigramQuery {
+    fn literal(bytes: &[u8]) -> Self {
+        if bytes.len() < 3 {
+            TrigramQuery::All
+        } else {
+            TrigramQuery::Trigrams(trigrams_of(bytes))
+        }
+    }
+
+    /// Plan the trigrams any match of `pattern` must contain. Patterns the planner
+    /// cannot reason about simply produce [`TrigramQuery::All`].
+    pub fn for_pattern(pattern: &str, case_insensitive: bool) -> Self {
+        regex_syntax::ParserBuilder::new()
+            .case_insensitive(case_insensitive)
+            .multi_line(true)
+            .crlf(true)
+            .build()
+            .parse(pattern)
+            .map_or(TrigramQuery::All, |hir| plan(&hir))
+    }
+}
+
+/// The single byte a class stands for once ASCII case is folded, e.g. `[Kk]`.
+/// Non-ASCII members that Unicode case folding adds (the Kelvin sign for `k`) are
+/// ignored, which only matters for files that spell ASCII words with them.
+fn folded_byte(class: &Class) -> Option<u8> {
+    let mut folded = None;
+    let mut check = |start: u32, end: u32| -> bool {
+        if start != end {
+            return start >= 0x80;
+        }
+        if start >= 0x80 {
+            return true;
+        }
+        let byte = (start as u8).to_ascii_lowercase();
+        *folded.get_or_insert(byte) == byte
+    };
+    let consistent = match class {
+        Class::Unicode(class) => class.ranges().iter().all(|r| check(r.start() as u32, r.end() as u32)),
+        Class::Bytes(class) => class.ranges().iter().all(|r| check(r.start() as u32, r.end() as u32)),
+    };
+    if consistent { folded } else { None }
+}
+
+fn plan(hir: &Hir) -> TrigramQuery {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) | HirKind::Class(_) => TrigramQuery::All,
+        HirKind::Literal(literal) => TrigramQuery::literal(&literal.0),
+        HirKind::Capture(capture) => plan(&capture.sub),
+        HirKind::Repetition(repetition) if repetition.min > 0 => plan(&repetition.sub),
+        HirKind::Repetition(_) => TrigramQuery::All,
+        HirKind::Concat(parts) => {
+            let mut all = Vec::new();
+            let mut run = Vec::new();
+            for part in parts {
+                match part.kind() {
+                    HirKind::Literal(literal) => run.extend_from_slice(&literal.0),
+                    HirKind::Class(class) if folded_byte(class).is_some() => run.extend(folded_byte(class)),
+                    // Zero-width, so the bytes on either side are still adjacent
+                    HirKind::Look(_) | HirKind::Empty => {}
+                    _ => {
+                        all.push(TrigramQuery::literal(&run));
+                        run.clear();
+                        all.push(plan(part));
+                    }
+                }
+            }
+            all.push(TrigramQuery::literal(&run));
+            all.retain(|q| *q != TrigramQuery::All);
+            match all.len() {
+                0 => TrigramQuery::All,
+                1 => all.remove(0),
+                _ => TrigramQuery::And(all),
+            }
+        }
+        HirKind::Alternation(branches) => {
+            let branches: Vec<TrigramQuery> = branches.iter().map(plan).collect();
+            if branches.contains(&TrigramQuery::All) {
+                TrigramQuery::All
+            } else {
+                TrigramQuery::Or(branches)
+            }
+        }
+    }
+}
+
+fn intersect(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::with_capacity(a.len().min(b.len()));
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                out.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    out
+}
+
+fn union(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len() + b.len());
+    out.extend_from_slice(a);
+    out.extend_from_slice(b);
+    out.sort_unstable();
+    out.dedup();
+    out
+}
+
+/// `.gitignore`/`.ignore` rules seen while walking, so single paths reported by
+/// the file watcher can be filtered the same way the walk filtered them.
+#[derive(Default)]
+struct IgnoreRules {
+    rules: Vec<(PathBuf, Gitignore)>,
+}
+
+impl IgnoreRules {
+    fn new(ignore_files: &[PathBuf]) -> Self {
+        let rules = ignore_files.iter()
+            .filter_map(|file| {
+                let dir = file.parent()?;
+                let mut builder = GitignoreBuilder::new(dir);
+                builder.add(file);
+                Some((dir.to_path_buf(), builder.build().ok()?))
+            })
+            .collect();
+        IgnoreRules { rules }
+    }
+
+    fn is_ignored(&self, path: &Path) -> bool {
+        self.rules.iter()
+            .filter(|(dir, _)| path.starts_with(dir))
+            .any(|(_, rules)| rules.matched_path_or_any_parents(path, false).is_ignore())
+    }
+}
+
+fn is_ignore_file(path: &Path) -> bool {
+    matches!(path.file_name().and_then(|n| n.to_str()), Some(".gitignore" | ".ignore"))
+}
+
+/// Posting lists for one workspace.
+pub struct TrigramIndex {
+    root: PathBuf,
+    files: Vec<Option<IndexedFile>>,
+    ids: HashMap<PathBuf, u32>,
+    postings: HashMap<Trigram, Vec<u32>>,
+    ignore_files: Vec<PathBuf>,
+    ignore_rules: IgnoreRules,
+    dead: usize,
+}
+
+/// Result of reading one file for the index.
+enum Scanned {
+    Indexed(IndexedFile, Vec<Trigram>),
+    AlwaysScan(IndexedFile),
+    NotText,
+}
+
+fn scan_file(path: &Path, forced: Option<ForcedKind>) -> io::Result<Scanned> {
+    let metadata = fs::metadata(path)?;
+    let file = IndexedFile {
+        path: path.to_path_buf(),
+        mtime: mtime_of(&metadata),
+        size: metadata.len(),
+        always_scan: metadata.len() > MAX_INDEXED_FILE_SIZE,
+    };
+    if forced == Some(ForcedKind::Binary) {
+        return Ok(Scanned::NotText);
+    }
+    if file.always_scan {
+        return Ok(Scanned::AlwaysScan(file));
+    }
+
+    let contents = read_file(path)?;
+    let encoding = match (sniff_encoding(contents.as_bytes()), forced) {
+        (Some(encoding), _) => encoding,
+        (None, Some(ForcedKind::Text)) => super::engine::TextEncoding::Utf8,
+        (None, _) => return Ok(Scanned::NotText),
+    };
+    let trigrams = trigrams_of(&text_bytes(contents.as_bytes(), encoding));
+    Ok(Scanned::Indexed(file, trigrams))
+}
+
+impl TrigramIndex {
+    fn empty(root: &Path) -> Self {
+        TrigramIndex {
+            root: root.to_path_buf(),
+            files: Vec::new(),
+            ids: HashMap::new(),
+            postings: HashMap::new(),
+            ignore_files: Vec::new(),
+            ignore_rules: IgnoreRules::default(),
+            dead: 0,
+        }
+    }
+
+    fn live_files(&self) -> impl Iterator<Item = &IndexedFile> {
+        self.files.iter().flatten()
+    }
+
+    fn remove(&mut self, path: &Path) {
+        if let Some(id) = self.ids.remove(path) {
+            // Posting lists keep the id; it is filtered out at query time and
+            // dropped for good by the next compaction
+            self.files[id as usize] = None;
+            self.dead += 1;
+        }
+    }
+
+    fn insert(&mut self, scanned: Scanned) {
+        let (file, trigrams) = match scanned {
+            Scanned::Indexed(file, trigrams) => (file, trigrams),
+            Scanned::AlwaysScan(file) => (file, Vec::new()),
+            Scanned::NotText => return,
+        };
+        self.remove(&file.path);
+
+        let id = self.files.len() as u32;
+        for trigram in trigrams {
+            self.postings.entry(trigram).or_default().push(id);
+        }
+        self.ids.insert(file.path.clone(), id);
+        self.files.push(Some(file));
+    }
+
+    /// Bring the index in line with the disk: walk `scope`, re-read files whose size
+    /// or mtime changed, and drop files that disappeared. Used for the first build
+    /// and to catch up an index loaded from disk or marked stale.
+    fn refresh(&mut self, scope: &SearchScope, cancelled: &AtomicBool) {
+        let known: HashMap<PathBuf, (u64, u32, u64)> = self.live_files()
+            .map(|f| (f.path.clone(), (f.mtime.0, f.mtime.1, f.size)))
+            .collect();
+        let seen = Mutex::new(HashSet::new());
+        let ignore_files = Mutex::new(Vec::new());
+        let this = Mutex::new(&mut *self);
+
+        for_each_file(scope, &|path, forced| {
+            if cancelled.load(Ordering::Relaxed) {
+                return false;
+            }
+            if is_ignore_file(path) {
+                if let Ok(mut files) = ignore_files.lock() {
+                    files.push(path.to_path_buf());
+                }
+            }
+            if let Ok(mut seen) = seen.lock() {
+                seen.insert(path.to_path_buf());
+            }
+
+            let unchanged = fs::metadata(path).ok().is_some_and(|metadata| {
+                let (secs, nanos) = mtime_of(&metadata);
+                known.get(path) == Some(&(secs, nanos, metadata.len()))
+            });
+            if !unchanged {
+                // Read outside the lock; only the posting list update is serialized
+                if let Ok(scanned) = scan_file(path, forced) {
+                    if let Ok(mut index) = this.lock() {
+                        index.insert(scanned);
+                    }
+                }
+            }
+            true
+        });
+
+        if cancelled.load(Ordering::Relaxed) {
+            return;
+        }
+        let seen = seen.into_inner().unwrap_or_default();
+        for path in known.keys().filter(|path| !seen.contains(*path)) {
+            self.remove(path);
+        }
+        self.ignore_files = ignore_files.into_inner().unwrap_or_default();
+        self.ignore_rules = IgnoreRules::new(&self.ignore_files);
+        self.compact();
+    }
+
+    /// Apply one watcher notification. Directories are walked, vanished paths are
+    /// dropped together with everything that was below them.
+    fn update_path(&mut self, scope: &SearchScope, path: &Path) {
+        if path.is_dir() {
+            let mut files = Vec::new();
+            let dir_scope = scope.clone().with_walk_root(path);
+            let collected = Mutex::new(&mut files);
+            for_each_file(&dir_scope, &|file, _| {
+                if let Ok(mut files) = collected.lock() {
+                    files.push(file.to_path_buf());
+                }
+                true
+            });
+            for file in files {
+                self.update_file(scope, &file);
+            }
+        } else if path.is_file() {
+            self.update_file(scope, path);
+        } else {
+            let gone: Vec<PathBuf> = self.ids.keys().filter(|p| p.starts_with(path)).cloned().collect();
+            for path in gone {
+                self.remove(&path);
+            }
+        }
+    }
+
+    fn update_file(&mut self, scope: &SearchScope, path: &Path) {
+        if !self.is_indexable(path) {
+            self.remove(path);
+            return;
+        }
+        match scan_file(path, scope.file_types().forced_kind(path)) {
+            Ok(Scanned::NotText) | Err(_) => self.remove(path),
+            Ok(scanned) => self.insert(scanned),
+        }
+    }
+
+    /// Whether the walk would have visited `path`, for paths reported by the watcher.
+    fn is_indexable(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        let mut dirs = relative.parent().into_iter().flat_map(|p| p.components());
+        if dirs.any(|c| c.as_os_str().to_str().is_some_and(is_ignored_dir_name)) {
+            return false;
+        }
+        !self.ignore_rules.is_ignored(path)
+    }
+
+    /// Drop removed files for good once they make up a quarter of the index.
+    fn compact(&mut self) {
+        if self.dead * 4 < self.files.len() {
+            return;
+        }
+
+        let mut remap = vec![u32::MAX; self.files.len()];
+        let mut files = Vec::with_capacity(self.files.len() - self.dead);
+        for (old, file) in self.files.drain(..).enumerate() {
+            if let Some(file) = file {
+                remap[old] = files.len() as u32;
+                files.push(Some(file));
+            }
+        }
+        self.postings.retain(|_, ids| {
+            // Ids are remapped in order, so the lists stay sorted
+            ids.retain_mut(|id| {
+                *id = remap[*id as usize];
+                *id != u32::MAX
+            });
+            ids.shrink_to_fit();
+            !ids.is_empty()
+        });
+        self.ids = files.iter().flatten()
+            .enumerate()
+            .map(|(id, file)| (file.path.clone(), id as u32))
+            .collect();
+        self.files = files;
+        self.dead = 0;
+    }
+
+    /// Sorted ids of files that may match; `None` means every file.
+    fn evaluate(&self, query: &TrigramQuery) -> Option<Vec<u32>> {
+        match query {
+            TrigramQuery::All => None,
+            TrigramQuery::Trigrams(trigrams) => {
+                let mut lists: Vec<&Vec<u32>> = Vec::with_capacity(trigrams.len());
+                for trigram in trigrams {
+                    match self.postings.get(trigram) {
+                        Some(ids) => lists.push(ids),
+                        None => return Some(Vec::new()),
+                    }
+                }
+                // Start from the rarest trigram to keep intermediate results small
+                lists.sort_by_key(|ids| ids.len());
+                let (first, rest) = lists.split_first()?;
+                Some(rest.iter().fold(first.to_vec(), |acc, ids| intersect(&acc, ids)))
+            }
+            TrigramQuery::And(parts) => parts.iter()
+                .filter_map(|part| self.evaluate(part))
+                .reduce(|acc, ids| intersect(&acc, &ids)),
+            TrigramQuery::Or(parts) => {
+                let mut acc = Vec::new();
+                for part in parts {
+                    acc = union(&acc, &self.evaluate(part)?);
+                }
+                Some(acc)
+            }
+        }
+    }
+
+    /// Files worth searching for `query`, including files too large to index.
+    fn candidates(&self, query: &TrigramQuery) -> Vec<PathBuf> {
+        let always_scan = self.live_files().filter(|f| f.always_scan).map(|f| f.path.clone());
+        match self.evaluate(query) {
+            None => self.live_files().map(|f| f.path.clone()).collect(),
+            Some(ids) => ids.into_iter()
+                .filter_map(|id| self.files[id as usize].as_ref())
+                .filter(|f| !f.always_scan)
+                .map(|f| f.path.clone())
+                .chain(always_scan)
+                .collect(),
+        }
+    }
+
+    /// Rough heap usage of the posting lists and file table.
+    fn memory_bytes(&self) -> u64 {
+        let postings: usize = self.postings.values()
+            .map(|ids| ids.capacity() * 4 + std::mem::size_of::<(Trigram, Vec<u32>)>())
+            .sum();
+        let files: usize = self.live_files()
+            .map(|f| f.path.as_os_str().len() * 2 + std::mem::size_of::<Option<IndexedFile>>() + std::mem::size_of::<(PathBuf, u32)>())
+            .sum();
+        (postings + files + self.dead * std::mem::size_of::<Option<IndexedFile>>()) as u64
+    }
+
+    fn save(&mut self, path: &Path) -> io::Result<u64> {
+        self.compact();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("idx.tmp");
+        {
+            let mut out = BufWriter::new(File::create(&tmp)?);
+            out.write_all(INDEX_MAGIC)?;
+            write_u32(&mut out, INDEX_VERSION)?;
+            write_str(&mut out, &self.root.to_string_lossy())?;
+
+            // Files whose path is not valid UTF-8 are left out; the catch-up walk
+            // after loading re-indexes them
+            let mut written = vec![u32::MAX; self.files.len()];
+            let storable: Vec<(usize, &IndexedFile, &str)> = self.files.iter()
+                .enumerate()
+                .filter_map(|(id, f)| {
+                    let f = f.as_ref()?;
+                    Some((id, f, f.path.strip_prefix(&self.root).ok()?.to_str()?))
+                })
+                .collect();
+            write_u32(&mut out, storable.len() as u32)?;
+            for (new_id, (id, file, relative)) in storable.iter().enumerate() {
+                written[*id] = new_id as u32;
+                write_str(&mut out, relative)?;
+                out.write_all(&file.mtime.0.to_le_bytes())?;
+                write_u32(&mut out, file.mtime.1)?;
+                out.write_all(&file.size.to_le_bytes())?;
+                out.write_all(&[file.always_scan as u8])?;
+            }
+
+            write_u32(&mut out, self.postings.len() as u32)?;
+            for (trigram, ids) in &self.postings {
+                let ids: Vec<u32> = ids.iter().map(|id| written[*id as usize]).filter(|id| *id != u32::MAX).collect();
+                write_u32(&mut out, *trigram)?;
+                write_u32(&mut out, ids.len() as u32)?;
+                for id in ids {
+                    write_u32(&mut out, id)?;
+                }
+            }
+            write_u32(&mut out, self.ignore_files.len() as u32)?;
+            for file in &self.ignore_files {
+                write_str(&mut out, &file.to_string_lossy())?;
+            }
+            out.flush()?;
+        }
+        fs::rename(&tmp, path)?;
+        Ok(fs::metadata(path)?.len())
+    }
+
+    fn load(path: &Path, root: &Path) -> io::Result<Self> {
+        let mut input = BufReader::new(File::open(path)?);
+        let mut magic = [0u8; 4];
+        input.read_exact(&mut magic)?;
+        if &magic != INDEX_MAGIC || read_u32(&mut input)? != INDEX_VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "unknown index format"));
+        }
+        if Path::new(&read_str(&mut input)?) != root {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "index belongs to another workspace"));
+        }
+
+        let mut index = TrigramIndex::empty(root);
+        for id in 0..read_u32(&mut input)? {
+            let path = root.join(read_str(&mut input)?);
+            let mut secs = [0u8; 8];
+            input.read_exact(&mut secs)?;
+            let nanos = read_u32(&mut input)?;
+            let mut size = [0u8; 8];
+            input.read_exact(&mut size)?;
+            let mut always_scan = [0u8; 1];
+            input.read_exact(&mut always_scan)?;
+            index.ids.insert(path.clone(), id);
+            index.files.push(Some(IndexedFile {
+                path,
+                mtime: (u64::from_le_bytes(secs), nanos),
+                size: u64::from_le_bytes(size),
+                always_scan: always_scan[0] != 0,
+            }));
+        }
+        for _ in 0..read_u32(&mut input)? {
+            let trigram = read_u32(&mut input)?;
+            let count = read_u32(&mut input)? as usize;
+            let mut ids = Vec::with_capacity(count);
+            for _ in 0..count {
+                let id = read_u32(&mut input)?;
+                if id as usize >= index.files.len() {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "corrupt posting list"));
+                }
+                ids.push(id);
+            }
+            index.postings.insert(trigram, ids);
+        }
+        for _ in 0..read_u32(&mut input)? {
+            index.ignore_files.push(PathBuf::from(read_str(&mut input)?));
+        }
+        index.ignore_rules = IgnoreRules::new(&index.ignore_files);
+        Ok(index)
+    }
+}
+
+fn write_u32(out: &mut impl Write, value: u32) -> io::Result<()> {
+    out.write_all(&value.to_le_bytes())
+}
+
+fn write_str(out: &mut impl Write, value: &str) -> io::Result<()> {
+    write_u32(out, value.len() as u32)?;
+    out.write_all(value.as_bytes())
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_str(input: &mut impl Read) -> io::Result<String> {
+    let len = read_u32(input)? as usize;
+    let mut bytes = vec![0u8; len];
+    input.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IndexState {
+    Building,
+    Ready,
+    /// The watcher lost events; queries scan the workspace until a rebuild finishes.
+    Stale,
+    Disabled,
+}
+
+/// Reported by `get_search_index_status`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexStatus {
+    pub state: IndexState,
+    pub files_indexed: usize,
+    pub files_always_scanned: usize,
+    pub trigrams: usize,
+    pub pending_changes: usize,
+    pub memory_bytes: u64,
+    pub disk_bytes: u64,
+    pub persisted: bool,
+    pub last_built_ms: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+impl IndexStatus {
+    pub fn disabled() -> Self {
+        IndexStatus {
+            state: IndexState::Disabled,
+            files_indexed: 0,
+            files_always_scanned: 0,
+            trigrams: 0,
+            pending_changes: 0,
+            memory_bytes: 0,
+            disk_bytes: 0,
+            persisted: false,
+            last_built_ms: None,
+            last_error: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Bookkeeping {
+    state: Option<IndexState>,
+    disk_bytes: u64,
+    last_built: Option<SystemTime>,
+    last_saved: Option<Instant>,
+    last_error: Option<String>,
+}
+
+/// A live index: the posting lists, the watcher feeding them, and the files
+/// changed since the last update.
+pub struct IndexHandle {
+    scope: SearchScope,
+    index_file: Option<PathBuf>,
+    index: RwLock<Option<TrigramIndex>>,
+    pending: Mutex<HashSet<PathBuf>>,
+    book: Mutex<Bookkeeping>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    rebuilding: AtomicBool,
+    rescan: AtomicBool,
+    shutdown: AtomicBool,
+}
+
+impl IndexHandle {
+    /// Start indexing `root` in the background. With `index_file` the index is
+    /// loaded from there if possible and written back after changes.
+    pub fn start(root: &Path, file_types: FileTypes, index_file: Option<PathBuf>) -> io::Result<Arc<Self>> {
+        let scope = SearchScope::new(root, &SearchFilters::default(), file_types)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.message))?;
+        let handle = Arc::new(IndexHandle {
+            scope,
+            index_file,
+            index: RwLock::new(None),
+            pending: Mutex::new(HashSet::new()),
+            book: Mutex::new(Bookkeeping { state: Some(IndexState::Building), ..Default::default() }),
+            watcher: Mutex::new(None),
+            rebuilding: AtomicBool::new(false),
+            rescan: AtomicBool::new(false),
+            shutdown: AtomicBool::new(false),
+        });
+
+        // Watch before walking so changes made during the build are not lost
+        let weak = Arc::downgrade(&handle);
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Some(handle) = weak.upgrade() {
+                handle.on_watch_event(event);
+            }
+        })
+        .map_err(|e| io::Error::other(e.to_string()))?;
+        watcher.watch(root, RecursiveMode::Recursive)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        if let Ok(mut slot) = handle.watcher.lock() {
+            *slot = Some(watcher);
+        }
+
+        // A persisted index only needs to catch up with what changed since it was saved
+        let loaded = handle.index_file.as_deref().and_then(|file| TrigramIndex::load(file, root).ok());
+        if let Ok(mut slot) = handle.index.write() {
+            *slot = loaded;
+        }
+        handle.rebuild();
+
+        let updater = Arc::downgrade(&handle);
+        std::thread::spawn(move || Self::run_updates(updater));
+        Ok(handle)
+    }
+
+    pub fn stop(&self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        if let Ok(mut watcher) = self.watcher.lock() {
+            watcher.take();
+        }
+    }
+
+    pub fn index_file(&self) -> Option<&Path> {
+        self.index_file.as_deref()
+    }
+
+    fn state(&self) -> IndexState {
+        self.book.lock().ok().and_then(|book| book.state).unwrap_or(IndexState::Stale)
+    }
+
+    fn on_watch_event(self: &Arc<Self>, event: notify::Result<notify::Event>) {
+        match event {
+            Ok(event) if event.need_rescan() => self.mark_stale("File watcher dropped events".to_string()),
+            Ok(event) if event.kind.is_access() => {}
+            Ok(event) => {
+                // Ignore rules changed: which files belong in the index changed too
+                if event.paths.iter().any(|p| is_ignore_file(p)) {
+                    self.mark_stale("Ignore files changed".to_string());
+                    return;
+                }
+                if let Ok(mut pending) = self.pending.lock() {
+                    pending.extend(event.paths);
+                }
+            }
+            Err(e) => self.mark_stale(format!("File watcher error: {}", e)),
+        }
+    }
+
+    fn mark_stale(self: &Arc<Self>, reason: String) {
+        // Requested before the state changes, so a finishing pass cannot see the
+        // index as stale without also seeing the request
+        self.rescan.store(true, Ordering::Release);
+        if let Ok(mut book) = self.book.lock() {
+            book.state = Some(IndexState::Stale);
+            book.last_error = Some(reason);
+        }
+        self.rebuild();
+    }
+
+    /// Refresh the current index (or build one from scratch) on a background thread.
+    /// Requests that arrive while a refresh runs are folded into one more pass.
+    fn rebuild(self: &Arc<Self>) {
+        if self.rebuilding.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let handle = self.clone();
+        std::thread::spawn(move || {
+            loop {
+                handle.rescan.store(false, Ordering::Release);
+                // Taken out of the slot so queries fall back to a full scan meanwhile
+                let base = handle.index.write().ok().and_then(|mut slot| slot.take());
+                let mut index = base.unwrap_or_else(|| TrigramIndex::empty(handle.scope.root()));
+                index.refresh(&handle.scope, &handle.shutdown);
+                if let Ok(mut slot) = handle.index.write() {
+                    *slot = Some(index);
+                }
+                if !handle.rescan.load(Ordering::Acquire) || handle.shutdown.load(Ordering::Relaxed) {
+                    break;
+                }
+            }
+            if let Ok(mut book) = handle.book.lock() {
+                // Not ready over a request made since the last pass
+                if !handle.rescan.load(Ordering::Acquire) {
+                    book.state = Some(IndexState::Ready);
+                }
+                book.last_built = Some(SystemTime::now());
+            }
+            handle.rebuilding.store(false, Ordering::Release);
+            handle.save();
+            // A request that came in after the last pass but before `rebuilding` was cleared
+            if handle.rescan.load(Ordering::Acquire) && !handle.shutdown.load(Ordering::Relaxed) {
+                handle.rebuild();
+            }
+        });
+    }
+
+    fn run_updates(handle: Weak<Self>) {
+        loop {
+            std::thread::sleep(UPDATE_INTERVAL);
+            let Some(handle) = handle.upgrade() else {
+                return;
+            };
+            if handle.shutdown.load(Ordering::Relaxed) {
+                return;
+            }
+            if handle.apply_pending() {
+                let due = handle.book.lock().ok()
+                    .is_some_and(|book| book.last_saved.is_none_or(|saved| saved.elapsed() >= SAVE_INTERVAL));
+                if due {
+                    handle.save();
+                }
+            }
+        }
+    }
+
+    /// Fold queued changes into the index. Holding the write lock while draining
+    /// means a query sees each change either as pending or as applied.
+    fn apply_pending(&self) -> bool {
+        let Ok(mut slot) = self.index.write() else {
+            return false;
+        };
+        let Some(index) = slot.as_mut() else {
+            return false;
+        };
+        let changed: Vec<PathBuf> = match self.pending.lock() {
+            Ok(mut pending) => pending.drain().collect(),
+            Err(_) => return false,
+        };
+        for path in &changed {
+            index.update_path(&self.scope, path);
+        }
+        index.compact();
+        !changed.is_empty()
+    }
+
+    fn save(&self) {
+        let Some(file) = &self.index_file else {
+            return;
+        };
+        let result = match self.index.write() {
+            Ok(mut slot) => match slot.as_mut() {
+                Some(index) => index.save(file),
+                None => return,
+            },
+            Err(_) => return,
+        };
+        if let Ok(mut book) = self.book.lock() {
+            book.last_saved = Some(Instant::now());
+            match result {
+                Ok(bytes) => book.disk_bytes = bytes,
+                Err(e) => book.last_error = Some(format!("Failed to save index: {}", e)),
+            }
+        }
+    }
+
+    /// Files to search for a query, or `None` when the index cannot be trusted
+    /// right now (building, stale, or too far behind) and a full scan is needed.
+    pub fn candidates(&self, query: &TrigramQuery) -> Option<Vec<PathBuf>> {
+        if self.state() != IndexState::Ready {
+            return None;
+        }
+        let slot = self.index.read().ok()?;
+        let index = slot.as_ref()?;
+        let pending = self.pending.lock().ok()?;
+        if pending.len() > MAX_PENDING_CHANGES {
+            return None;
+        }
+
+        let mut candidates = index.candidates(query);
+        // Changed since the last update: search them directly, whatever they contain
+        let known: HashSet<&Path> = candidates.iter().map(PathBuf::as_path).collect();
+        let extra: Vec<PathBuf> = pending.iter()
+            .filter(|p| !known.contains(p.as_path()) && p.is_file() && index.is_indexable(p))
+            .cloned()
+            .collect();
+        candidates.extend(extra);
+        Some(candidates)
+    }
+
+    pub fn status(&self) -> IndexStatus {
+        let mut status = IndexStatus::disabled();
+        status.persisted = self.index_file.is_some();
+        if let Ok(book) = self.book.lock() {
+            status.state = book.state.unwrap_or(IndexState::Stale);
+            status.disk_bytes = book.disk_bytes;
+            status.last_error = book.last_error.clone();
+            status.last_built_ms = book.last_built
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64);
+        }
+        if let Ok(pending) = self.pending.lock() {
+            status.pending_changes = pending.len();
+        }
+        if let Ok(slot) = self.index.read() {
+            if let Some(index) = slot.as_ref() {
+                status.files_indexed = index.ids.len();
+                status.files_always_scanned = index.live_files().filter(|f| f.always_scan).count();
+                status.trigrams = index.postings.len();
+                status.memory_bytes = index.memory_bytes();
+            }
+        }
+        status
+    }
+}
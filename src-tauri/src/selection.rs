@@ -0,0 +1,74 @@
+//! Expand and shrink selection by syntax node: the ranges of the nodes that
+//! enclose a position, innermost first. Inside embedded code the chain starts
+//! with the embedded language's nodes and continues with the document's.
+
+use tree_sitter::{Node, Point, Tree};
+
+use crate::documents::{self, Documents};
+use crate::error::CmdResult;
+use crate::injections;
+use crate::queries::{Queries, QueryCache};
+use crate::{node_to_range, Range};
+
+/// The enclosing node ranges at `point`, each strictly larger than the one before.
+pub fn selection_ranges(tree: &Tree, source: &str, language_id: &str, queries: &Queries, point: Point) -> Vec<Range> {
+    let offset = byte_at(source, point);
+    let mut chain: Vec<Node> = Vec::new();
+
+    let around = offset.saturating_sub(1)..(offset + 1).min(source.len());
+    let layers = injections::parse_layers(tree, source, language_id, queries, Some(around));
+    // Nested layers come after the layers that hold them, so innermost first is in reverse
+    for layer in layers.iter().rev() {
+        let Some(included) = layer.tree.included_ranges().into_iter()
+            .find(|range| range.start_point <= point && point <= range.end_point) else { continue };
+        // A combined injection's tree spans all of its ranges; only the part in this one belongs in the chain
+        let nodes = enclosing(layer.tree.root_node(), point)
+            .filter(|node| node.start_byte() >= included.start_byte && node.end_byte() <= included.end_byte);
+        extend(&mut chain, nodes);
+    }
+    extend(&mut chain, enclosing(tree.root_node(), point));
+
+    chain.into_iter().map(node_to_range).collect()
+}
+
+/// The node at `point` and its ancestors.
+fn enclosing(root: Node, point: Point) -> impl Iterator<Item = Node> {
+    std::iter::successors(root.descendant_for_point_range(point, point), |node| node.parent())
+}
+
+/// Append the nodes that strictly enclose the last one in the chain.
+fn extend<'tree>(chain: &mut Vec<Node<'tree>>, nodes: impl Iterator<Item = Node<'tree>>) {
+    for node in nodes {
+        let grows = chain.last().is_none_or(|last| {
+            node.start_byte() <= last.start_byte() && node.end_byte() >= last.end_byte() && node.byte_range() != last.byte_range()
+        });
+        if grows {
+            chain.push(node);
+        }
+    }
+}
+
+/// Byte offset of a tree-sitter point, clamped to the text.
+fn byte_at(source: &str, point: Point) -> usize {
+    let line_start = source.split_inclusive('\n').take(point.row).map(str::len).sum::<usize>();
+    let line_end = source[line_start..].find('\n').map_or(source.len(), |end| line_start + end);
+    (line_start + point.column).min(line_end)
+}
+
+/// Selection ranges at a position of an open document (1-based, in the same
+/// coordinates as the outline's ranges), innermost first.
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_selection_ranges(
+    file_path: String,
+    line_number: u32,
+    column: u32,
+    documents: tauri::State<'_, Documents>,
+    query_cache: tauri::State<'_, QueryCache>,
+    app_handle: tauri::AppHandle,
+) -> CmdResult<Vec<Range>> {
+    let queries = Queries::new(&query_cache, &app_handle);
+    let point = Point::new(line_number.saturating_sub(1) as usize, column.saturating_sub(1) as usize);
+    documents::with_document(&documents, &file_path, |document| {
+        selection_ranges(document.tree(), document.source(), document.language_id(), &queries, point)
+    })
+}
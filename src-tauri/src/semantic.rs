@@ -0,0 +1,1009 @@
+//! Semantic code search: "where do we retry HTTP calls" instead of a keyword.
+//! Source files are cut into chunks along tree-sitter definitions, each chunk is
+//! embedded through the configured endpoint (Ollama or OpenAI-compatible), and
+//! queries are answered by nearest neighbour over the stored vectors.
+//!
+//! Vectors are persisted per workspace under the app data directory. Files are
+//! re-chunked when their size or modification time changes, and only chunks whose
+//! text changed are sent to the embedding endpoint again.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tree_sitter::Parser;
+
+use crate::error::{AppError, CmdResult, ErrorCode};
+use crate::grammars::Grammars;
+use crate::languages::{self, FileTypes};
+use crate::search::{self, SearchFilters, SearchScope};
+use crate::Range;
+
+mod chunks;
+mod provider;
+mod store;
+
+use chunks::Chunk;
+use provider::{EmbeddingClient, EmbeddingSettings};
+use store::{FileEntry, StoredChunk, VectorStore};
+
+/// Larger files are almost always generated or minified.
+const MAX_INDEXED_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Languages that are data or configuration rather than code. Dotenv and ini
+/// files often hold credentials, which must never reach a remote embedding endpoint.
+const SKIPPED_LANGUAGES: &[&str] = &["json", "jsonc", "csv", "plaintext", "ignore", "diff", "dotenv", "ini", "xml"];
+
+/// File names (matched case-insensitively) that likely hold secrets. These are
+/// never embedded, whatever language they resolve to.
+const SECRET_FILE_GLOBS: &[&str] = &[".npmrc", ".env*", "*.pem", "*.key", "id_*", "*secret*", "*credential*"];
+
+/// How often watcher events are folded into the index.
+const UPDATE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// More queued changes than this (a branch switch, a large generator run) are
+/// cheaper to pick up with one walk of the workspace than file by file.
+const MAX_PENDING_CHANGES: usize = 5_000;
+
+/// Minimum time between writes of the persisted vectors.
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+const DEFAULT_MAX_RESULTS: usize = 20;
+
+/// Lines of each match shown in the results list.
+const PREVIEW_LINES: usize = 6;
+
+/// A chunk returned by `semantic_search`, closest first.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticMatch {
+    pub path: String,
+    pub relative_path: String,
+    /// Definition the chunk covers; `None` for plain line windows.
+    pub name: Option<String>,
+    pub range: Range,
+    /// Cosine similarity to the query.
+    pub score: f32,
+    pub preview: String,
+}
+
+/// Reported by `get_semantic_index_status`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticIndexStatus {
+    pub running: bool,
+    pub building: bool,
+    pub model: Option<String>,
+    pub files_indexed: usize,
+    pub chunks: usize,
+    /// Chunks sent to the embedding endpoint since the index was started.
+    pub chunks_embedded: usize,
+    pub last_built_ms: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+pub type SemanticIndexes = Arc<Mutex<HashMap<PathBuf, Arc<SemanticIndex>>>>;
+
+/// The `semanticSearch` section of settings.json; defaults to a local Ollama server.
+pub fn load_settings(app_handle: &AppHandle) -> EmbeddingSettings {
+    app_handle.path().app_config_dir().ok()
+        .and_then(|dir| fs::read_to_string(dir.join("settings.json")).ok())
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|settings| settings.get("semanticSearch").cloned())
+        .and_then(|section| serde_json::from_value::<EmbeddingSettings>(section).ok())
+        .unwrap_or_default()
+}
+
+fn secret_file_globs() -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in SECRET_FILE_GLOBS {
+        builder.add(GlobBuilder::new(pattern).case_insensitive(true).build().expect("valid secret file glob"));
+    }
+    builder.build().expect("valid secret file globs")
+}
+
+/// What is sent to the endpoint for a chunk. The path gives the model context
+/// that the chunk text alone often lacks.
+fn embedding_input(relative_path: &str, chunk: &Chunk) -> String {
+    let mut end = chunk.text.len().min(chunks::MAX_CHUNK_BYTES);
+    while !chunk.text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}\n{}", relative_path, &chunk.text[..end])
+}
+
+#[derive(Default)]
+struct Bookkeeping {
+    last_built: Option<SystemTime>,
+    last_saved: Option<Instant>,
+    unsaved: bool,
+    embedded: usize,
+    last_error: Option<String>,
+}
+
+/// A live semantic index: the vectors per file, the watcher feeding it, and the
+/// background threads that build and update it.
+pub struct SemanticIndex {
+    scope: SearchScope,
+    file_types: FileTypes,
+    secret_files: GlobSet,
+    grammars: Grammars,
+    client: EmbeddingClient,
+    store: Option<PathBuf>,
+    vectors: RwLock<VectorStore>,
+    book: Mutex<Bookkeeping>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    building: AtomicBool,
+    rescan: AtomicBool,
+    /// Paths reported by the watcher or saved since the last update, re-embedded one by one.
+    pending: Mutex<HashSet<PathBuf>>,
+    /// The watcher lost track (dropped events, errors): walk the whole workspace.
+    stale: AtomicBool,
+    shutdown: AtomicBool,
+}
+
+impl SemanticIndex {
+    /// An empty index over `root`; nothing runs until [`SemanticIndex::refresh`]
+    /// or [`SemanticIndex::start`]. With `store` vectors are loaded from there
+    /// and written back after changes.
+    pub fn new(root: &Path, file_types: FileTypes, settings: EmbeddingSettings, store: Option<PathBuf>) -> CmdResult<Arc<Self>> {
+        let scope = SearchScope::new(root, &SearchFilters::default(), file_types.clone())?;
+        let client = EmbeddingClient::new(settings)?;
+        Ok(Arc::new(SemanticIndex {
+            scope,
+            file_types,
+            secret_files: secret_file_globs(),
+            grammars: Grammars::load(),
+            vectors: RwLock::new(VectorStore::new(client.model())),
+            client,
+            store,
+            book: Mutex::new(Bookkeeping::default()),
+            watcher: Mutex::new(None),
+            building: AtomicBool::new(false),
+            rescan: AtomicBool::new(false),
+            pending: Mutex::new(HashSet::new()),
+            stale: AtomicBool::new(false),
+            shutdown: AtomicBool::new(false),
+        }))
+    }
+
+    /// Start indexing `root` in the background and keep following changes on disk.
+    pub fn start(root: &Path, file_types: FileTypes, settings: EmbeddingSettings, store: Option<PathBuf>) -> CmdResult<Arc<Self>> {
+        let handle = Self::new(root, file_types, settings, store)?;
+        let weak = Arc::downgrade(&handle);
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Some(handle) = weak.upgrade() {
+                handle.on_watch_event(event);
+            }
+        })
+        .map_err(|e| AppError::internal(format!("Failed to watch workspace: {}", e)))?;
+        watcher.watch(root, RecursiveMode::Recursive)
+            .map_err(|e| AppError::internal(format!("Failed to watch workspace: {}", e)).with_path(root))?;
+        if let Ok(mut slot) = handle.watcher.lock() {
+            *slot = Some(watcher);
+        }
+
+        handle.rebuild();
+        let updater = Arc::downgrade(&handle);
+        std::thread::spawn(move || Self::run_updates(updater));
+        Ok(handle)
+    }
+
+    pub fn stop(&self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        if let Ok(mut watcher) = self.watcher.lock() {
+            watcher.take();
+        }
+        self.save(true);
+    }
+
+    fn on_watch_event(&self, event: notify::Result<notify::Event>) {
+        match event {
+            Ok(event) if event.need_rescan() => self.stale.store(true, Ordering::Release),
+            Ok(event) if event.kind.is_access() => {}
+            Ok(event) => {
+                for path in event.paths {
+                    self.queue(path);
+                }
+            }
+            Err(e) => {
+                self.set_error(format!("File watcher error: {}", e));
+                self.stale.store(true, Ordering::Release);
+            }
+        }
+    }
+
+    /// Re-embed `path` on the next update, if it can affect the index.
+    fn queue(&self, path: PathBuf) {
+        if self.is_relevant(&path) {
+            if let Ok(mut pending) = self.pending.lock() {
+                pending.insert(path);
+            }
+        }
+    }
+
+    fn is_relevant(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(self.scope.root()) else {
+            return false;
+        };
+        let mut dirs = relative.parent().into_iter().flat_map(|p| p.components());
+        if dirs.any(|c| c.as_os_str().to_str().is_some_and(search::is_ignored_dir_name)) {
+            return false;
+        }
+        self.is_indexable(path)
+            || self.vectors.read().is_ok_and(|vectors| vectors.files.keys().any(|file| file.starts_with(path)))
+    }
+
+    fn is_indexable(&self, path: &Path) -> bool {
+        let secret = path.file_name().is_some_and(|name| self.secret_files.is_match(name));
+        !secret && self.file_types.language_for_path(path)
+            .is_some_and(|id| !SKIPPED_LANGUAGES.contains(&id.as_str()))
+    }
+
+    /// Bring the index up to date on a background thread. Requests that arrive
+    /// while a pass runs are folded into one more pass.
+    pub fn rebuild(self: &Arc<Self>) {
+        self.rescan.store(true, Ordering::Release);
+        if self.building.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let handle = self.clone();
+        std::thread::spawn(move || {
+            while handle.rescan.swap(false, Ordering::AcqRel) && !handle.shutdown.load(Ordering::Relaxed) {
+                if let Err(e) = handle.refresh() {
+                    // Retried on the next change or start instead of hammering a dead endpoint
+                    handle.set_error(e.message);
+                    handle.rescan.store(false, Ordering::Release);
+                }
+            }
+            handle.building.store(false, Ordering::Release);
+            handle.save(false);
+            if handle.rescan.load(Ordering::Acquire) && !handle.shutdown.load(Ordering::Relaxed) {
+                handle.rebuild();
+            }
+        });
+    }
+
+    /// One synchronous pass: load the persisted vectors if nothing is in memory
+    /// yet, drop files that are gone, and embed what changed. Stops at the first
+    /// failed embedding request; files done so far are kept.
+    pub fn refresh(&self) -> CmdResult<()> {
+        self.load();
+        let seen = Mutex::new(HashSet::new());
+        let stale = Mutex::new(Vec::new());
+        search::for_each_file(&self.scope, &|path, _| {
+            if self.shutdown.load(Ordering::Relaxed) {
+                return false;
+            }
+            if !self.is_indexable(path) {
+                return true;
+            }
+            let unchanged = fs::metadata(path).ok().is_some_and(|metadata| {
+                self.vectors.read().is_ok_and(|vectors| {
+                    vectors.files.get(path).is_some_and(|cached| {
+                        cached.size == metadata.len() && cached.modified == metadata.modified().ok()
+                    })
+                })
+            });
+            if let Ok(mut seen) = seen.lock() {
+                seen.insert(path.to_path_buf());
+            }
+            if !unchanged {
+                if let Ok(mut stale) = stale.lock() {
+                    stale.push(path.to_path_buf());
+                }
+            }
+            true
+        });
+        if self.shutdown.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let seen = seen.into_inner().unwrap_or_default();
+        if let Ok(mut vectors) = self.vectors.write() {
+            let before = vectors.files.len();
+            vectors.files.retain(|path, _| seen.contains(path));
+            if vectors.files.len() != before {
+                self.mark_unsaved();
+            }
+        }
+
+        let mut stale = stale.into_inner().unwrap_or_default();
+        stale.sort();
+        for path in stale {
+            if self.shutdown.load(Ordering::Relaxed) {
+                break;
+            }
+            self.update_file(&path)?;
+        }
+        if let Ok(mut book) = self.book.lock() {
+            book.last_built = Some(SystemTime::now());
+            book.last_error = None;
+        }
+        Ok(())
+    }
+
+    /// Fill an empty index from the persisted copy, if there is one for this model.
+    fn load(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let Ok(mut vectors) = self.vectors.write() else {
+            return;
+        };
+        if vectors.files.is_empty() {
+            if let Some(stored) = VectorStore::load(self.scope.root(), self.client.model(), store) {
+                *vectors = stored;
+            }
+        }
+    }
+
+    fn read_chunks(&self, path: &Path) -> Option<(fs::Metadata, Vec<Chunk>)> {
+        let metadata = fs::metadata(path).ok()?;
+        if !metadata.is_file() || metadata.len() > MAX_INDEXED_FILE_SIZE || !self.is_indexable(path) {
+            return None;
+        }
+        let bytes = fs::read(path).ok()?;
+        if memchr::memchr(0, &bytes[..bytes.len().min(8192)]).is_some() {
+            return None;
+        }
+        let source = String::from_utf8_lossy(&bytes);
+        let tree = self.file_types.language_for_path(path)
+            .and_then(|id| self.grammars.for_language(&id).cloned())
+            .and_then(|language| {
+                let mut parser = Parser::new();
+                parser.set_language(&language).ok()?;
+                parser.parse(source.as_ref(), None)
+            });
+        Some((metadata, chunks::chunk_source(&source, tree.as_ref())))
+    }
+
+    /// Re-chunk one file and embed the chunks whose text is new, or drop the file
+    /// when it is gone or no longer indexable.
+    pub fn update_file(&self, path: &Path) -> CmdResult<()> {
+        let Some((metadata, chunks)) = self.read_chunks(path) else {
+            if let Ok(mut vectors) = self.vectors.write() {
+                if vectors.files.remove(path).is_some() {
+                    self.mark_unsaved();
+                }
+            }
+            return Ok(());
+        };
+
+        let relative = self.scope.relative(path);
+        let inputs: Vec<String> = chunks.iter().map(|chunk| embedding_input(&relative, chunk)).collect();
+        let hashes: Vec<u64> = inputs.iter().map(|input| search::stable_hash(input.as_bytes())).collect();
+        let mut known: HashMap<u64, Vec<f32>> = self.vectors.read()
+            .map_err(|_| AppError::lock_poisoned("semantic index"))?
+            .files.get(path)
+            .map(|file| file.chunks.iter().map(|chunk| (chunk.hash, chunk.vector.clone())).collect())
+            .unwrap_or_default();
+
+        let mut missing: Vec<usize> = Vec::new();
+        let mut queued = HashSet::new();
+        for (i, hash) in hashes.iter().enumerate() {
+            if !known.contains_key(hash) && queued.insert(*hash) {
+                missing.push(i);
+            }
+        }
+        for batch in missing.chunks(self.client.batch_size()) {
+            let batch_inputs: Vec<String> = batch.iter().map(|&i| inputs[i].clone()).collect();
+            let embedded = self.client.embed(&batch_inputs)?;
+            for (&i, vector) in batch.iter().zip(embedded) {
+                known.insert(hashes[i], store::normalize(vector));
+            }
+            if let Ok(mut book) = self.book.lock() {
+                book.embedded += batch.len();
+            }
+        }
+
+        let mut vectors = self.vectors.write().map_err(|_| AppError::lock_poisoned("semantic index"))?;
+        let dimensions = known.values().next().map(Vec::len);
+        if let Some(dimensions) = dimensions {
+            if known.values().any(|vector| vector.len() != dimensions) {
+                return Err(AppError::new(ErrorCode::Network, "Embedding server returned vectors of different sizes"));
+            }
+            if vectors.dimensions == 0 || vectors.files.is_empty() {
+                vectors.dimensions = dimensions;
+            } else if vectors.dimensions != dimensions {
+                return Err(AppError::new(
+                    ErrorCode::Conflict,
+                    format!(
+                        "Model {} returned {}-dimensional vectors but the index has {}; delete the index to rebuild it",
+                        self.client.model(), dimensions, vectors.dimensions
+                    ),
+                ));
+            }
+        }
+        let chunks = chunks.into_iter().zip(hashes)
+            .filter_map(|(chunk, hash)| {
+                Some(StoredChunk { hash, name: chunk.name, range: chunk.range, vector: known.get(&hash)?.clone() })
+            })
+            .collect();
+        vectors.files.insert(path.to_path_buf(), FileEntry {
+            modified: metadata.modified().ok(),
+            size: metadata.len(),
+            chunks,
+        });
+        drop(vectors);
+        self.mark_unsaved();
+        Ok(())
+    }
+
+    /// Bring one queued path up to date: a file is re-embedded, a directory that
+    /// appeared is walked, and whatever no longer exists is dropped along with
+    /// everything below it.
+    fn update_path(&self, path: &Path) -> CmdResult<()> {
+        if path.is_dir() {
+            let files = Mutex::new(Vec::new());
+            search::for_each_file(&self.scope.clone().with_walk_root(path), &|file, _| {
+                if self.is_indexable(file) {
+                    if let Ok(mut files) = files.lock() {
+                        files.push(file.to_path_buf());
+                    }
+                }
+                true
+            });
+            for file in files.into_inner().unwrap_or_default() {
+                self.update_file(&file)?;
+            }
+        } else if path.is_file() {
+            self.update_file(path)?;
+        } else if let Ok(mut vectors) = self.vectors.write() {
+            let before = vectors.files.len();
+            vectors.files.retain(|file, _| !file.starts_with(path));
+            if vectors.files.len() != before {
+                self.mark_unsaved();
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-embed the queued paths, or fall back to a full pass when the watcher
+    /// lost track or too much changed at once.
+    fn apply_pending(self: &Arc<Self>) {
+        let changed: Vec<PathBuf> = match self.pending.lock() {
+            Ok(mut pending) => pending.drain().collect(),
+            Err(_) => return,
+        };
+        if self.stale.swap(false, Ordering::AcqRel) || changed.len() > MAX_PENDING_CHANGES {
+            self.rebuild();
+            return;
+        }
+        for path in &changed {
+            if self.shutdown.load(Ordering::Relaxed) {
+                return;
+            }
+            if let Err(e) = self.update_path(path) {
+                // Retried on the next change or start instead of hammering a dead endpoint
+                self.set_error(e.message);
+                return;
+            }
+        }
+    }
+
+    fn mark_unsaved(&self) {
+        if let Ok(mut book) = self.book.lock() {
+            book.unsaved = true;
+        }
+    }
+
+    fn set_error(&self, message: String) {
+        eprintln!("[SEMANTIC DEBUG] {}", message);
+        if let Ok(mut book) = self.book.lock() {
+            book.last_error = Some(message);
+        }
+    }
+
+    fn run_updates(handle: Weak<Self>) {
+        loop {
+            std::thread::sleep(UPDATE_INTERVAL);
+            let Some(handle) = handle.upgrade() else {
+                return;
+            };
+            if handle.shutdown.load(Ordering::Relaxed) {
+                return;
+            }
+            // A full pass picks up queued changes by their modification times
+            if !handle.building.load(Ordering::Acquire) {
+                handle.apply_pending();
+                handle.save(false);
+            }
+        }
+    }
+
+    /// Write the vectors out if they changed, at most every `SAVE_INTERVAL` unless `force`.
+    fn save(&self, force: bool) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let due = self.book.lock().is_ok_and(|book| {
+            book.unsaved && (force || book.last_saved.is_none_or(|saved| saved.elapsed() >= SAVE_INTERVAL))
+        });
+        if !due {
+            return;
+        }
+        let result = match self.vectors.read() {
+            Ok(vectors) => vectors.save(self.scope.root(), store),
+            Err(_) => return,
+        };
+        if let Ok(mut book) = self.book.lock() {
+            book.last_saved = Some(Instant::now());
+            match result {
+                Ok(()) => book.unsaved = false,
+                Err(e) => book.last_error = Some(format!("Failed to save semantic index: {}", e)),
+            }
+        }
+    }
+
+    /// Remove the persisted vectors; used when the index is discarded for good.
+    pub fn delete_store(&self) -> io::Result<()> {
+        match &self.store {
+            Some(store) if store.exists() => fs::remove_file(store),
+            _ => Ok(()),
+        }
+    }
+
+    /// The `max_results` chunks closest in meaning to `query`. Blocks on the
+    /// embedding endpoint.
+    pub fn search(&self, query: &str, max_results: usize) -> CmdResult<Vec<SemanticMatch>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        if self.vectors.read().is_ok_and(|vectors| vectors.files.is_empty()) {
+            return Ok(Vec::new());
+        }
+        let vector = self.client.embed(&[query.to_string()])?
+            .pop()
+            .map(store::normalize)
+            .unwrap_or_default();
+
+        let vectors = self.vectors.read().map_err(|_| AppError::lock_poisoned("semantic index"))?;
+        let nearest = vectors.nearest(&vector, max_results);
+        let mut sources: HashMap<&Path, Vec<String>> = HashMap::new();
+        let matches = nearest.into_iter()
+            .map(|(score, path, chunk)| {
+                let lines = sources.entry(path).or_insert_with(|| {
+                    fs::read(path)
+                        .map(|bytes| String::from_utf8_lossy(&bytes).lines().map(str::to_string).collect())
+                        .unwrap_or_default()
+                });
+                let start = chunk.range.start_line_number.saturating_sub(1) as usize;
+                let end = (chunk.range.end_line_number as usize).min(start + PREVIEW_LINES).min(lines.len());
+                SemanticMatch {
+                    path: path.to_string_lossy().into_owned(),
+                    relative_path: self.scope.relative(path),
+                    name: chunk.name.clone(),
+                    range: chunk.range.clone(),
+                    score,
+                    preview: lines.get(start..end).map(|lines| lines.join("\n")).unwrap_or_default(),
+                }
+            })
+            .collect();
+        Ok(matches)
+    }
+
+    pub fn status(&self) -> SemanticIndexStatus {
+        let mut status = SemanticIndexStatus::stopped();
+        status.running = true;
+        status.building = self.building.load(Ordering::Acquire);
+        status.model = Some(self.client.model().to_string());
+        if let Ok(vectors) = self.vectors.read() {
+            status.files_indexed = vectors.files.len();
+            status.chunks = vectors.chunk_count();
+        }
+        if let Ok(book) = self.book.lock() {
+            status.chunks_embedded = book.embedded;
+            status.last_error = book.last_error.clone();
+            status.last_built_ms = book.last_built
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64);
+        }
+        status
+    }
+}
+
+impl SemanticIndexStatus {
+    pub fn stopped() -> Self {
+        SemanticIndexStatus {
+            running: false,
+            building: false,
+            model: None,
+            files_indexed: 0,
+            chunks: 0,
+            chunks_embedded: 0,
+            last_built_ms: None,
+            last_error: None,
+        }
+    }
+}
+
+fn find_index(indexes: &SemanticIndexes, workspace_path: &str) -> CmdResult<Option<Arc<SemanticIndex>>> {
+    let indexes = indexes.lock().map_err(|_| AppError::lock_poisoned("semantic indexes"))?;
+    Ok(indexes.get(Path::new(workspace_path)).cloned())
+}
+
+/// Start the workspace's semantic index with the endpoint from settings, or catch
+/// a running index up with the files on disk.
+#[tauri::command(rename_all = "snake_case")]
+pub fn start_semantic_index(
+    workspace_path: String,
+    indexes: tauri::State<'_, SemanticIndexes>,
+    app_handle: AppHandle,
+) -> CmdResult<SemanticIndexStatus> {
+    let workspace = search::validate_workspace(&workspace_path)?;
+    let mut indexes = indexes.lock().map_err(|_| AppError::lock_poisoned("semantic indexes"))?;
+    if let Some(handle) = indexes.get(workspace) {
+        handle.rebuild();
+        return Ok(handle.status());
+    }
+
+    let store = search::workspace_data_file(workspace, "semantic-index", "vec", &app_handle)?;
+    let handle = SemanticIndex::start(
+        workspace,
+        languages::load_file_types(&app_handle),
+        load_settings(&app_handle),
+        Some(store),
+    )?;
+    let status = handle.status();
+    indexes.insert(workspace.to_path_buf(), handle);
+    Ok(status)
+}
+
+/// Stop the workspace's semantic index. The vectors are kept for next time unless
+/// `delete_from_disk` is set. Returns `false` when no index was running.
+#[tauri::command(rename_all = "snake_case")]
+pub fn stop_semantic_index(
+    workspace_path: String,
+    delete_from_disk: Option<bool>,
+    indexes: tauri::State<'_, SemanticIndexes>,
+) -> CmdResult<bool> {
+    let mut indexes = indexes.lock().map_err(|_| AppError::lock_poisoned("semantic indexes"))?;
+    let Some(handle) = indexes.remove(Path::new(&workspace_path)) else {
+        return Ok(false);
+    };
+    handle.stop();
+    if delete_from_disk.unwrap_or(false) {
+        handle.delete_store().map_err(|e| AppError::io(e, "Failed to delete semantic index for", &workspace_path))?;
+    }
+    Ok(true)
+}
+
+/// Chunks closest in meaning to `query`. Empty when the workspace has no running
+/// index; fails with `Network` when the embedding endpoint cannot be reached.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn semantic_search(
+    workspace_path: String,
+    query: String,
+    max_results: Option<usize>,
+    indexes: tauri::State<'_, SemanticIndexes>,
+) -> CmdResult<Vec<SemanticMatch>> {
+    let Some(handle) = find_index(&indexes, &workspace_path)? else {
+        return Ok(Vec::new());
+    };
+    let max_results = max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+    tauri::async_runtime::spawn_blocking(move || handle.search(&query, max_results))
+        .await
+        .map_err(|e| AppError::internal(format!("Semantic search failed: {}", e)))?
+}
+
+/// Queue one file that was just saved for re-embedding on the index's next
+/// update, in case the watcher misses it.
+#[tauri::command(rename_all = "snake_case")]
+pub fn update_semantic_index_for_file(
+    workspace_path: String,
+    file_path: String,
+    indexes: tauri::State<'_, SemanticIndexes>,
+) -> CmdResult<()> {
+    let path = Path::new(&file_path);
+    if let Some(handle) = find_index(&indexes, &workspace_path)? {
+        if path.starts_with(&workspace_path) {
+            // The embedding request runs on the index's own thread
+            handle.queue(path.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_semantic_index_status(
+    workspace_path: String,
+    indexes: tauri::State<'_, SemanticIndexes>,
+) -> CmdResult<SemanticIndexStatus> {
+    Ok(find_index(&indexes, &workspace_path)?
+        .map(|handle| handle.status())
+        .unwrap_or_else(SemanticIndexStatus::stopped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use provider::EmbeddingProvider;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::AtomicUsize;
+
+    const DIMENSIONS: usize = 64;
+
+    /// Bag-of-words vectors: texts sharing words end up close together.
+    fn fake_embedding(text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0; DIMENSIONS];
+        for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| w.len() > 2) {
+            vector[(search::stable_hash(word.to_lowercase().as_bytes()) % DIMENSIONS as u64) as usize] += 1.0;
+        }
+        vector
+    }
+
+    /// A local embeddings server speaking both the Ollama and the OpenAI format.
+    struct StubServer {
+        url: String,
+        requests: Arc<AtomicUsize>,
+        inputs: Arc<AtomicUsize>,
+        fail: Arc<AtomicBool>,
+    }
+
+    impl StubServer {
+        fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(AtomicUsize::new(0));
+            let inputs = Arc::new(AtomicUsize::new(0));
+            let fail = Arc::new(AtomicBool::new(false));
+            let (counter, input_counter, failing) = (requests.clone(), inputs.clone(), fail.clone());
+            std::thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let mut reader = BufReader::new(stream);
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    let mut length = 0;
+                    loop {
+                        let mut header = String::new();
+                        reader.read_line(&mut header).unwrap();
+                        if header.trim().is_empty() {
+                            break;
+                        }
+                        if let Some((name, value)) = header.split_once(':') {
+                            if name.eq_ignore_ascii_case("content-length") {
+                                length = value.trim().parse().unwrap();
+                            }
+                        }
+                    }
+                    let mut body = vec![0; length];
+                    reader.read_exact(&mut body).unwrap();
+                    counter.fetch_add(1, Ordering::SeqCst);
+
+                    let (status, response) = if failing.load(Ordering::SeqCst) {
+                        ("500 Internal Server Error", r#"{"error":"model not loaded"}"#.to_string())
+                    } else {
+                        let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                        let texts: Vec<String> = request["input"].as_array().unwrap().iter()
+                            .map(|input| input.as_str().unwrap().to_string())
+                            .collect();
+                        input_counter.fetch_add(texts.len(), Ordering::SeqCst);
+                        let vectors: Vec<Vec<f32>> = texts.iter().map(|text| fake_embedding(text)).collect();
+                        let response = if request_line.contains("/api/embed") {
+                            serde_json::json!({ "embeddings": vectors })
+                        } else {
+                            // Out of order on purpose; clients must sort by index
+                            let data: Vec<_> = vectors.iter().enumerate().rev()
+                                .map(|(index, embedding)| serde_json::json!({ "embedding": embedding, "index": index }))
+                                .collect();
+                            serde_json::json!({ "data": data })
+                        };
+                        ("200 OK", response.to_string())
+                    };
+                    let mut stream = reader.into_inner();
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status, response.len(), response
+                    );
+                }
+            });
+            StubServer { url, requests, inputs, fail }
+        }
+
+        fn settings(&self, provider: EmbeddingProvider) -> EmbeddingSettings {
+            EmbeddingSettings { provider, endpoint: self.url.clone(), batch_size: 4, ..Default::default() }
+        }
+
+        fn inputs(&self) -> usize {
+            self.inputs.load(Ordering::SeqCst)
+        }
+    }
+
+    const HTTP_CLIENT: &str = r#"
+/// Sends a request and retries failed HTTP calls with exponential backoff.
+export async function fetchWithRetry(url, options, attempts = 3) {
+  for (let attempt = 0; attempt < attempts; attempt++) {
+    try {
+      const response = await fetch(url, options);
+      if (response.ok) return response;
+    } catch (error) {
+      await sleep(2 ** attempt * 100);
+    }
+  }
+  throw new Error(`request to ${url} failed after retry attempts`);
+}
+"#;
+
+    const MATH: &str = r#"
+pub fn matrix_determinant(matrix: &[[f64; 2]; 2]) -> f64 {
+    matrix[0][0] * matrix[1][1] - matrix[0][1] * matrix[1][0]
+}
+
+pub fn vector_length(vector: &[f64]) -> f64 {
+    vector.iter().map(|component| component * component).sum::<f64>().sqrt()
+}
+"#;
+
+    fn workspace() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/http.js"), HTTP_CLIENT).unwrap();
+        fs::write(dir.path().join("src/math.rs"), MATH).unwrap();
+        fs::write(dir.path().join(".env"), "API_TOKEN=secret-token-that-must-not-leave-the-machine\n").unwrap();
+        fs::write(dir.path().join(".env.production"), "API_TOKEN=another-secret\n").unwrap();
+        fs::write(dir.path().join(".npmrc"), "//registry.npmjs.org/:_authToken=npm-secret\n").unwrap();
+        fs::write(dir.path().join("deploy.ini"), "[aws]\naccess_key = not-for-embedding\n").unwrap();
+        fs::write(dir.path().join("src/Secrets.js"), "export const apiKey = 'js-secret';\n").unwrap();
+        fs::write(dir.path().join("src/db_credentials.rs"), "pub const PASSWORD: &str = \"rs-secret\";\n").unwrap();
+        dir
+    }
+
+    fn index(server: &StubServer, root: &Path, store: Option<PathBuf>) -> Arc<SemanticIndex> {
+        SemanticIndex::new(root, FileTypes::default(), server.settings(EmbeddingProvider::Ollama), store).unwrap()
+    }
+
+    #[test]
+    fn embeds_through_both_providers() {
+        let server = StubServer::start();
+        let inputs = vec!["retry http calls".to_string(), "matrix determinant".to_string()];
+        for provider in [EmbeddingProvider::Ollama, EmbeddingProvider::OpenAi] {
+            let client = EmbeddingClient::new(server.settings(provider)).unwrap();
+            let vectors = client.embed(&inputs).unwrap();
+            assert_eq!(vectors, vec![fake_embedding(&inputs[0]), fake_embedding(&inputs[1])]);
+        }
+    }
+
+    #[test]
+    fn reports_endpoint_errors() {
+        let server = StubServer::start();
+        server.fail.store(true, Ordering::SeqCst);
+        let client = EmbeddingClient::new(server.settings(EmbeddingProvider::Ollama)).unwrap();
+        let error = client.embed(&["anything".to_string()]).unwrap_err();
+        assert_eq!(error.code, ErrorCode::Network);
+        assert!(error.message.contains("model not loaded"), "{}", error.message);
+
+        let dir = workspace();
+        let index = index(&server, dir.path(), None);
+        assert_eq!(index.refresh().unwrap_err().code, ErrorCode::Network);
+    }
+
+    #[test]
+    fn chunks_along_definitions() {
+        let mut parser = Parser::new();
+        parser.set_language(&tree_sitter_rust::LANGUAGE.into()).unwrap();
+        let tree = parser.parse(MATH, None).unwrap();
+        let chunks = chunks::chunk_source(MATH, Some(&tree));
+        let names: Vec<_> = chunks.iter().map(|chunk| chunk.name.as_deref()).collect();
+        assert_eq!(names, vec![Some("matrix_determinant"), Some("vector_length")]);
+        assert_eq!(chunks[0].range.start_line_number, 2);
+        assert_eq!(chunks[0].range.end_line_number, 4);
+
+        // Comments above a definition belong to it
+        let mut parser = Parser::new();
+        parser.set_language(&tree_sitter_javascript::LANGUAGE.into()).unwrap();
+        let tree = parser.parse(HTTP_CLIENT, None).unwrap();
+        let chunks = chunks::chunk_source(HTTP_CLIENT, Some(&tree));
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].name.as_deref(), Some("fetchWithRetry"));
+        assert!(chunks[0].text.starts_with("/// Sends a request"));
+    }
+
+    #[test]
+    fn finds_code_by_meaning() {
+        let server = StubServer::start();
+        let dir = workspace();
+        let index = index(&server, dir.path(), None);
+        index.refresh().unwrap();
+        assert_eq!(index.status().files_indexed, 2, "files that may hold secrets must be skipped");
+
+        let matches = index.search("where do we retry failed http calls", 5).unwrap();
+        assert_eq!(matches[0].relative_path, "src/http.js");
+        assert_eq!(matches[0].name.as_deref(), Some("fetchWithRetry"));
+        assert_eq!(matches[0].range.start_line_number, 2);
+        assert!(matches[0].preview.contains("fetchWithRetry"));
+        assert!(matches.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    }
+
+    #[test]
+    fn re_embeds_only_changed_chunks() {
+        let server = StubServer::start();
+        let dir = workspace();
+        let index = index(&server, dir.path(), None);
+        index.refresh().unwrap();
+        let initial = server.inputs();
+        assert_eq!(initial, 3);
+
+        // Nothing changed on disk: no requests at all
+        let requests = server.requests.load(Ordering::SeqCst);
+        index.refresh().unwrap();
+        assert_eq!(server.requests.load(Ordering::SeqCst), requests);
+
+        // Editing one function re-embeds just that function
+        let edited = MATH.replace("component * component", "component.powi(2)");
+        fs::write(dir.path().join("src/math.rs"), edited).unwrap();
+        index.update_file(&dir.path().join("src/math.rs")).unwrap();
+        assert_eq!(server.inputs(), initial + 1);
+        assert_eq!(index.status().chunks, 3);
+
+        fs::remove_file(dir.path().join("src/http.js")).unwrap();
+        index.refresh().unwrap();
+        assert_eq!(index.status().files_indexed, 1);
+        assert!(index.search("retry http", 5).unwrap().iter().all(|m| m.relative_path != "src/http.js"));
+    }
+
+    #[test]
+    fn re_embeds_only_queued_files() {
+        let server = StubServer::start();
+        let dir = workspace();
+        let index = index(&server, dir.path(), None);
+        index.refresh().unwrap();
+        let initial = server.inputs();
+
+        // A queued save re-embeds that file alone, without walking the workspace
+        let edited = MATH.replace("component * component", "component.powi(2)");
+        fs::write(dir.path().join("src/math.rs"), edited).unwrap();
+        fs::write(dir.path().join("src/http.js"), HTTP_CLIENT.replace("Sends", "Issues")).unwrap();
+        index.queue(dir.path().join("src/math.rs"));
+        index.queue(dir.path().join(".env"));
+        index.apply_pending();
+        assert_eq!(server.inputs(), initial + 1);
+        assert!(!index.building.load(Ordering::Acquire));
+
+        // A queued path that is gone takes its vectors with it
+        fs::remove_dir_all(dir.path().join("src")).unwrap();
+        index.queue(dir.path().join("src"));
+        index.apply_pending();
+        assert_eq!(index.status().files_indexed, 0);
+    }
+
+    #[test]
+    fn reloads_persisted_vectors() {
+        let server = StubServer::start();
+        let dir = workspace();
+        let store = dir.path().join("data/index.vec");
+        let first = index(&server, dir.path(), Some(store.clone()));
+        first.refresh().unwrap();
+        first.stop();
+        let embedded = server.inputs();
+
+        let second = index(&server, dir.path(), Some(store.clone()));
+        second.refresh().unwrap();
+        assert_eq!(server.inputs(), embedded);
+        assert_eq!(second.status().chunks, 3);
+        assert_eq!(second.search("matrix determinant", 1).unwrap()[0].name.as_deref(), Some("matrix_determinant"));
+
+        // Vectors from another model are not reused
+        let before = server.inputs();
+        let other = SemanticIndex::new(
+            dir.path(),
+            FileTypes::default(),
+            EmbeddingSettings { model: "other-model".to_string(), ..server.settings(EmbeddingProvider::Ollama) },
+            Some(store),
+        )
+        .unwrap();
+        other.refresh().unwrap();
+        assert_eq!(server.inputs(), before + embedded);
+    }
+}
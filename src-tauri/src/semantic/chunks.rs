@@ -0,0 +1,196 @@
+//! Splitting a file into the pieces that get embedded. Functions, classes and
+//! similar definitions become one chunk each, together with the comments right
+//! above them; whatever lies between them is cut into line windows.
+
+use tree_sitter::{Node, Tree};
+
+use crate::Range;
+
+/// Definitions longer than this are split into their members instead.
+pub const MAX_CHUNK_BYTES: usize = 6000;
+
+/// Lines per chunk for code outside definitions and files without a grammar.
+const WINDOW_LINES: usize = 60;
+
+/// Chunks with fewer non-whitespace bytes than this say too little to be worth a vector.
+const MIN_CHUNK_BYTES: usize = 40;
+
+const DEFINITION_KINDS: &[&str] = &[
+    // JavaScript / TypeScript
+    "function_declaration",
+    "generator_function_declaration",
+    "method_definition",
+    "class_declaration",
+    "abstract_class_declaration",
+    "interface_declaration",
+    "enum_declaration",
+    "arrow_function",
+    "function_expression",
+    // Python
+    "function_definition",
+    "class_definition",
+    "decorated_definition",
+    // Rust
+    "function_item",
+    "impl_item",
+    "trait_item",
+    "struct_item",
+    "enum_item",
+    "mod_item",
+    "macro_definition",
+    // Go
+    "method_declaration",
+    "type_declaration",
+];
+
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    /// Name of the definition; `None` for line windows.
+    pub name: Option<String>,
+    pub range: Range,
+    pub text: String,
+}
+
+/// Chunks covering `source`, in file order. Without a tree the whole file is cut
+/// into line windows.
+pub fn chunk_source(source: &str, tree: Option<&Tree>) -> Vec<Chunk> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut definitions = Vec::new();
+    if let Some(tree) = tree {
+        collect_definitions(tree.root_node(), source, &mut definitions);
+    }
+    definitions.sort_by_key(|definition| definition.start);
+
+    let mut chunks = Vec::new();
+    let mut line = 0;
+    for definition in definitions {
+        // Two definitions on one line (or nested function expressions) are already covered
+        if definition.start < line {
+            continue;
+        }
+        push_windows(&lines, line, definition.start, &mut chunks);
+        push_chunk(&lines, definition.start, definition.end + 1, definition.name, &mut chunks);
+        line = definition.end + 1;
+    }
+    push_windows(&lines, line, lines.len(), &mut chunks);
+    chunks
+}
+
+/// Zero-based, inclusive line span of a definition.
+struct Definition {
+    start: usize,
+    end: usize,
+    name: Option<String>,
+}
+
+fn collect_definitions(node: Node, source: &str, out: &mut Vec<Definition>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        let is_definition = DEFINITION_KINDS.contains(&child.kind())
+            && child.byte_range().len() <= MAX_CHUNK_BYTES
+            && non_whitespace_len(&source[child.byte_range()]) >= MIN_CHUNK_BYTES;
+        if is_definition {
+            out.push(Definition {
+                start: leading_comments_start(child),
+                end: child.end_position().row,
+                name: definition_name(child, source),
+            });
+        } else {
+            collect_definitions(child, source, out);
+        }
+    }
+}
+
+/// First line of the comment block directly above `node`, or its own first line.
+fn leading_comments_start(node: Node) -> usize {
+    // `export function` and `pub fn` keep their comments above the export statement
+    let mut anchor = node;
+    while let Some(parent) = anchor.parent().filter(|p| p.start_position().row == node.start_position().row) {
+        if parent.parent().is_none() {
+            break;
+        }
+        anchor = parent;
+    }
+
+    let mut start = anchor.start_position().row;
+    let mut sibling = anchor.prev_named_sibling();
+    while let Some(comment) = sibling.filter(|s| s.kind().contains("comment") || s.kind() == "attribute_item") {
+        if comment.end_position().row + 1 < start {
+            break;
+        }
+        start = comment.start_position().row;
+        sibling = comment.prev_named_sibling();
+    }
+    start
+}
+
+fn definition_name(node: Node, source: &str) -> Option<String> {
+    let text = |n: Node| source.get(n.byte_range()).map(str::to_string);
+    if node.kind() == "decorated_definition" {
+        return definition_name(node.child_by_field_name("definition")?, source);
+    }
+    if let Some(name) = node.child_by_field_name("name") {
+        return text(name);
+    }
+    if node.kind() == "impl_item" {
+        let ty = text(node.child_by_field_name("type")?)?;
+        return Some(match node.child_by_field_name("trait").and_then(text) {
+            Some(trait_name) => format!("impl {} for {}", trait_name, ty),
+            None => format!("impl {}", ty),
+        });
+    }
+    if node.kind() == "type_declaration" {
+        let mut cursor = node.walk();
+        let spec = node.named_children(&mut cursor).find(|c| c.kind() == "type_spec")?;
+        return text(spec.child_by_field_name("name")?);
+    }
+    // `const handler = () => {}`, `{ retry: function () {} }`
+    let parent = node.parent()?;
+    let field = match parent.kind() {
+        "variable_declarator" => "name",
+        "pair" => "key",
+        "assignment_expression" => "left",
+        _ => return None,
+    };
+    text(parent.child_by_field_name(field)?)
+}
+
+fn non_whitespace_len(text: &str) -> usize {
+    text.bytes().filter(|b| !b.is_ascii_whitespace()).count()
+}
+
+/// Cut lines `start..end` into windows, skipping ones that are (nearly) empty.
+fn push_windows(lines: &[&str], start: usize, end: usize, out: &mut Vec<Chunk>) {
+    let mut window = start;
+    while window < end {
+        let window_end = (window + WINDOW_LINES).min(end);
+        push_chunk(lines, window, window_end, None, out);
+        window = window_end;
+    }
+}
+
+fn push_chunk(lines: &[&str], start: usize, end: usize, name: Option<String>, out: &mut Vec<Chunk>) {
+    let end = end.min(lines.len());
+    if start >= end {
+        return;
+    }
+    // Trim blank lines so ranges point at code
+    let Some(first) = (start..end).find(|&i| !lines[i].trim().is_empty()) else {
+        return;
+    };
+    let last = (first..end).rev().find(|&i| !lines[i].trim().is_empty()).unwrap_or(first);
+    let text = lines[first..=last].join("\n");
+    if non_whitespace_len(&text) < MIN_CHUNK_BYTES {
+        return;
+    }
+    out.push(Chunk {
+        name,
+        range: Range {
+            start_line_number: first as u32 + 1,
+            start_column: 1,
+            end_line_number: last as u32 + 1,
+            end_column: lines[last].encode_utf16().count() as u32 + 1,
+        },
+        text,
+    });
+}
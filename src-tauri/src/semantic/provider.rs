@@ -0,0 +1,145 @@
+//! Clients for the embedding endpoints the semantic index can use: a local Ollama
+//! server or anything that speaks OpenAI's `/embeddings` API.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::error::{AppError, CmdResult, ErrorCode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingProvider {
+    Ollama,
+    #[serde(rename = "openai")]
+    OpenAi,
+}
+
+/// The `semanticSearch` section of settings.json:
+///
+/// ```json
+/// "semanticSearch": {
+///   "provider": "openai",
+///   "endpoint": "https://api.openai.com/v1",
+///   "model": "text-embedding-3-small",
+///   "apiKey": "sk-..."
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EmbeddingSettings {
+    pub provider: EmbeddingProvider,
+    /// Base URL: `http://host:11434` for Ollama, the `/v1` root for OpenAI-compatible servers.
+    pub endpoint: String,
+    pub model: String,
+    pub api_key: Option<String>,
+    /// Inputs sent per request.
+    pub batch_size: usize,
+    pub timeout_secs: u64,
+}
+
+impl Default for EmbeddingSettings {
+    fn default() -> Self {
+        EmbeddingSettings {
+            provider: EmbeddingProvider::Ollama,
+            endpoint: "http://localhost:11434".to_string(),
+            model: "nomic-embed-text".to_string(),
+            api_key: None,
+            batch_size: 32,
+            timeout_secs: 60,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct OllamaResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiResponse {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedding {
+    embedding: Vec<f32>,
+    index: usize,
+}
+
+pub struct EmbeddingClient {
+    settings: EmbeddingSettings,
+    http: reqwest::blocking::Client,
+}
+
+impl EmbeddingClient {
+    pub fn new(settings: EmbeddingSettings) -> CmdResult<Self> {
+        if settings.model.trim().is_empty() {
+            return Err(AppError::invalid_input("No embedding model configured"));
+        }
+        if !settings.endpoint.starts_with("http://") && !settings.endpoint.starts_with("https://") {
+            return Err(AppError::invalid_input(format!("Invalid embedding endpoint: {}", settings.endpoint)));
+        }
+        let http = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(settings.timeout_secs.max(1)))
+            .build()?;
+        Ok(EmbeddingClient { settings, http })
+    }
+
+    pub fn model(&self) -> &str {
+        &self.settings.model
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.settings.batch_size.max(1)
+    }
+
+    /// One vector per input, in input order. Blocks until the server answers.
+    pub fn embed(&self, inputs: &[String]) -> CmdResult<Vec<Vec<f32>>> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let endpoint = self.settings.endpoint.trim_end_matches('/');
+        let body = json!({ "model": self.settings.model, "input": inputs });
+        let url = match self.settings.provider {
+            EmbeddingProvider::Ollama => format!("{}/api/embed", endpoint),
+            EmbeddingProvider::OpenAi => format!("{}/embeddings", endpoint),
+        };
+
+        let mut request = self.http.post(&url).json(&body);
+        if let Some(key) = self.settings.api_key.as_deref().filter(|key| !key.is_empty()) {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send()?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().unwrap_or_default();
+            let detail = text.trim();
+            return Err(AppError::new(
+                ErrorCode::Network,
+                if detail.is_empty() {
+                    format!("Embedding request to {} failed with {}", url, status)
+                } else {
+                    format!("Embedding request to {} failed with {}: {}", url, status, detail)
+                },
+            ));
+        }
+
+        let vectors = match self.settings.provider {
+            EmbeddingProvider::Ollama => response.json::<OllamaResponse>()?.embeddings,
+            EmbeddingProvider::OpenAi => {
+                let mut data = response.json::<OpenAiResponse>()?.data;
+                data.sort_by_key(|item| item.index);
+                data.into_iter().map(|item| item.embedding).collect()
+            }
+        };
+        if vectors.len() != inputs.len() {
+            return Err(AppError::new(
+                ErrorCode::Network,
+                format!("Embedding server returned {} vectors for {} inputs", vectors.len(), inputs.len()),
+            ));
+        }
+        Ok(vectors)
+    }
+}
@@ -0,0 +1,202 @@
+//! Chunk vectors per file, and their on-disk form. Vectors are normalized when
+//! stored so a query is a dot product per chunk.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::Range;
+
+const MAGIC: &[u8; 4] = b"CTSE";
+const STORE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone)]
+pub struct StoredChunk {
+    /// Hash of the embedded text; unchanged chunks keep their vector.
+    pub hash: u64,
+    pub name: Option<String>,
+    pub range: Range,
+    pub vector: Vec<f32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileEntry {
+    pub modified: Option<SystemTime>,
+    pub size: u64,
+    pub chunks: Vec<StoredChunk>,
+}
+
+/// The vectors of one workspace for one model.
+#[derive(Debug, Default)]
+pub struct VectorStore {
+    pub model: String,
+    pub dimensions: usize,
+    pub files: HashMap<PathBuf, FileEntry>,
+}
+
+/// Scale to unit length; all-zero vectors are left alone.
+pub fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+impl VectorStore {
+    pub fn new(model: &str) -> Self {
+        VectorStore { model: model.to_string(), dimensions: 0, files: HashMap::new() }
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.files.values().map(|file| file.chunks.len()).sum()
+    }
+
+    /// The `k` chunks closest to `query` (normalized) by cosine similarity, best first.
+    pub fn nearest(&self, query: &[f32], k: usize) -> Vec<(f32, &Path, &StoredChunk)> {
+        if query.len() != self.dimensions {
+            return Vec::new();
+        }
+        let mut scored: Vec<(f32, &Path, &StoredChunk)> = self.files.iter()
+            .flat_map(|(path, file)| file.chunks.iter().map(move |chunk| (path.as_path(), chunk)))
+            .map(|(path, chunk)| (dot(query, &chunk.vector), path, chunk))
+            .collect();
+        scored.sort_by(|a, b| {
+            b.0.total_cmp(&a.0)
+                .then(a.1.cmp(b.1))
+                .then(a.2.range.start_line_number.cmp(&b.2.range.start_line_number))
+        });
+        scored.truncate(k);
+        scored
+    }
+
+    /// Write the store with paths relative to `root`, replacing `path` atomically.
+    pub fn save(&self, root: &Path, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("vec.tmp");
+        let mut out = BufWriter::new(fs::File::create(&tmp)?);
+        out.write_all(MAGIC)?;
+        write_u32(&mut out, STORE_VERSION)?;
+        write_str(&mut out, &root.to_string_lossy())?;
+        write_str(&mut out, &self.model)?;
+        write_u32(&mut out, self.dimensions as u32)?;
+
+        let files: Vec<(&str, &FileEntry)> = self.files.iter()
+            .filter_map(|(path, file)| Some((path.strip_prefix(root).ok()?.to_str()?, file)))
+            .collect();
+        write_u32(&mut out, files.len() as u32)?;
+        for (relative, file) in files {
+            write_str(&mut out, relative)?;
+            let modified = file.modified
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(u64::MAX, |d| d.as_nanos() as u64);
+            write_u64(&mut out, modified)?;
+            write_u64(&mut out, file.size)?;
+            write_u32(&mut out, file.chunks.len() as u32)?;
+            for chunk in &file.chunks {
+                write_u64(&mut out, chunk.hash)?;
+                write_str(&mut out, chunk.name.as_deref().unwrap_or(""))?;
+                let range = &chunk.range;
+                for value in [range.start_line_number, range.start_column, range.end_line_number, range.end_column] {
+                    write_u32(&mut out, value)?;
+                }
+                for value in &chunk.vector {
+                    out.write_all(&value.to_le_bytes())?;
+                }
+            }
+        }
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Read a store written by [`VectorStore::save`]. `None` when it is missing,
+    /// unreadable, or was built for another workspace or model.
+    pub fn load(root: &Path, model: &str, path: &Path) -> Option<Self> {
+        let mut input = BufReader::new(fs::File::open(path).ok()?);
+        let mut magic = [0u8; 4];
+        input.read_exact(&mut magic).ok()?;
+        if &magic != MAGIC || read_u32(&mut input).ok()? != STORE_VERSION {
+            return None;
+        }
+        if read_str(&mut input).ok()? != root.to_string_lossy() || read_str(&mut input).ok()? != model {
+            return None;
+        }
+        Self::read_files(&mut input, root, model).ok()
+    }
+
+    fn read_files(input: &mut impl Read, root: &Path, model: &str) -> io::Result<Self> {
+        let mut store = VectorStore::new(model);
+        store.dimensions = read_u32(input)? as usize;
+        for _ in 0..read_u32(input)? {
+            let relative = read_str(input)?;
+            let modified = match read_u64(input)? {
+                u64::MAX => None,
+                nanos => Some(UNIX_EPOCH + Duration::from_nanos(nanos)),
+            };
+            let size = read_u64(input)?;
+            let count = read_u32(input)? as usize;
+            let mut chunks = Vec::with_capacity(count.min(4096));
+            for _ in 0..count {
+                let hash = read_u64(input)?;
+                let name = Some(read_str(input)?).filter(|name| !name.is_empty());
+                let range = Range {
+                    start_line_number: read_u32(input)?,
+                    start_column: read_u32(input)?,
+                    end_line_number: read_u32(input)?,
+                    end_column: read_u32(input)?,
+                };
+                let mut bytes = vec![0u8; store.dimensions * 4];
+                input.read_exact(&mut bytes)?;
+                let vector = bytes.chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect();
+                chunks.push(StoredChunk { hash, name, range, vector });
+            }
+            store.files.insert(root.join(relative), FileEntry { modified, size, chunks });
+        }
+        Ok(store)
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn write_u32(out: &mut impl Write, value: u32) -> io::Result<()> {
+    out.write_all(&value.to_le_bytes())
+}
+
+fn write_u64(out: &mut impl Write, value: u64) -> io::Result<()> {
+    out.write_all(&value.to_le_bytes())
+}
+
+fn write_str(out: &mut impl Write, value: &str) -> io::Result<()> {
+    write_u32(out, value.len() as u32)?;
+    out.write_all(value.as_bytes())
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_str(input: &mut impl Read) -> io::Result<String> {
+    let len = read_u32(input)? as usize;
+    let mut bytes = Vec::new();
+    input.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
@@ -0,0 +1,458 @@
+//! Structural search: find syntax, not text. A pattern is either a tree-sitter
+//! S-expression query or a code snippet with metavariables, e.g.
+//!
+//! ```text
+//! $X.lock().unwrap()                                  (rust)
+//! ((call_expression function: (identifier) @fn) (#eq? @fn "eval"))   (javascript)
+//! ```
+//!
+//! In snippets `$NAME` matches any single node and `$$$NAME` any run of sibling
+//! nodes (`$_` and `$$$` match without capturing). A name used twice must match
+//! the same text both times.
+
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use regex::Regex;
+use serde::Serialize;
+use streaming_iterator::StreamingIterator;
+use tauri::AppHandle;
+use tree_sitter::{Language, Node, Parser, Query, QueryCursor};
+
+use crate::error::{AppError, CmdResult, ErrorCode};
+use crate::grammars::grammar_family;
+use crate::languages;
+use crate::search::{self, SearchFilters, SearchScope};
+
+/// Files larger than this (usually generated or minified) are not parsed.
+const MAX_PARSED_FILE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Matched text is cut off after this many bytes; the range still covers it all.
+const MAX_MATCH_TEXT: usize = 2000;
+
+const META_PREFIX: &str = "__sm_";
+const MULTI_META_PREFIX: &str = "__smm_";
+
+/// 1-based lines and UTF-16 columns, like workspace search results.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchRange {
+    pub start_line_number: usize,
+    pub start_column: usize,
+    pub end_line_number: usize,
+    pub end_column: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuralCapture {
+    pub name: String,
+    pub text: String,
+    pub range: MatchRange,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuralMatch {
+    pub range: MatchRange,
+    pub text: String,
+    pub captures: Vec<StructuralCapture>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuralResult {
+    pub path: String,
+    pub name: String,
+    pub relative_path: String,
+    pub language_id: String,
+    pub matches: Vec<StructuralMatch>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuralSearchResponse {
+    pub results: Vec<StructuralResult>,
+    pub files_scanned: usize,
+    pub matches: usize,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Meta {
+    Single(String),
+    Multi(String),
+}
+
+/// A snippet pattern, parsed once and detached from its tree so workers can share it.
+#[derive(Debug, Clone)]
+struct PatternNode {
+    kind: &'static str,
+    text: String,
+    meta: Option<Meta>,
+    children: Vec<PatternNode>,
+}
+
+enum StructuralPattern {
+    Query(Query),
+    Snippet(PatternNode),
+}
+
+/// The metavariable a node stands for, if its whole text is one placeholder.
+fn placeholder_meta(text: &str) -> Option<Meta> {
+    let is_name = |name: &str| name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_');
+    if let Some(name) = text.strip_prefix(MULTI_META_PREFIX).filter(|name| is_name(name)) {
+        return Some(Meta::Multi(if name.is_empty() { "_".to_string() } else { name.to_string() }));
+    }
+    text.strip_prefix(META_PREFIX)
+        .filter(|name| !name.is_empty() && is_name(name))
+        .map(|name| Meta::Single(name.to_string()))
+}
+
+/// Ways to make a snippet parse on its own, tried in order.
+fn snippet_wrappers(language_id: &str) -> &'static [&'static str] {
+    match grammar_family(language_id) {
+        "rust" => &["PATTERN", "fn __sm_wrapper() { PATTERN }", "fn __sm_wrapper() { PATTERN; }"],
+        "go" => &["package __sm\nPATTERN", "package __sm\nfunc __sm_wrapper() {\nPATTERN\n}"],
+        "python" | "javascript" | "typescript" | "tsx" => &["PATTERN", "x = PATTERN"],
+        _ => &["PATTERN"],
+    }
+}
+
+fn build_pattern_node(node: Node, source: &[u8]) -> PatternNode {
+    let text = node.utf8_text(source).unwrap_or_default().to_string();
+    let meta = placeholder_meta(&text);
+    let children = if meta.is_some() {
+        Vec::new()
+    } else {
+        let mut cursor = node.walk();
+        node.children(&mut cursor)
+            .filter(|child| !child.is_extra())
+            .map(|child| build_pattern_node(child, source))
+            .collect()
+    };
+    PatternNode { kind: node.kind(), text, meta, children }
+}
+
+fn parse_snippet(snippet: &str, language: &Language, language_id: &str) -> CmdResult<PatternNode> {
+    let metavariable = Regex::new(r"\$\$\$([A-Z_][A-Z0-9_]*)?|\$([A-Z_][A-Z0-9_]*)").expect("valid metavariable regex");
+    let code = metavariable.replace_all(snippet.trim(), |caps: &regex::Captures| match (caps.get(1), caps.get(2)) {
+        (_, Some(name)) => format!("{}{}", META_PREFIX, name.as_str()),
+        (Some(name), _) => format!("{}{}", MULTI_META_PREFIX, name.as_str()),
+        (None, None) => MULTI_META_PREFIX.to_string(),
+    });
+
+    let mut parser = Parser::new();
+    parser.set_language(language).map_err(|e| AppError::internal(format!("Failed to set language: {}", e)))?;
+    for wrapper in snippet_wrappers(language_id) {
+        let (before, after) = wrapper.split_once("PATTERN").unwrap_or((wrapper, ""));
+        let source = format!("{}{}{}", before, code, after);
+        let Some(tree) = parser.parse(&source, None) else {
+            continue;
+        };
+        if tree.root_node().has_error() {
+            continue;
+        }
+        let span = before.len()..before.len() + code.len();
+        let Some(node) = tree.root_node().named_descendant_for_byte_range(span.start, span.end) else {
+            continue;
+        };
+        if node.byte_range() != span {
+            continue;
+        }
+
+        let pattern = build_pattern_node(node, source.as_bytes());
+        if pattern.meta.is_some() {
+            return Err(AppError::new(ErrorCode::InvalidPattern, "Pattern must contain code besides a metavariable"));
+        }
+        return Ok(pattern);
+    }
+    Err(AppError::new(ErrorCode::InvalidPattern, format!("Pattern is not valid {} code", language_id)))
+}
+
+fn compile_pattern(pattern: &str, language: &Language, language_id: &str) -> CmdResult<StructuralPattern> {
+    let trimmed = pattern.trim_start();
+    if trimmed.starts_with('(') || trimmed.starts_with('[') {
+        let query = Query::new(language, pattern)
+            .map_err(|e| AppError::new(ErrorCode::InvalidPattern, format!("Invalid tree-sitter query: {}", e)))?;
+        return Ok(StructuralPattern::Query(query));
+    }
+    parse_snippet(pattern, language, language_id).map(StructuralPattern::Snippet)
+}
+
+/// Metavariable bindings made so far; truncated again when a branch fails.
+type Bindings = Vec<(String, Range<usize>)>;
+
+fn bind(name: &str, range: Range<usize>, source: &[u8], bindings: &mut Bindings) -> bool {
+    if name == "_" {
+        return true;
+    }
+    match bindings.iter().find(|(bound, _)| bound == name) {
+        Some((_, previous)) => source[previous.clone()] == source[range],
+        None => {
+            bindings.push((name.to_string(), range));
+            true
+        }
+    }
+}
+
+fn significant_children<'tree>(node: Node<'tree>) -> Vec<Node<'tree>> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).filter(|child| !child.is_extra()).collect()
+}
+
+fn match_node(pattern: &PatternNode, node: Node, source: &[u8], bindings: &mut Bindings) -> bool {
+    match &pattern.meta {
+        Some(Meta::Single(name)) | Some(Meta::Multi(name)) => bind(name, node.byte_range(), source, bindings),
+        None => {
+            if pattern.kind != node.kind() {
+                return false;
+            }
+            let children = significant_children(node);
+            if pattern.children.is_empty() && children.is_empty() {
+                return pattern.text.as_bytes() == &source[node.byte_range()];
+            }
+            match_sequence(&pattern.children, &children, source, bindings)
+        }
+    }
+}
+
+fn match_sequence(patterns: &[PatternNode], nodes: &[Node], source: &[u8], bindings: &mut Bindings) -> bool {
+    let Some((first, rest)) = patterns.split_first() else {
+        return nodes.is_empty();
+    };
+    let saved = bindings.len();
+
+    if let Some(Meta::Multi(name)) = &first.meta {
+        for taken in 0..=nodes.len() {
+            let range = match taken {
+                0 => 0..0,
+                _ => nodes[0].start_byte()..nodes[taken - 1].end_byte(),
+            };
+            if bind(name, range, source, bindings) && match_sequence(rest, &nodes[taken..], source, bindings) {
+                return true;
+            }
+            bindings.truncate(saved);
+        }
+        return false;
+    }
+
+    let Some((node, remaining)) = nodes.split_first() else {
+        return false;
+    };
+    if match_node(first, *node, source, bindings) && match_sequence(rest, remaining, source, bindings) {
+        return true;
+    }
+    bindings.truncate(saved);
+    false
+}
+
+/// Converts byte offsets to 1-based lines and UTF-16 columns.
+struct LineIndex<'a> {
+    source: &'a [u8],
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(source: &'a [u8]) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(memchr::memchr_iter(b'\n', source).map(|i| i + 1));
+        LineIndex { source, line_starts }
+    }
+
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let prefix = String::from_utf8_lossy(&self.source[self.line_starts[line]..offset]);
+        (line + 1, prefix.encode_utf16().count() + 1)
+    }
+
+    fn range(&self, bytes: Range<usize>) -> MatchRange {
+        let (start_line_number, start_column) = self.position(bytes.start);
+        let (end_line_number, end_column) = self.position(bytes.end);
+        MatchRange { start_line_number, start_column, end_line_number, end_column }
+    }
+
+    fn text(&self, bytes: Range<usize>) -> String {
+        let mut end = bytes.end.min(bytes.start + MAX_MATCH_TEXT);
+        // Never split a UTF-8 sequence
+        while end < bytes.end && (self.source[end] & 0xC0) == 0x80 {
+            end -= 1;
+        }
+        String::from_utf8_lossy(&self.source[bytes.start..end]).into_owned()
+    }
+
+    fn capture(&self, name: &str, bytes: Range<usize>) -> StructuralCapture {
+        StructuralCapture { name: name.to_string(), text: self.text(bytes.clone()), range: self.range(bytes) }
+    }
+}
+
+/// Up to `limit` matches, and whether the limit left any out.
+fn find_matches(pattern: &StructuralPattern, root: Node, source: &[u8], limit: usize) -> (Vec<StructuralMatch>, bool) {
+    let lines = LineIndex::new(source);
+    let mut matches = Vec::new();
+    let mut dropped = false;
+
+    match pattern {
+        StructuralPattern::Query(query) => {
+            let names = query.capture_names();
+            let mut cursor = QueryCursor::new();
+            let mut found = cursor.matches(query, root, source);
+            while let Some(found) = found.next() {
+                if matches.len() >= limit {
+                    dropped = true;
+                    break;
+                }
+                // `@match` marks the whole match; otherwise span all captures
+                let whole = found.captures.iter().find(|c| names[c.index as usize] == "match");
+                let range = match whole {
+                    Some(capture) => capture.node.byte_range(),
+                    None => {
+                        let start = found.captures.iter().map(|c| c.node.start_byte()).min();
+                        let end = found.captures.iter().map(|c| c.node.end_byte()).max();
+                        match (start, end) {
+                            (Some(start), Some(end)) => start..end,
+                            _ => continue,
+                        }
+                    }
+                };
+                // Captures starting with `_` are helpers for predicates, as in tree-sitter's own tooling
+                let captures = found.captures.iter()
+                    .map(|c| (names[c.index as usize], c.node.byte_range()))
+                    .filter(|(name, _)| *name != "match" && !name.starts_with('_'))
+                    .map(|(name, bytes)| lines.capture(name, bytes))
+                    .collect();
+                matches.push(StructuralMatch { range: lines.range(range.clone()), text: lines.text(range), captures });
+            }
+        }
+        StructuralPattern::Snippet(snippet) => {
+            let mut stack = vec![root];
+            while let Some(node) = stack.pop() {
+                let mut bindings = Bindings::new();
+                if node.kind() == snippet.kind && match_node(snippet, node, source, &mut bindings) {
+                    if matches.len() >= limit {
+                        dropped = true;
+                        break;
+                    }
+                    let captures = bindings.into_iter()
+                        .map(|(name, bytes)| lines.capture(&name, bytes))
+                        .collect();
+                    matches.push(StructuralMatch {
+                        range: lines.range(node.byte_range()),
+                        text: lines.text(node.byte_range()),
+                        captures,
+                    });
+                }
+                // Reversed so matches come out in document order
+                let mut cursor = node.walk();
+                let children: Vec<Node> = node.children(&mut cursor).collect();
+                stack.extend(children.into_iter().rev());
+            }
+        }
+    }
+    (matches, dropped)
+}
+
+fn search_file(path: &Path, pattern: &StructuralPattern, language: &Language, limit: usize) -> Option<(Vec<StructuralMatch>, bool)> {
+    if fs::metadata(path).ok()?.len() > MAX_PARSED_FILE_SIZE {
+        return None;
+    }
+    let source = fs::read(path).ok()?;
+    let mut parser = Parser::new();
+    parser.set_language(language).ok()?;
+    let tree = parser.parse(&source, None)?;
+    Some(find_matches(pattern, tree.root_node(), &source, limit))
+}
+
+/// Run a structural pattern over every `language_id` file in the workspace, in
+/// parallel. Filters work as in workspace search. At most `max_results` matches
+/// are returned, sorted by path.
+#[tauri::command(rename_all = "snake_case")]
+pub fn structural_search(
+    workspace_path: String,
+    pattern: String,
+    language_id: String,
+    max_results: usize,
+    filters: Option<SearchFilters>,
+    app_handle: AppHandle,
+) -> CmdResult<StructuralSearchResponse> {
+    if pattern.trim().is_empty() {
+        return Err(AppError::invalid_input("Structural search pattern cannot be empty"));
+    }
+
+    let workspace = search::validate_workspace(&workspace_path)?;
+    let language = crate::get_language(&language_id)?;
+    let scope = SearchScope::new(workspace, &filters.unwrap_or_default(), languages::load_file_types(&app_handle))?;
+    run_structural_search(&scope, &pattern, &language_id, &language, max_results)
+}
+
+/// [`structural_search`] over an already resolved scope and grammar.
+pub fn run_structural_search(
+    scope: &SearchScope,
+    pattern: &str,
+    language_id: &str,
+    language: &Language,
+    max_results: usize,
+) -> CmdResult<StructuralSearchResponse> {
+    let compiled = compile_pattern(pattern, language, language_id)?;
+    let family = grammar_family(language_id);
+
+    let found = AtomicUsize::new(0);
+    let truncated = AtomicBool::new(false);
+    let scanned = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::new());
+    search::for_each_file(scope, &|path, _| {
+        let Some(file_language) = scope.file_types().language_for_path(path) else {
+            return true;
+        };
+        if grammar_family(&file_language) != family {
+            return true;
+        }
+        scanned.fetch_add(1, Ordering::Relaxed);
+
+        // Past the limit files are still searched, with no room, until one has a
+        // match that is left out: only then are the results known to be incomplete
+        let remaining = max_results.saturating_sub(found.load(Ordering::Relaxed));
+        let Some((matches, dropped)) = search_file(path, &compiled, language, remaining) else {
+            return true;
+        };
+        if dropped {
+            truncated.store(true, Ordering::Relaxed);
+        }
+        if !matches.is_empty() {
+            found.fetch_add(matches.len(), Ordering::Relaxed);
+            if let Ok(mut results) = results.lock() {
+                results.push(StructuralResult {
+                    path: path.to_string_lossy().to_string(),
+                    name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+                    relative_path: scope.relative(path),
+                    language_id: file_language,
+                    matches,
+                });
+            }
+        }
+        !truncated.load(Ordering::Relaxed)
+    });
+
+    let mut results: Vec<StructuralResult> = results.into_inner().unwrap_or_default();
+    results.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+
+    // Workers race past the limit; trim back to it in path order
+    let mut remaining = max_results;
+    let mut truncated = truncated.into_inner();
+    for result in &mut results {
+        truncated |= result.matches.len() > remaining;
+        result.matches.truncate(remaining);
+        remaining -= result.matches.len();
+    }
+    results.retain(|result| !result.matches.is_empty());
+
+    Ok(StructuralSearchResponse {
+        results,
+        files_scanned: scanned.load(Ordering::Relaxed),
+        matches: max_results - remaining,
+        truncated,
+    })
+}
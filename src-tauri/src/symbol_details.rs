@@ -0,0 +1,273 @@
+//! What a language server adds to an outline symbol, read from the syntax tree
+//! after extraction so that the built-in extractors, [`crate::outline`] and tags
+//! queries all give the same shape as LSP's `DocumentSymbol`: the `SymbolKind`
+//! number, a one-line detail (a signature or type annotation), modifiers, the
+//! deprecated tag, and a selection range on the name rather than the whole
+//! declaration.
+
+use tree_sitter::{Node, Point};
+
+use crate::tags::OUTLINE_KINDS;
+use crate::{node_to_range, DocumentSymbol, Range};
+
+/// LSP `SymbolTag.Deprecated`.
+pub const DEPRECATED: u8 = 1;
+
+/// Longer details are cut off, as the outline shows them on one line.
+const MAX_DETAIL_CHARS: usize = 80;
+
+/// Nodes that only wrap a declaration, adding `export`, decorators or a keyword.
+const WRAPPERS: &[&str] = &[
+    "export_statement", "decorated_definition", "lexical_declaration", "variable_declaration", "ambient_declaration",
+];
+
+/// Fields naming the declared thing, tried in order.
+const NAME_FIELDS: &[&str] = &["name", "declarator", "left", "pattern", "key"];
+
+/// LSP `SymbolKind` for an outline kind. The outline kinds are LSP's kind names
+/// in order, so this is the 1-based position; anything else is a variable.
+pub fn symbol_kind(kind: &str) -> u8 {
+    OUTLINE_KINDS.iter().position(|known| *known == kind).map_or(13, |index| index as u8 + 1)
+}
+
+/// Fill in the LSP fields of `symbols` and their children from the tree they
+/// were extracted from. Run again on the same symbols, it gives the same result.
+pub fn annotate(symbols: &mut [DocumentSymbol], root: Node, source: &str, language_id: &str) {
+    for symbol in symbols {
+        symbol.symbol_kind = symbol_kind(&symbol.kind);
+        if let Some(node) = declaration(root, &symbol.range) {
+            if symbol.selection_range == symbol.range {
+                if let Some(name) = name_node(node, &symbol.name, source) {
+                    symbol.selection_range = node_to_range(name);
+                }
+            }
+            symbol.detail = detail(node, source);
+            symbol.modifiers = modifiers(node, &symbol.name, source, language_id);
+            symbol.tags = if is_deprecated(node, source) { vec![DEPRECATED] } else { Vec::new() };
+        }
+        annotate(&mut symbol.children, root, source, language_id);
+    }
+}
+
+/// The node an outline range was taken from.
+fn declaration<'tree>(root: Node<'tree>, range: &Range) -> Option<Node<'tree>> {
+    // Outline ranges are 1-based
+    let point = |line: u32, column: u32| Point::new(line.saturating_sub(1) as usize, column.saturating_sub(1) as usize);
+    let start = point(range.start_line_number, range.start_column);
+    let end = point(range.end_line_number, range.end_column);
+    root.named_descendant_for_point_range(start, end)
+        .filter(|node| node.start_position() == start && node.end_position() == end)
+}
+
+/// `node` and the wrappers around it, innermost first.
+fn with_wrappers(node: Node) -> Vec<Node> {
+    let mut nodes = vec![node];
+    let mut current = node;
+    while let Some(parent) = current.parent().filter(|parent| WRAPPERS.contains(&parent.kind())) {
+        nodes.push(parent);
+        current = parent;
+    }
+    nodes
+}
+
+/// The identifier a declaration introduces: its name field, the innermost of
+/// C-style declarators, or failing those the first descendant spelled like the
+/// symbol.
+fn name_node<'tree>(node: Node<'tree>, name: &str, source: &str) -> Option<Node<'tree>> {
+    let by_field = NAME_FIELDS.iter().find_map(|field| node.child_by_field_name(field)).map(|mut name_node| {
+        while let Some(inner) = name_node.child_by_field_name("declarator") {
+            name_node = inner;
+        }
+        name_node
+    });
+    // An `impl Foo` is named by its type
+    let by_field = by_field.or_else(|| (node.kind() == "impl_item").then(|| node.child_by_field_name("type")).flatten());
+    if let Some(name_node) = by_field.filter(|name_node| name_node.start_position().row == name_node.end_position().row) {
+        return Some(name_node);
+    }
+    let name = name.strip_prefix("mut ").unwrap_or(name);
+    find_spelled(node, name, source, 3)
+}
+
+fn find_spelled<'tree>(node: Node<'tree>, name: &str, source: &str, depth: usize) -> Option<Node<'tree>> {
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+    children.iter()
+        .find(|child| child.utf8_text(source.as_bytes()).is_ok_and(|text| text == name))
+        .copied()
+        .or_else(|| {
+            if depth == 0 {
+                return None;
+            }
+            children.into_iter().find_map(|child| find_spelled(child, name, source, depth - 1))
+        })
+}
+
+/// A function's signature, `<T>(a: T, b: i32) -> T` or `int (int a)`, or a
+/// variable's or field's type.
+fn detail(node: Node, source: &str) -> Option<String> {
+    let text = |node: Node| node.utf8_text(source.as_bytes()).unwrap_or_default();
+    let signature = signature_node(node, 3);
+    let detail = match signature.and_then(|signature| Some((signature, signature.child_by_field_name("parameters")?))) {
+        Some((signature, parameters)) => {
+            let returns = ["return_type", "result"].iter().find_map(|field| signature.child_by_field_name(field));
+            let end = returns.map_or(parameters.end_byte(), |returns| returns.end_byte());
+            let mut detail = source[parameters.start_byte()..end].to_string();
+            if let Some(type_parameters) = signature.child_by_field_name("type_parameters") {
+                detail.insert_str(0, text(type_parameters));
+            }
+            // C, Java and C# write the return type first, as `type name(...)`
+            let leading_type = ["type", "returns"].iter()
+                .find_map(|field| node.child_by_field_name(field))
+                .filter(|leading| returns.is_none() && leading.end_byte() <= parameters.start_byte());
+            if let Some(leading) = leading_type {
+                detail = format!("{} {}", text(leading), detail);
+            }
+            detail
+        }
+        // An `impl Foo`'s type is its name
+        None if node.kind() == "impl_item" => return None,
+        None => {
+            // `int a, b` types each declarator from the declaration
+            let annotation = node.child_by_field_name("type")
+                .or_else(|| node.parent()?.child_by_field_name("type").filter(|shared| shared.end_byte() <= node.start_byte()))?;
+            let annotation = text(annotation).trim_start_matches(':').trim();
+            if annotation.contains('\n') {
+                return None;
+            }
+            annotation.to_string()
+        }
+    };
+    let detail = detail.split_whitespace().collect::<Vec<_>>().join(" ");
+    if detail.is_empty() {
+        return None;
+    }
+    Some(match detail.char_indices().nth(MAX_DETAIL_CHARS) {
+        Some((cut, _)) => format!("{}…", &detail[..cut]),
+        None => detail,
+    })
+}
+
+/// The node holding a declaration's parameters: itself, its C-style declarator,
+/// or the function assigned to it (`const f = (a) => a`).
+fn signature_node(node: Node, depth: usize) -> Option<Node> {
+    if node.child_by_field_name("parameters").is_some() {
+        return Some(node);
+    }
+    if depth == 0 {
+        return None;
+    }
+    let inner = node.child_by_field_name("declarator")
+        .or_else(|| node.child_by_field_name("value").filter(|value| value.kind().contains("function")))?;
+    signature_node(inner, depth - 1)
+}
+
+/// `pub`, `async`, `static`, `abstract` and `exported`, as written on the
+/// declaration, its wrappers or its decorators. Go exports by capitalization.
+fn modifiers(node: Node, name: &str, source: &str, language_id: &str) -> Vec<String> {
+    let mut modifiers = Vec::new();
+    let mut scanned = with_wrappers(node);
+    if let Some(value) = node.child_by_field_name("value").filter(|value| value.kind().contains("function")) {
+        scanned.push(value);
+    }
+    for scanned in scanned {
+        if scanned.kind() == "export_statement" {
+            add_modifier(&mut modifiers, "export");
+        }
+        let mut cursor = scanned.walk();
+        for child in scanned.children(&mut cursor) {
+            let kind = child.kind();
+            if kind == "decorator" {
+                // `@staticmethod`, `@abc.abstractmethod`
+                let decorator = child.utf8_text(source.as_bytes()).unwrap_or_default();
+                let decorator = decorator.trim_start_matches('@').split('(').next().unwrap_or_default();
+                match decorator.rsplit('.').next().unwrap_or_default() {
+                    "staticmethod" => add_modifier(&mut modifiers, "static"),
+                    "abstractmethod" => add_modifier(&mut modifiers, "abstract"),
+                    _ => {}
+                }
+            } else if kind.contains("modifier") || kind == "storage_class_specifier" || (!child.is_named() && child.child_count() == 0) {
+                modifier_words(child, source, &mut modifiers);
+            }
+        }
+    }
+    if language_id == "go" && name.starts_with(|c: char| c.is_uppercase()) {
+        add_modifier(&mut modifiers, "export");
+    }
+    modifiers
+}
+
+/// The modifier keywords among the tokens of a modifier node, skipping the
+/// annotations Java keeps in the same list.
+fn modifier_words(node: Node, source: &str, modifiers: &mut Vec<String>) {
+    if is_attribute(node.kind()) {
+        return;
+    }
+    if node.child_count() == 0 {
+        add_modifier(modifiers, node.utf8_text(source.as_bytes()).unwrap_or_default());
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        modifier_words(child, source, modifiers);
+    }
+}
+
+/// Attributes, decorators and annotations; not Python's `a.b`, which is an `attribute` too.
+fn is_attribute(kind: &str) -> bool {
+    kind.starts_with("attribute_") || kind == "decorator" || kind.contains("annotation")
+}
+
+fn add_modifier(modifiers: &mut Vec<String>, keyword: &str) {
+    let modifier = match keyword {
+        "pub" | "public" => "pub",
+        "async" => "async",
+        "static" => "static",
+        "abstract" => "abstract",
+        "export" => "exported",
+        _ => return,
+    };
+    if !modifiers.iter().any(|known| known == modifier) {
+        modifiers.push(modifier.to_string());
+    }
+}
+
+/// Marked deprecated by an attribute, decorator or annotation (`#[deprecated]`,
+/// `@deprecated`, `@Deprecated`, `[Obsolete]`) or by its doc comment (JSDoc's
+/// `@deprecated`, Go's `Deprecated:` paragraph).
+fn is_deprecated(node: Node, source: &str) -> bool {
+    let text = |node: Node| node.utf8_text(source.as_bytes()).unwrap_or_default();
+    let marks = |attribute: &str| attribute.to_lowercase().contains("deprecated") || attribute.contains("Obsolete");
+    let scanned = with_wrappers(node);
+
+    let marked_inside = scanned.iter().any(|scanned| {
+        let mut cursor = scanned.walk();
+        let mut children = scanned.children(&mut cursor);
+        children.any(|child| (child.kind() == "modifiers" || is_attribute(child.kind())) && marks(text(child)))
+    });
+    if marked_inside {
+        return true;
+    }
+
+    // Attributes and comments directly above the outermost wrapper
+    let mut below = *scanned.last().unwrap_or(&node);
+    while let Some(previous) = below.prev_named_sibling() {
+        let kind = previous.kind();
+        let adjacent = previous.end_position().row + 1 >= below.start_position().row;
+        if !adjacent || !(kind.contains("comment") || is_attribute(kind)) {
+            break;
+        }
+        let deprecated = if kind.contains("comment") {
+            let comment = text(previous);
+            comment.contains("@deprecated")
+                || comment.lines().any(|line| line.trim_start_matches(['/', '*', '#', ' ', '\t']).starts_with("Deprecated:"))
+        } else {
+            marks(text(previous))
+        };
+        if deprecated {
+            return true;
+        }
+        below = previous;
+    }
+    false
+}
@@ -0,0 +1,131 @@
+//! Syntax errors from the parse tree, for files no language server covers.
+//! `ERROR` and `MISSING` nodes become LSP diagnostics, published like a
+//! server's: a `textDocument/publishDiagnostics` notification on the
+//! `lsp_message` event, after every open and edit of a document.
+//!
+//! Each diagnostic has `source: "tree-sitter"`, and so do the notification's
+//! params, so the frontend can merge these with a server's diagnostics for the
+//! same file or drop them while a server is running.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tree_sitter::{Node, Point, Tree};
+
+use crate::documents::{self, Document, Documents};
+use crate::error::CmdResult;
+
+const SOURCE: &str = "tree-sitter";
+
+/// Reporting stops here; a file in the wrong language is mostly errors.
+const MAX_DIAGNOSTICS: usize = 100;
+
+/// LSP `Position`: 0-based line and UTF-16 column.
+#[derive(Debug, Clone, Serialize)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LspRange {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// LSP `Diagnostic`, always with severity 1 (error).
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub range: LspRange,
+    pub severity: u8,
+    pub source: &'static str,
+    pub message: String,
+}
+
+/// LSP `PublishDiagnosticsParams`, plus `source`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PublishDiagnosticsParams {
+    pub uri: String,
+    pub version: i32,
+    pub diagnostics: Vec<Diagnostic>,
+    pub source: &'static str,
+}
+
+/// The syntax errors in a tree, in document order.
+pub fn syntax_errors(tree: &Tree, source: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    collect(tree.root_node(), source, &mut diagnostics);
+    diagnostics
+}
+
+fn collect(node: Node, source: &str, diagnostics: &mut Vec<Diagnostic>) {
+    if diagnostics.len() >= MAX_DIAGNOSTICS || !node.has_error() {
+        return;
+    }
+    if node.is_missing() {
+        let message = if node.is_named() {
+            format!("missing {}", node.kind().replace('_', " "))
+        } else {
+            format!("missing `{}`", node.kind())
+        };
+        diagnostics.push(diagnostic(node, source, message));
+        return;
+    }
+    if node.is_error() {
+        // Errors inside an error are part of the same mistake
+        let text = node.utf8_text(source.as_bytes()).unwrap_or_default().trim();
+        let message = if !text.is_empty() && text.len() <= 24 && !text.contains('\n') {
+            format!("unexpected `{}`", text)
+        } else {
+            "syntax error".to_string()
+        };
+        diagnostics.push(diagnostic(node, source, message));
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect(child, source, diagnostics);
+    }
+}
+
+fn diagnostic(node: Node, source: &str, message: String) -> Diagnostic {
+    Diagnostic {
+        range: LspRange {
+            start: position(source, node.start_byte(), node.start_position()),
+            end: position(source, node.end_byte(), node.end_position()),
+        },
+        severity: 1,
+        source: SOURCE,
+        message,
+    }
+}
+
+/// LSP position of a byte offset, from its tree-sitter point (whose column is in bytes).
+fn position(source: &str, offset: usize, point: Point) -> Position {
+    let character = source[offset - point.column..offset].encode_utf16().count();
+    Position { line: point.row as u32, character: character as u32 }
+}
+
+fn params(file_path: &str, document: &Document) -> PublishDiagnosticsParams {
+    PublishDiagnosticsParams {
+        uri: format!("file://{}", file_path),
+        version: document.version(),
+        diagnostics: syntax_errors(document.tree(), document.source()),
+        source: SOURCE,
+    }
+}
+
+/// Send a document's syntax errors to the frontend the way a server would.
+pub fn publish(app_handle: &AppHandle, file_path: &str, document: &Document) {
+    let notification = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": params(file_path, document),
+    });
+    let _ = app_handle.emit("lsp_message", notification.to_string());
+}
+
+/// The current syntax errors of an open document, for the frontend to pull.
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_syntax_diagnostics(file_path: String, documents: tauri::State<'_, Documents>) -> CmdResult<PublishDiagnosticsParams> {
+    documents::with_document(&documents, &file_path, |document| params(&file_path, document))
+}
@@ -0,0 +1,139 @@
+//! Outline extraction driven by tree-sitter `tags.scm` queries. Defaults ship in
+//! `queries/<grammar>/tags.scm` and installed grammars bring their own; a file at
+//! the same path under the app's config directory replaces either (see
+//! [`crate::queries`]), so the outline can be customized without rebuilding the app.
+//!
+//! Captures follow the tree-sitter tags convention: `@definition.<kind>` marks a
+//! symbol and `@name` its name. The kind is an outline kind (`function`, `class`,
+//! `enummember`, ...) or one of the usual tags aliases (`macro`, `type`, ...).
+//! A symbol's container is the innermost symbol whose node encloses it.
+//! `@reference.*`, `@doc` and captures starting with `_` are ignored.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Node, Query, QueryCursor};
+
+use crate::queries::Queries;
+use crate::{node_to_range, DocumentSymbol};
+
+/// The tags query for a language, or `None` when it has neither an override, nor a
+/// query from its installed grammar, nor a default (its outline then comes from
+/// the built-in extractors).
+pub fn tag_query(queries: &Queries, language_id: &str) -> Option<TagQuery> {
+    queries.get(language_id, "tags.scm").map(TagQuery::new)
+}
+
+enum Capture {
+    Definition(String),
+    Name,
+    Ignored,
+}
+
+pub struct TagQuery {
+    query: Arc<Query>,
+    /// What each capture of `query` means, by capture index.
+    captures: Vec<Capture>,
+}
+
+/// A definition found by the query, before nesting.
+struct Tag<'tree> {
+    node: Node<'tree>,
+    name_node: Node<'tree>,
+    kind: String,
+    pattern: usize,
+}
+
+impl TagQuery {
+    pub fn new(query: Arc<Query>) -> Self {
+        let captures = query.capture_names().iter()
+            .map(|name| match name.strip_prefix("definition.") {
+                Some(kind) => Capture::Definition(outline_kind(kind).to_string()),
+                None if *name == "name" => Capture::Name,
+                None => Capture::Ignored,
+            })
+            .collect();
+        TagQuery { query, captures }
+    }
+
+    /// The outline of a parsed file.
+    pub fn symbols(&self, root: Node, source: &str) -> Vec<DocumentSymbol> {
+        // One symbol per definition and name; the first pattern decides its kind
+        let mut tags: HashMap<(usize, usize), Tag> = HashMap::new();
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&self.query, root, source.as_bytes());
+        while let Some(m) = matches.next() {
+            let mut definition = None;
+            let mut name_node = None;
+            for capture in m.captures {
+                match &self.captures[capture.index as usize] {
+                    Capture::Definition(kind) => definition = Some((capture.node, kind)),
+                    Capture::Name => name_node = Some(capture.node),
+                    Capture::Ignored => {}
+                }
+            }
+            let (Some((node, kind)), Some(name_node)) = (definition, name_node) else { continue };
+            let key = (node.id(), name_node.id());
+            if tags.get(&key).is_some_and(|tag| tag.pattern <= m.pattern_index) {
+                continue;
+            }
+            tags.insert(key, Tag { node, name_node, kind: kind.clone(), pattern: m.pattern_index });
+        }
+
+        let mut tags: Vec<Tag> = tags.into_values().collect();
+        tags.sort_by_key(|tag| (tag.node.start_byte(), std::cmp::Reverse(tag.node.end_byte()), tag.name_node.start_byte()));
+        let mut next = 0;
+        nest(&tags, &mut next, None, source)
+    }
+}
+
+/// Symbols for `tags[next..]` that lie inside `container`, each with its own
+/// contents as children. `tags` is in document order, enclosing nodes first.
+fn nest(tags: &[Tag], next: &mut usize, container: Option<&Tag>, source: &str) -> Vec<DocumentSymbol> {
+    let mut symbols = Vec::new();
+    while let Some(tag) = tags.get(*next) {
+        if let Some(container) = container {
+            // `let (a, b)` or `var x, y` name several symbols with one node; those are siblings
+            let same_node = tag.node.byte_range() == container.node.byte_range();
+            if tag.node.end_byte() > container.node.end_byte() || same_node {
+                break;
+            }
+        }
+        *next += 1;
+        let children = nest(tags, next, Some(tag), source);
+        let name = tag.name_node.utf8_text(source.as_bytes()).unwrap_or_default();
+        symbols.push(DocumentSymbol {
+            name: name.split_whitespace().collect::<Vec<_>>().join(" "),
+            kind: tag.kind.clone(),
+            range: node_to_range(tag.node),
+            selection_range: node_to_range(tag.name_node),
+            children,
+            ..Default::default()
+        });
+    }
+    symbols
+}
+
+/// Outline kinds the frontend has icons for: LSP's `SymbolKind` names, in order.
+pub const OUTLINE_KINDS: &[&str] = &[
+    "file", "module", "namespace", "package", "class", "method", "property", "field",
+    "constructor", "enum", "interface", "function", "variable", "constant", "string",
+    "number", "boolean", "array", "object", "key", "null", "enummember", "struct",
+    "event", "operator", "typeparameter",
+];
+
+/// Outline kind for a `@definition.<kind>` capture.
+fn outline_kind(kind: &str) -> &str {
+    match kind {
+        "macro" => "function",
+        "type" | "implementation" => "class",
+        "trait" => "interface",
+        "union" => "struct",
+        "const" => "constant",
+        "var" | "let" => "variable",
+        "variant" | "enum_variant" => "enummember",
+        kind if OUTLINE_KINDS.contains(&kind) => kind,
+        _ => "variable",
+    }
+}
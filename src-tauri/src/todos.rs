@@ -0,0 +1,461 @@
+//! TODO/FIXME extraction for the todo panel.
+//!
+//! Comments are found with tree-sitter where a grammar is bundled and with the
+//! language's comment syntax otherwise; only comment text is searched for tags.
+//! Results are cached per workspace so rescans only re-read changed files, and
+//! saving a file refreshes just that file.
+
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tree_sitter::{Language, Node, Parser};
+
+use crate::error::{AppError, CmdResult};
+use crate::grammars::{self, Grammars};
+use crate::languages::{self, FileTypes};
+use crate::search::{self, SearchFilters, SearchScope};
+
+/// Used when settings.json has no `todos.tags`.
+const DEFAULT_TAGS: &[&str] = &["TODO", "FIXME", "HACK", "XXX"];
+
+/// Bigger files are almost always generated and are not scanned.
+const MAX_SCANNED_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+/// The `todos` section of settings.json: `"todos": { "tags": ["TODO", "NOTE"] }`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TodoSettings {
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoItem {
+    pub tag: String,
+    pub text: String,
+    /// From `TODO(alice)` or `TODO(@alice)`.
+    pub assignee: Option<String>,
+    /// From `TODO(#123)` or `TODO(PROJ-42)`.
+    pub issue: Option<String>,
+    pub path: String,
+    pub relative_path: String,
+    pub line_number: usize,
+    /// UTF-16, 1-based, at the tag.
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TodoGrouping {
+    #[default]
+    File,
+    Tag,
+    Assignee,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoGroup {
+    pub key: String,
+    pub items: Vec<TodoItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoScan {
+    pub groups: Vec<TodoGroup>,
+    pub total: usize,
+    pub files_scanned: usize,
+}
+
+/// Emitted as `todos_updated` when a saved file was rescanned.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoFileUpdate {
+    pub workspace_path: String,
+    pub path: String,
+    pub items: Vec<TodoItem>,
+}
+
+pub struct FileTodos {
+    modified: Option<SystemTime>,
+    size: u64,
+    items: Vec<TodoItem>,
+}
+
+pub struct WorkspaceTodos {
+    tags: Vec<String>,
+    files: HashMap<PathBuf, FileTodos>,
+}
+
+/// Last scan of each workspace, keyed by workspace path.
+pub type TodoCache = Arc<Mutex<HashMap<PathBuf, WorkspaceTodos>>>;
+
+fn load_tags(app_handle: &AppHandle) -> Vec<String> {
+    let tags = app_handle.path().app_config_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join("settings.json")).ok())
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|settings| settings.get("todos").cloned())
+        .and_then(|todos| serde_json::from_value::<TodoSettings>(todos).ok())
+        .map(|settings| settings.tags)
+        .unwrap_or_default();
+    let tags: Vec<String> = tags.into_iter().map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect();
+    if tags.is_empty() {
+        DEFAULT_TAGS.iter().map(|t| t.to_string()).collect()
+    } else {
+        tags
+    }
+}
+
+/// How comments look in languages without a bundled grammar.
+struct CommentSyntax {
+    line: &'static [&'static str],
+    block: &'static [(&'static str, &'static str)],
+    /// Whether `'`/`"` start strings that may contain comment markers.
+    quotes: bool,
+}
+
+const C_LIKE: CommentSyntax = CommentSyntax { line: &["//"], block: &[("/*", "*/")], quotes: true };
+const HASH: CommentSyntax = CommentSyntax { line: &["#"], block: &[], quotes: true };
+const MARKUP: CommentSyntax = CommentSyntax { line: &[], block: &[("<!--", "-->")], quotes: false };
+
+fn comment_syntax(language_id: &str) -> Option<CommentSyntax> {
+    let syntax = match language_id {
+        "c" | "cpp" | "csharp" | "java" | "kotlin" | "scala" | "swift" | "objective-c" | "dart" | "groovy"
+        | "zig" | "jsonc" | "proto" | "scss" | "less" | "javascript" | "javascriptreact" | "typescript"
+        | "typescriptreact" | "rust" | "go" => C_LIKE,
+        "css" => CommentSyntax { line: &[], block: &[("/*", "*/")], quotes: true },
+        "php" | "terraform" => CommentSyntax { line: &["//", "#"], block: &[("/*", "*/")], quotes: true },
+        "python" | "shellscript" | "fish" | "ruby" | "perl" | "yaml" | "toml" | "makefile" | "dockerfile"
+        | "cmake" | "r" | "julia" | "elixir" | "nix" | "graphql" | "dotenv" | "ignore" => HASH,
+        "powershell" => CommentSyntax { line: &["#"], block: &[("<#", "#>")], quotes: true },
+        "ini" => CommentSyntax { line: &[";", "#"], block: &[], quotes: false },
+        "sql" => CommentSyntax { line: &["--"], block: &[("/*", "*/")], quotes: true },
+        "lua" => CommentSyntax { line: &["--"], block: &[("--[[", "]]")], quotes: true },
+        "haskell" => CommentSyntax { line: &["--"], block: &[("{-", "-}")], quotes: true },
+        "ocaml" => CommentSyntax { line: &[], block: &[("(*", "*)")], quotes: true },
+        "erlang" | "latex" => CommentSyntax { line: &["%"], block: &[], quotes: false },
+        "clojure" => CommentSyntax { line: &[";"], block: &[], quotes: true },
+        "bat" => CommentSyntax { line: &["REM ", "rem ", "::"], block: &[], quotes: false },
+        "html" | "xml" | "markdown" | "vue" | "svelte" => MARKUP,
+        _ => return None,
+    };
+    Some(syntax)
+}
+
+/// Comment byte ranges from a parse: every node whose kind mentions "comment".
+fn tree_comments(language: &Language, source: &[u8]) -> Option<Vec<Range<usize>>> {
+    let mut parser = Parser::new();
+    parser.set_language(language).ok()?;
+    let tree = parser.parse(source, None)?;
+
+    let mut comments = Vec::new();
+    let mut stack: Vec<Node> = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if node.kind().contains("comment") {
+            comments.push(node.byte_range());
+            continue;
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+    comments.sort_by_key(|range| range.start);
+    Some(comments)
+}
+
+/// Whether a grammar has comment nodes at all, as [`tree_comments`] needs.
+fn has_comment_nodes(language: &Language) -> bool {
+    (0..language.node_kind_count() as u16)
+        .any(|id| language.node_kind_is_named(id) && language.node_kind_for_id(id).is_some_and(|kind| kind.contains("comment")))
+}
+
+/// Comment byte ranges found by scanning for the language's comment markers.
+/// Strings are tracked per line only, which is enough to skip `"// not a comment"`.
+fn heuristic_comments(syntax: &CommentSyntax, source: &[u8]) -> Vec<Range<usize>> {
+    let mut comments = Vec::new();
+    let mut quote: Option<u8> = None;
+    let mut pos = 0;
+    while pos < source.len() {
+        let byte = source[pos];
+        if byte == b'\n' {
+            quote = None;
+            pos += 1;
+            continue;
+        }
+        if let Some(open) = quote {
+            if byte == b'\\' {
+                pos += 2;
+                continue;
+            }
+            if byte == open {
+                quote = None;
+            }
+            pos += 1;
+            continue;
+        }
+        if syntax.quotes && (byte == b'"' || byte == b'\'' || byte == b'`') {
+            quote = Some(byte);
+            pos += 1;
+            continue;
+        }
+
+        let rest = &source[pos..];
+        // Checked before line markers so Lua's `--[[` wins over `--`
+        if let Some((open, close)) = syntax.block.iter().find(|(open, _)| rest.starts_with(open.as_bytes())) {
+            let body = pos + open.len();
+            let end = memchr::memmem::find(&source[body..], close.as_bytes())
+                .map_or(source.len(), |i| body + i + close.len());
+            comments.push(pos..end);
+            pos = end;
+            continue;
+        }
+        if syntax.line.iter().any(|marker| rest.starts_with(marker.as_bytes())) {
+            let end = memchr::memchr(b'\n', rest).map_or(source.len(), |i| pos + i);
+            comments.push(pos..end);
+            pos = end;
+            continue;
+        }
+        pos += 1;
+    }
+    comments
+}
+
+/// Matches one tag with an optional `(annotations)` and captures the rest of the line.
+fn tag_regex(tags: &[String]) -> Option<Regex> {
+    let alternatives: Vec<String> = tags.iter().map(|tag| regex::escape(tag)).collect();
+    Regex::new(&format!(r"\b({})\b(?:\(([^)]*)\))?:?(.*)", alternatives.join("|"))).ok()
+}
+
+/// Split `alice, #123` into an assignee and an issue reference.
+fn parse_annotations(annotations: &str) -> (Option<String>, Option<String>) {
+    let issue_key = Regex::new(r"^[A-Z][A-Z0-9]+-\d+$").expect("valid issue regex");
+    let mut assignee = None;
+    let mut issue = None;
+    for part in annotations.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let is_issue = part.strip_prefix('#').is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()))
+            || issue_key.is_match(part)
+            || part.contains("/issues/");
+        if is_issue {
+            issue.get_or_insert_with(|| part.to_string());
+        } else {
+            assignee.get_or_insert_with(|| part.trim_start_matches('@').to_string());
+        }
+    }
+    (assignee, issue)
+}
+
+fn clean_text(text: &str) -> String {
+    let mut text = text.trim();
+    for closer in ["*/", "-->", "#>", "-}", "*)", "]]"] {
+        text = text.strip_suffix(closer).unwrap_or(text).trim_end();
+    }
+    text.trim_start_matches(['-', ':']).trim().to_string()
+}
+
+fn scan_source(
+    source: &[u8],
+    comments: &[Range<usize>],
+    tags: &Regex,
+    path: &Path,
+    relative_path: &str,
+) -> Vec<TodoItem> {
+    let mut line_starts = vec![0];
+    line_starts.extend(memchr::memchr_iter(b'\n', source).map(|i| i + 1));
+
+    let mut items = Vec::new();
+    for comment in comments {
+        let Ok(text) = std::str::from_utf8(&source[comment.clone()]) else {
+            continue;
+        };
+        let mut offset = comment.start;
+        for line in text.split_inclusive('\n') {
+            if let Some(found) = tags.captures(line) {
+                let tag = found.get(1).map_or(0..0, |m| m.range());
+                let (assignee, issue) = found.get(2).map_or((None, None), |m| parse_annotations(m.as_str()));
+                let at = offset + tag.start;
+                let line_index = line_starts.partition_point(|start| *start <= at) - 1;
+                let prefix = String::from_utf8_lossy(&source[line_starts[line_index]..at]);
+                items.push(TodoItem {
+                    tag: line[tag].to_string(),
+                    text: clean_text(found.get(3).map_or("", |m| m.as_str())),
+                    assignee,
+                    issue,
+                    path: path.to_string_lossy().to_string(),
+                    relative_path: relative_path.to_string(),
+                    line_number: line_index + 1,
+                    column: prefix.encode_utf16().count() + 1,
+                });
+            }
+            offset += line.len();
+        }
+    }
+    items
+}
+
+/// Everything needed to scan files of one workspace, shared by all workers.
+struct Scanner {
+    tags: Regex,
+    file_types: FileTypes,
+    grammars: Arc<Grammars>,
+}
+
+impl Scanner {
+    fn new(tags: &[String], file_types: FileTypes) -> CmdResult<Self> {
+        let tags = tag_regex(tags).ok_or_else(|| AppError::invalid_input("Invalid TODO tags in settings"))?;
+        Ok(Scanner { tags, file_types, grammars: grammars::shared() })
+    }
+
+    fn scan_file(&self, path: &Path, relative_path: &str) -> Option<FileTodos> {
+        let metadata = fs::metadata(path).ok()?;
+        if metadata.len() > MAX_SCANNED_FILE_SIZE {
+            return None;
+        }
+        let source = fs::read(path).ok()?;
+        if memchr::memchr(0, &source[..source.len().min(8192)]).is_some() {
+            return None;
+        }
+
+        let language_id = self.file_types.language_for_path(path)?;
+        // Markdown's grammar has no comment node (`<!-- -->` is an HTML block), so
+        // its comments are found by their markers like those of unparsed languages
+        let grammar = self.grammars.for_language(&language_id).filter(|language| has_comment_nodes(language));
+        let comments = match grammar {
+            Some(language) => tree_comments(language, &source)?,
+            // Plain text has no comment syntax; all of it is searched
+            None if language_id == "plaintext" => std::iter::once(0..source.len()).collect(),
+            None => heuristic_comments(&comment_syntax(&language_id)?, &source),
+        };
+        Some(FileTodos {
+            modified: metadata.modified().ok(),
+            size: metadata.len(),
+            items: scan_source(&source, &comments, &self.tags, path, relative_path),
+        })
+    }
+}
+
+fn group_items(files: &HashMap<PathBuf, FileTodos>, grouping: TodoGrouping, tags: &[String]) -> Vec<TodoGroup> {
+    let mut groups: HashMap<String, Vec<TodoItem>> = HashMap::new();
+    for item in files.values().flat_map(|file| file.items.iter()) {
+        let key = match grouping {
+            TodoGrouping::File => item.relative_path.clone(),
+            TodoGrouping::Tag => item.tag.clone(),
+            TodoGrouping::Assignee => item.assignee.clone().unwrap_or_default(),
+        };
+        groups.entry(key).or_default().push(item.clone());
+    }
+
+    let mut groups: Vec<TodoGroup> = groups.into_iter()
+        .map(|(key, mut items)| {
+            items.sort_by(|a, b| a.relative_path.cmp(&b.relative_path).then(a.line_number.cmp(&b.line_number)));
+            TodoGroup { key, items }
+        })
+        .collect();
+    match grouping {
+        // Tags keep the order they are configured in
+        TodoGrouping::Tag => groups.sort_by_key(|g| tags.iter().position(|t| *t == g.key).unwrap_or(usize::MAX)),
+        // Unassigned items last
+        TodoGrouping::Assignee => groups.sort_by(|a, b| a.key.is_empty().cmp(&b.key.is_empty()).then(a.key.cmp(&b.key))),
+        TodoGrouping::File => groups.sort_by(|a, b| a.key.cmp(&b.key)),
+    }
+    groups
+}
+
+/// Scan the workspace for TODO comments and return them grouped and sorted.
+/// Files unchanged since the last scan are not read again.
+#[tauri::command(rename_all = "snake_case")]
+pub fn scan_todos(
+    workspace_path: String,
+    group_by: Option<TodoGrouping>,
+    cache: tauri::State<'_, TodoCache>,
+    app_handle: AppHandle,
+) -> CmdResult<TodoScan> {
+    let workspace = search::validate_workspace(&workspace_path)?;
+    let tags = load_tags(&app_handle);
+    let file_types = languages::load_file_types(&app_handle);
+    let scope = SearchScope::new(workspace, &SearchFilters::default(), file_types.clone())?;
+    let scanner = Scanner::new(&tags, file_types)?;
+
+    let previous = {
+        let mut cache = cache.lock().map_err(|_| AppError::lock_poisoned("todo cache"))?;
+        cache.remove(workspace).filter(|todos| todos.tags == tags).map(|todos| todos.files).unwrap_or_default()
+    };
+    let previous = Mutex::new(previous);
+    let files = Mutex::new(HashMap::new());
+
+    search::for_each_file(&scope, &|path, _| {
+        let unchanged = fs::metadata(path).ok().and_then(|metadata| {
+            let mut previous = previous.lock().ok()?;
+            let cached = previous.get(path)?;
+            if cached.size == metadata.len() && cached.modified == metadata.modified().ok() {
+                previous.remove(path)
+            } else {
+                None
+            }
+        });
+        let scanned = unchanged.or_else(|| scanner.scan_file(path, &scope.relative(path)));
+        if let (Some(todos), Ok(mut files)) = (scanned, files.lock()) {
+            files.insert(path.to_path_buf(), todos);
+        }
+        true
+    });
+
+    let files = files.into_inner().unwrap_or_default();
+    let scan = TodoScan {
+        groups: group_items(&files, group_by.unwrap_or_default(), &tags),
+        total: files.values().map(|f| f.items.len()).sum(),
+        files_scanned: files.len(),
+    };
+    let mut cache = cache.lock().map_err(|_| AppError::lock_poisoned("todo cache"))?;
+    cache.insert(workspace.to_path_buf(), WorkspaceTodos { tags, files });
+    Ok(scan)
+}
+
+/// Rescan one file after it was saved (or deleted) and emit `todos_updated`.
+/// Does nothing for workspaces that were never scanned.
+#[tauri::command(rename_all = "snake_case")]
+pub fn refresh_todos_for_file(
+    workspace_path: String,
+    file_path: String,
+    cache: tauri::State<'_, TodoCache>,
+    app_handle: AppHandle,
+) -> CmdResult<Vec<TodoItem>> {
+    let workspace = Path::new(&workspace_path);
+    let path = Path::new(&file_path);
+    let mut cache = cache.lock().map_err(|_| AppError::lock_poisoned("todo cache"))?;
+    let Some(todos) = cache.get_mut(workspace) else {
+        return Ok(Vec::new());
+    };
+    if !path.starts_with(workspace) {
+        return Ok(Vec::new());
+    }
+
+    let file_types = languages::load_file_types(&app_handle);
+    let scope = SearchScope::new(workspace, &SearchFilters::default(), file_types.clone())?;
+    let scanner = Scanner::new(&todos.tags, file_types)?;
+    let items = match path.is_file().then(|| scanner.scan_file(path, &scope.relative(path))).flatten() {
+        Some(file) => {
+            let items = file.items.clone();
+            todos.files.insert(path.to_path_buf(), file);
+            items
+        }
+        None => {
+            todos.files.remove(path);
+            Vec::new()
+        }
+    };
+
+    let _ = app_handle.emit("todos_updated", TodoFileUpdate {
+        workspace_path,
+        path: file_path,
+        items: items.clone(),
+    });
+    Ok(items)
+}
@@ -0,0 +1,769 @@
+//! Workspace-wide symbol table for "go to symbol in workspace" without a language
+//! server. Every file with a bundled grammar goes through the same tree-sitter
+//! extractors as the outline; the flattened result is kept per file.
+//!
+//! The table fills on a background thread and can be queried while it does. It is
+//! persisted under the app data directory so reopening a workspace only re-parses
+//! files that changed, and a file watcher keeps it current afterwards.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tree_sitter::{Parser, Point, Tree};
+
+use crate::error::{AppError, CmdResult};
+use crate::grammars::Grammars;
+use crate::languages::{self, FileTypes};
+use crate::search::{self, SearchFilters, SearchScope};
+use crate::queries::{Queries, QueryCache};
+use crate::tags;
+use crate::{DocumentSymbol, Range};
+
+/// Larger files are almost always generated or minified.
+const MAX_INDEXED_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+/// How often watcher events are folded into the table.
+const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// More queued changes than this (a branch switch, a large generator run) are
+/// cheaper to pick up with one walk of the workspace than file by file.
+const MAX_PENDING_CHANGES: usize = 5_000;
+
+/// Minimum time between writes of the persisted table.
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Bumped when extraction changes, so tables built by older versions are redone.
+const STORE_VERSION: u32 = 3;
+
+const DEFAULT_MAX_RESULTS: usize = 100;
+
+/// One symbol as stored; the file it belongs to is the key it is stored under.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedSymbol {
+    name: String,
+    kind: String,
+    container_name: Option<String>,
+    range: Range,
+    selection_range: Range,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileSymbols {
+    modified: Option<SystemTime>,
+    size: u64,
+    symbols: Vec<IndexedSymbol>,
+}
+
+/// On-disk form of the table, with paths relative to the workspace.
+#[derive(Serialize, Deserialize)]
+struct StoredTable {
+    version: u32,
+    root: PathBuf,
+    files: HashMap<String, FileSymbols>,
+}
+
+/// A match returned by `query_workspace_symbols`, best first.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSymbol {
+    pub name: String,
+    pub kind: String,
+    pub container_name: Option<String>,
+    pub path: String,
+    pub relative_path: String,
+    pub range: Range,
+    pub selection_range: Range,
+    pub score: i64,
+}
+
+/// Reported by `get_symbol_index_status`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolIndexStatus {
+    pub running: bool,
+    pub building: bool,
+    pub files_indexed: usize,
+    pub symbols: usize,
+    pub last_built_ms: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+pub type SymbolIndexes = Arc<Mutex<HashMap<PathBuf, Arc<SymbolIndexHandle>>>>;
+
+/// Parses files and turns their outline into table entries.
+struct Extractor {
+    file_types: FileTypes,
+    grammars: Grammars,
+    queries: Queries,
+}
+
+/// Languages whose outline is keys and markup rather than declarations; listing
+/// every `name` key of every package.json would drown the code symbols.
+const UNINDEXED_LANGUAGES: &[&str] = &["json", "jsonc", "yaml", "toml", "html", "markdown"];
+
+impl Extractor {
+    /// The language id of a file the index covers.
+    fn indexed_language(&self, path: &Path) -> Option<String> {
+        self.file_types.language_for_path(path)
+            .filter(|id| !UNINDEXED_LANGUAGES.contains(&id.as_str()))
+    }
+
+    fn has_grammar(&self, path: &Path) -> bool {
+        self.indexed_language(path)
+            .is_some_and(|id| self.grammars.for_language(&id).is_some())
+    }
+
+    fn extract(&self, path: &Path) -> Option<FileSymbols> {
+        let metadata = fs::metadata(path).ok()?;
+        if !metadata.is_file() || metadata.len() > MAX_INDEXED_FILE_SIZE {
+            return None;
+        }
+        let language_id = self.indexed_language(path)?;
+        let language = self.grammars.for_language(&language_id)?;
+        let bytes = fs::read(path).ok()?;
+        if memchr::memchr(0, &bytes[..bytes.len().min(8192)]).is_some() {
+            return None;
+        }
+        let source = String::from_utf8_lossy(&bytes);
+
+        let mut parser = Parser::new();
+        parser.set_language(language).ok()?;
+        let tree = parser.parse(source.as_ref(), None)?;
+        let tags = tags::tag_query(&self.queries, &language_id);
+        let outline = crate::extract_symbols_from_tree(&tree, &source, &language_id, &Vec::new(), tags.as_ref());
+        let mut symbols = Vec::new();
+        flatten(&outline, None, &tree, &mut symbols);
+        Some(FileSymbols { modified: metadata.modified().ok(), size: metadata.len(), symbols })
+    }
+}
+
+/// The outline also lists control flow (`if (x)`, `for loop`) and, for JavaScript,
+/// call sites. Those are skipped, but what is declared inside them is kept.
+fn flatten(outline: &[DocumentSymbol], container: Option<&str>, tree: &Tree, out: &mut Vec<IndexedSymbol>) {
+    for symbol in outline {
+        let name = symbol.name.strip_prefix("mut ").unwrap_or(&symbol.name);
+        if is_declaration(symbol, name, tree) {
+            out.push(IndexedSymbol {
+                name: name.to_string(),
+                kind: symbol.kind.clone(),
+                container_name: container.map(str::to_string),
+                range: symbol.range.clone(),
+                selection_range: symbol.selection_range.clone(),
+            });
+            flatten(&symbol.children, Some(name), tree, out);
+        } else {
+            flatten(&symbol.children, container, tree, out);
+        }
+    }
+}
+
+fn is_declaration(symbol: &DocumentSymbol, name: &str, tree: &Tree) -> bool {
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == '(') {
+        return false;
+    }
+    // Outline ranges are 1-based
+    let point = |line: u32, column: u32| Point::new(line.saturating_sub(1) as usize, column.saturating_sub(1) as usize);
+    let range = &symbol.range;
+    let node = tree.root_node().named_descendant_for_point_range(
+        point(range.start_line_number, range.start_column),
+        point(range.end_line_number, range.end_column),
+    );
+    !node.is_some_and(|node| {
+        let kind = node.kind();
+        kind.contains("call")
+            || kind.ends_with("_statement")
+            || matches!(kind, "if_expression" | "match_expression" | "for_expression" | "while_expression" | "loop_expression")
+    })
+}
+
+/// Chars of `query` must appear in `candidate` in order, ignoring case. The best
+/// alignment is chosen so that matches at word starts (`gts` in `getTableSize`)
+/// and runs of consecutive chars win over scattered ones.
+fn fuzzy_score(query: &[char], candidate: &str) -> Option<i64> {
+    const MATCH: i64 = 1;
+    const WORD_START: i64 = 8;
+    const CONSECUTIVE: i64 = 5;
+    const UNMATCHED: i64 = i64::MIN / 2;
+
+    let chars: Vec<char> = candidate.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+    if query.is_empty() || query.len() > lower.len() {
+        return None;
+    }
+    // Cheap rejection before the full alignment
+    let mut rest = lower.iter();
+    if !query.iter().all(|q| rest.any(|c| c == q)) {
+        return None;
+    }
+
+    let word_start = |i: usize| {
+        i == 0
+            || !chars[i - 1].is_alphanumeric()
+            || (chars[i - 1].is_lowercase() && chars[i].is_uppercase())
+            || (!chars[i - 1].is_ascii_digit() && chars[i].is_ascii_digit())
+    };
+    // best[i]: best score with the current query char matched at i
+    let mut previous = vec![UNMATCHED; lower.len()];
+    let mut best = vec![UNMATCHED; lower.len()];
+    for (j, q) in query.iter().enumerate() {
+        let mut best_before = UNMATCHED;
+        for i in 0..lower.len() {
+            best[i] = UNMATCHED;
+            if i >= 2 {
+                best_before = best_before.max(previous[i - 2]);
+            }
+            if lower[i] != *q {
+                continue;
+            }
+            let from = if j == 0 {
+                0
+            } else if i == 0 {
+                continue;
+            } else if previous[i - 1] > UNMATCHED {
+                best_before.max(previous[i - 1] + CONSECUTIVE)
+            } else {
+                best_before
+            };
+            if from <= UNMATCHED {
+                continue;
+            }
+            best[i] = from + MATCH + if word_start(i) { WORD_START } else { 0 };
+        }
+        std::mem::swap(&mut previous, &mut best);
+    }
+
+    let mut score = previous.into_iter().max().filter(|s| *s > UNMATCHED)?;
+    if lower.len() == query.len() && lower.iter().eq(query) {
+        score += 100;
+    } else if lower.starts_with(query) {
+        score += 50;
+    }
+    Some(score - (lower.len() - query.len()) as i64 / 4)
+}
+
+/// Score a symbol against the query. `Container.name` queries match both parts;
+/// `Container.` lists everything inside a container.
+fn score_symbol(name_query: &[char], container_query: Option<&[char]>, symbol: &IndexedSymbol) -> Option<i64> {
+    let container_score = match container_query {
+        Some(query) => fuzzy_score(query, symbol.container_name.as_deref()?)?,
+        None => 0,
+    };
+    let name_score = if name_query.is_empty() { 0 } else { fuzzy_score(name_query, &symbol.name)? };
+    Some(name_score + container_score)
+}
+
+#[derive(Default)]
+struct Bookkeeping {
+    last_built: Option<SystemTime>,
+    last_saved: Option<Instant>,
+    unsaved: bool,
+    last_error: Option<String>,
+}
+
+/// A live table: the symbols per file, the watcher feeding it, and the
+/// background threads that build and update it.
+pub struct SymbolIndexHandle {
+    scope: SearchScope,
+    extractor: Extractor,
+    store: Option<PathBuf>,
+    files: RwLock<HashMap<PathBuf, FileSymbols>>,
+    book: Mutex<Bookkeeping>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    building: AtomicBool,
+    rescan: AtomicBool,
+    /// Paths the watcher reported since the last update, re-extracted one by one.
+    pending: Mutex<HashSet<PathBuf>>,
+    /// The watcher lost track (dropped events, errors): walk the whole workspace.
+    stale: AtomicBool,
+    shutdown: AtomicBool,
+}
+
+impl SymbolIndexHandle {
+    /// Start indexing `root` in the background. With `store` the table is loaded
+    /// from there first and written back after changes.
+    pub fn start(root: &Path, file_types: FileTypes, queries: Queries, store: Option<PathBuf>) -> io::Result<Arc<Self>> {
+        let scope = SearchScope::new(root, &SearchFilters::default(), file_types.clone())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.message))?;
+        let handle = Arc::new(SymbolIndexHandle {
+            scope,
+            extractor: Extractor { file_types, grammars: Grammars::load(), queries },
+            store,
+            files: RwLock::new(HashMap::new()),
+            book: Mutex::new(Bookkeeping::default()),
+            watcher: Mutex::new(None),
+            building: AtomicBool::new(false),
+            rescan: AtomicBool::new(false),
+            pending: Mutex::new(HashSet::new()),
+            stale: AtomicBool::new(false),
+            shutdown: AtomicBool::new(false),
+        });
+
+        let weak = Arc::downgrade(&handle);
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Some(handle) = weak.upgrade() {
+                handle.on_watch_event(event);
+            }
+        })
+        .map_err(|e| io::Error::other(e.to_string()))?;
+        watcher.watch(root, RecursiveMode::Recursive)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        if let Ok(mut slot) = handle.watcher.lock() {
+            *slot = Some(watcher);
+        }
+
+        handle.rebuild();
+        let updater = Arc::downgrade(&handle);
+        std::thread::spawn(move || Self::run_updates(updater));
+        Ok(handle)
+    }
+
+    pub fn stop(&self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        if let Ok(mut watcher) = self.watcher.lock() {
+            watcher.take();
+        }
+        self.save(true);
+    }
+
+    fn on_watch_event(&self, event: notify::Result<notify::Event>) {
+        match event {
+            Ok(event) if event.need_rescan() => self.stale.store(true, Ordering::Release),
+            Ok(event) if event.kind.is_access() => {}
+            Ok(event) => {
+                let relevant: Vec<PathBuf> = event.paths.into_iter().filter(|p| self.is_relevant(p)).collect();
+                if !relevant.is_empty() {
+                    if let Ok(mut pending) = self.pending.lock() {
+                        pending.extend(relevant);
+                    }
+                }
+            }
+            Err(e) => {
+                if let Ok(mut book) = self.book.lock() {
+                    book.last_error = Some(format!("File watcher error: {}", e));
+                }
+                self.stale.store(true, Ordering::Release);
+            }
+        }
+    }
+
+    /// Whether a changed path can affect the table: a parseable source file outside
+    /// the always-skipped directories, or something that contained indexed files.
+    fn is_relevant(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(self.scope.root()) else {
+            return false;
+        };
+        let mut dirs = relative.parent().into_iter().flat_map(|p| p.components());
+        if dirs.any(|c| c.as_os_str().to_str().is_some_and(search::is_ignored_dir_name)) {
+            return false;
+        }
+        self.extractor.has_grammar(path)
+            || self.files.read().is_ok_and(|files| files.keys().any(|file| file.starts_with(path)))
+    }
+
+    /// Bring the table up to date with the workspace on a background thread. Only
+    /// files whose size or modification time changed are parsed again. Requests
+    /// that arrive while a pass runs are folded into one more pass.
+    pub fn rebuild(self: &Arc<Self>) {
+        self.rescan.store(true, Ordering::Release);
+        if self.building.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let handle = self.clone();
+        std::thread::spawn(move || {
+            handle.load();
+            while handle.rescan.swap(false, Ordering::AcqRel) && !handle.shutdown.load(Ordering::Relaxed) {
+                handle.refresh();
+            }
+            if let Ok(mut book) = handle.book.lock() {
+                book.last_built = Some(SystemTime::now());
+            }
+            handle.building.store(false, Ordering::Release);
+            handle.save(false);
+            // A request that came in after the last pass but before `building` was cleared
+            if handle.rescan.load(Ordering::Acquire) && !handle.shutdown.load(Ordering::Relaxed) {
+                handle.rebuild();
+            }
+        });
+    }
+
+    /// Fill an empty table from the persisted copy, if there is one.
+    fn load(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let Ok(mut files) = self.files.write() else {
+            return;
+        };
+        if !files.is_empty() {
+            return;
+        }
+        let stored = fs::read(store).ok()
+            .and_then(|bytes| serde_json::from_slice::<StoredTable>(&bytes).ok())
+            .filter(|stored| stored.version == STORE_VERSION && stored.root == self.scope.root());
+        if let Some(stored) = stored {
+            let root = self.scope.root();
+            files.extend(stored.files.into_iter().map(|(relative, file)| (root.join(relative), file)));
+        }
+    }
+
+    fn refresh(&self) {
+        let seen = Mutex::new(HashSet::new());
+        search::for_each_file(&self.scope, &|path, _| {
+            if self.shutdown.load(Ordering::Relaxed) {
+                return false;
+            }
+            if !self.extractor.has_grammar(path) {
+                return true;
+            }
+            if let Ok(mut seen) = seen.lock() {
+                seen.insert(path.to_path_buf());
+            }
+            let unchanged = fs::metadata(path).ok().is_some_and(|metadata| {
+                self.files.read().is_ok_and(|files| {
+                    files.get(path).is_some_and(|cached| {
+                        cached.size == metadata.len() && cached.modified == metadata.modified().ok()
+                    })
+                })
+            });
+            if !unchanged {
+                self.update_file(path);
+            }
+            true
+        });
+        if self.shutdown.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let seen = seen.into_inner().unwrap_or_default();
+        if let Ok(mut files) = self.files.write() {
+            let before = files.len();
+            files.retain(|path, _| seen.contains(path));
+            if files.len() != before {
+                self.mark_unsaved();
+            }
+        }
+    }
+
+    /// Parse one file again, or drop it from the table when it is gone or no longer
+    /// parseable.
+    pub fn update_file(&self, path: &Path) {
+        let extracted = self.extractor.extract(path);
+        if let Ok(mut files) = self.files.write() {
+            match extracted {
+                Some(file) => {
+                    files.insert(path.to_path_buf(), file);
+                }
+                None => {
+                    files.remove(path);
+                }
+            }
+        }
+        self.mark_unsaved();
+    }
+
+    /// Bring one path reported by the watcher up to date: a file is parsed again,
+    /// a directory that appeared is walked, and whatever no longer exists is dropped
+    /// along with everything below it.
+    fn update_path(&self, path: &Path) {
+        if path.is_dir() {
+            let files = Mutex::new(Vec::new());
+            search::for_each_file(&self.scope.clone().with_walk_root(path), &|file, _| {
+                if self.extractor.has_grammar(file) {
+                    if let Ok(mut files) = files.lock() {
+                        files.push(file.to_path_buf());
+                    }
+                }
+                true
+            });
+            for file in files.into_inner().unwrap_or_default() {
+                self.update_file(&file);
+            }
+        } else if path.is_file() {
+            if self.extractor.has_grammar(path) {
+                self.update_file(path);
+            }
+        } else if let Ok(mut files) = self.files.write() {
+            let before = files.len();
+            files.retain(|file, _| !file.starts_with(path));
+            if files.len() != before {
+                self.mark_unsaved();
+            }
+        }
+    }
+
+    /// Re-extract the files the watcher reported, or fall back to a full pass when
+    /// the watcher lost track or too much changed at once.
+    fn apply_pending(self: &Arc<Self>) {
+        let changed: Vec<PathBuf> = match self.pending.lock() {
+            Ok(mut pending) => pending.drain().collect(),
+            Err(_) => return,
+        };
+        if self.stale.swap(false, Ordering::AcqRel) || changed.len() > MAX_PENDING_CHANGES {
+            self.rebuild();
+            return;
+        }
+        for path in &changed {
+            if self.shutdown.load(Ordering::Relaxed) {
+                return;
+            }
+            self.update_path(path);
+        }
+    }
+
+    fn mark_unsaved(&self) {
+        if let Ok(mut book) = self.book.lock() {
+            book.unsaved = true;
+        }
+    }
+
+    fn run_updates(handle: Weak<Self>) {
+        loop {
+            std::thread::sleep(UPDATE_INTERVAL);
+            let Some(handle) = handle.upgrade() else {
+                return;
+            };
+            if handle.shutdown.load(Ordering::Relaxed) {
+                return;
+            }
+            // A full pass picks up queued changes by their modification times
+            if !handle.building.load(Ordering::Acquire) {
+                handle.apply_pending();
+                handle.save(false);
+            }
+        }
+    }
+
+    /// Write the table out if it changed, at most every `SAVE_INTERVAL` unless `force`.
+    fn save(&self, force: bool) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let due = self.book.lock().is_ok_and(|book| {
+            book.unsaved && (force || book.last_saved.is_none_or(|saved| saved.elapsed() >= SAVE_INTERVAL))
+        });
+        if !due {
+            return;
+        }
+
+        let root = self.scope.root();
+        let stored = match self.files.read() {
+            Ok(files) => StoredTable {
+                version: STORE_VERSION,
+                root: root.to_path_buf(),
+                files: files.iter()
+                    .filter_map(|(path, file)| Some((path.strip_prefix(root).ok()?.to_str()?.to_string(), file.clone())))
+                    .collect(),
+            },
+            Err(_) => return,
+        };
+        let result = serde_json::to_vec(&stored)
+            .map_err(io::Error::other)
+            .and_then(|bytes| {
+                if let Some(dir) = store.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                let tmp = store.with_extension("json.tmp");
+                fs::write(&tmp, bytes)?;
+                fs::rename(&tmp, store)
+            });
+        if let Ok(mut book) = self.book.lock() {
+            book.last_saved = Some(Instant::now());
+            match result {
+                Ok(()) => book.unsaved = false,
+                Err(e) => book.last_error = Some(format!("Failed to save symbol index: {}", e)),
+            }
+        }
+    }
+
+    /// Up to `max_results` symbols matching `query`, best first. Ties go to the
+    /// shorter name, then to the path.
+    pub fn query(&self, query: &str, max_results: usize) -> Vec<WorkspaceSymbol> {
+        let query = query.trim().to_lowercase();
+        let (container_query, name_query) = match query.rsplit_once('.') {
+            Some((container, name)) if !container.is_empty() => (Some(container.chars().collect::<Vec<_>>()), name),
+            _ => (None, query.as_str()),
+        };
+        let name_query: Vec<char> = name_query.chars().collect();
+        if name_query.is_empty() && container_query.is_none() {
+            return Vec::new();
+        }
+
+        let Ok(files) = self.files.read() else {
+            return Vec::new();
+        };
+        let mut scored: Vec<(i64, &Path, &IndexedSymbol)> = files.iter()
+            .flat_map(|(path, file)| file.symbols.iter().map(move |symbol| (path.as_path(), symbol)))
+            .filter_map(|(path, symbol)| {
+                Some((score_symbol(&name_query, container_query.as_deref(), symbol)?, path, symbol))
+            })
+            .collect();
+        scored.sort_by(|a, b| {
+            b.0.cmp(&a.0)
+                .then(a.2.name.len().cmp(&b.2.name.len()))
+                .then(a.1.cmp(b.1))
+                .then(a.2.range.start_line_number.cmp(&b.2.range.start_line_number))
+        });
+        scored.truncate(max_results);
+
+        scored.into_iter()
+            .map(|(score, path, symbol)| WorkspaceSymbol {
+                name: symbol.name.clone(),
+                kind: symbol.kind.clone(),
+                container_name: symbol.container_name.clone(),
+                path: path.to_string_lossy().into_owned(),
+                relative_path: self.scope.relative(path),
+                range: symbol.range.clone(),
+                selection_range: symbol.selection_range.clone(),
+                score,
+            })
+            .collect()
+    }
+
+    /// Every symbol named exactly `name`, by path and line; for go-to-definition
+    /// without a language server.
+    pub fn definitions(&self, name: &str) -> Vec<WorkspaceSymbol> {
+        let Ok(files) = self.files.read() else {
+            return Vec::new();
+        };
+        let mut found: Vec<WorkspaceSymbol> = files.iter()
+            .flat_map(|(path, file)| file.symbols.iter().map(move |symbol| (path, symbol)))
+            .filter(|(_, symbol)| symbol.name == name)
+            .map(|(path, symbol)| WorkspaceSymbol {
+                name: symbol.name.clone(),
+                kind: symbol.kind.clone(),
+                container_name: symbol.container_name.clone(),
+                path: path.to_string_lossy().into_owned(),
+                relative_path: self.scope.relative(path),
+                range: symbol.range.clone(),
+                selection_range: symbol.selection_range.clone(),
+                score: 0,
+            })
+            .collect();
+        found.sort_by(|a, b| a.path.cmp(&b.path).then(a.range.start_line_number.cmp(&b.range.start_line_number)));
+        found
+    }
+
+    pub fn status(&self) -> SymbolIndexStatus {
+        let mut status = SymbolIndexStatus::stopped();
+        status.running = true;
+        status.building = self.building.load(Ordering::Acquire);
+        if let Ok(files) = self.files.read() {
+            status.files_indexed = files.len();
+            status.symbols = files.values().map(|file| file.symbols.len()).sum();
+        }
+        if let Ok(book) = self.book.lock() {
+            status.last_error = book.last_error.clone();
+            status.last_built_ms = book.last_built
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64);
+        }
+        status
+    }
+}
+
+impl SymbolIndexStatus {
+    pub fn stopped() -> Self {
+        SymbolIndexStatus {
+            running: false,
+            building: false,
+            files_indexed: 0,
+            symbols: 0,
+            last_built_ms: None,
+            last_error: None,
+        }
+    }
+}
+
+pub fn find_index(indexes: &SymbolIndexes, workspace_path: &str) -> CmdResult<Option<Arc<SymbolIndexHandle>>> {
+    let indexes = indexes.lock().map_err(|_| AppError::lock_poisoned("symbol indexes"))?;
+    Ok(indexes.get(Path::new(workspace_path)).cloned())
+}
+
+/// Start indexing the workspace's symbols in the background, or catch an existing
+/// index up with the files on disk.
+#[tauri::command(rename_all = "snake_case")]
+pub fn start_symbol_index(
+    workspace_path: String,
+    indexes: tauri::State<'_, SymbolIndexes>,
+    query_cache: tauri::State<'_, QueryCache>,
+    app_handle: AppHandle,
+) -> CmdResult<SymbolIndexStatus> {
+    let workspace = search::validate_workspace(&workspace_path)?;
+    let mut indexes = indexes.lock().map_err(|_| AppError::lock_poisoned("symbol indexes"))?;
+    if let Some(handle) = indexes.get(workspace) {
+        handle.rebuild();
+        return Ok(handle.status());
+    }
+
+    let store = search::workspace_data_file(workspace, "symbol-index", "json", &app_handle)?;
+    let handle = SymbolIndexHandle::start(
+        workspace,
+        languages::load_file_types(&app_handle),
+        Queries::new(&query_cache, &app_handle),
+        Some(store),
+    )
+        .map_err(|e| AppError::io(e, "Failed to start symbol index for", workspace))?;
+    let status = handle.status();
+    indexes.insert(workspace.to_path_buf(), handle);
+    Ok(status)
+}
+
+/// Stop indexing the workspace. The persisted table is kept for next time.
+/// Returns `false` when no index was running.
+#[tauri::command(rename_all = "snake_case")]
+pub fn stop_symbol_index(workspace_path: String, indexes: tauri::State<'_, SymbolIndexes>) -> CmdResult<bool> {
+    let mut indexes = indexes.lock().map_err(|_| AppError::lock_poisoned("symbol indexes"))?;
+    let Some(handle) = indexes.remove(Path::new(&workspace_path)) else {
+        return Ok(false);
+    };
+    handle.stop();
+    Ok(true)
+}
+
+/// Fuzzy-match symbol names across the workspace. `Type.member` narrows by the
+/// containing symbol. Empty when the workspace has no running index.
+#[tauri::command(rename_all = "snake_case")]
+pub fn query_workspace_symbols(
+    workspace_path: String,
+    query: String,
+    max_results: Option<usize>,
+    indexes: tauri::State<'_, SymbolIndexes>,
+) -> CmdResult<Vec<WorkspaceSymbol>> {
+    Ok(find_index(&indexes, &workspace_path)?
+        .map(|handle| handle.query(&query, max_results.unwrap_or(DEFAULT_MAX_RESULTS)))
+        .unwrap_or_default())
+}
+
+/// Re-parse one file right after it was saved, without waiting for the watcher.
+#[tauri::command(rename_all = "snake_case")]
+pub fn update_symbol_index_for_file(
+    workspace_path: String,
+    file_path: String,
+    indexes: tauri::State<'_, SymbolIndexes>,
+) -> CmdResult<()> {
+    let path = Path::new(&file_path);
+    if let Some(handle) = find_index(&indexes, &workspace_path)? {
+        if path.starts_with(&workspace_path) {
+            handle.update_file(path);
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_symbol_index_status(
+    workspace_path: String,
+    indexes: tauri::State<'_, SymbolIndexes>,
+) -> CmdResult<SymbolIndexStatus> {
+    Ok(find_index(&indexes, &workspace_path)?
+        .map(|handle| handle.status())
+        .unwrap_or_else(SymbolIndexStatus::stopped))
+}
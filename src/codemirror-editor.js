@@ -1,12 +1,12 @@
 // codemirror-editor.js - CodeMirror 6 editor implementation with custom theming
 
-import { EditorView, lineNumbers, highlightActiveLineGutter, highlightSpecialChars, drawSelection, dropCursor, rectangularSelection, keymap, placeholder, scrollPastEnd } from '@codemirror/view';
-import { EditorState, Compartment } from '@codemirror/state';
+import { EditorView, Decoration, lineNumbers, highlightActiveLineGutter, highlightSpecialChars, drawSelection, dropCursor, rectangularSelection, keymap, placeholder, scrollPastEnd } from '@codemirror/view';
+import { EditorState, Compartment, StateField, StateEffect } from '@codemirror/state';
 import { foldGutter, indentOnInput, bracketMatching, foldKeymap, syntaxHighlighting, defaultHighlightStyle } from '@codemirror/language';
 import { history, defaultKeymap, historyKeymap } from '@codemirror/commands';
 import { highlightSelectionMatches, searchKeymap } from '@codemirror/search';
 import { autocompletion, completionKeymap, closeBrackets, closeBracketsKeymap, snippetCompletion, CompletionContext } from '@codemirror/autocomplete';
-import { lintKeymap } from '@codemirror/lint';
+import { lintKeymap, lintGutter, setDiagnostics } from '@codemirror/lint';
 import { javascript } from '@codemirror/lang-javascript';
 import { html } from '@codemirror/lang-html';
 import { css } from '@codemirror/lang-css';
@@ -22,6 +22,124 @@ import { oneDark } from '@codemirror/theme-one-dark';
 import { indentWithTab } from '@codemirror/commands';
 
 import { createCodeMirrorTheme, getCurrentTheme } from './theme-system.js';
+import { getLanguageForFile } from './languages.js';
+import { findReferences, getSyntaxDiagnostics } from './file-system.js';
+import { listenToLspMessages } from './tauri-helpers.js';
+
+// CodeMirror modes (see setLanguage) for the backend's language ids
+const CODEMIRROR_MODES = {
+  javascript: 'javascript',
+  javascriptreact: 'jsx',
+  typescript: 'typescript',
+  typescriptreact: 'tsx',
+  html: 'html',
+  xml: 'html', // Use HTML parser for XML
+  css: 'css',
+  scss: 'scss',
+  less: 'less',
+  json: 'json',
+  jsonc: 'json',
+  markdown: 'markdown',
+  python: 'python',
+  rust: 'rust',
+  go: 'go',
+  c: 'c',
+  cpp: 'cpp',
+  java: 'java',
+  php: 'php'
+};
+
+// Characters from each end of a document sent for language detection
+const DETECTION_SAMPLE_CHARS = 4096;
+
+// Delay after the cursor stops before its symbol's occurrences are looked up
+const OCCURRENCES_DELAY_MS = 250;
+
+// Occurrences of the symbol under the cursor, as found by the backend
+const setOccurrences = StateEffect.define();
+
+const occurrencesField = StateField.define({
+  create() {
+    return Decoration.none;
+  },
+  update(value, tr) {
+    // Stale as soon as the text changes; the next lookup replaces them
+    if (tr.docChanged) value = Decoration.none;
+    for (const effect of tr.effects) {
+      if (effect.is(setOccurrences)) {
+        value = Decoration.set(effect.value.map(({ from, to, isDefinition }) =>
+          Decoration.mark({ class: isDefinition ? 'cm-occurrence cm-occurrence-definition' : 'cm-occurrence' }).range(from, to)
+        ), true);
+      }
+    }
+    return value;
+  },
+  provide: field => EditorView.decorations.from(field)
+});
+
+// LSP DiagnosticSeverity (1-4) as CodeMirror lint severities
+const LINT_SEVERITIES = { 1: 'error', 2: 'warning', 3: 'info', 4: 'hint' };
+
+// The latest published diagnostics per file path, from the lsp_message event: a
+// language server's once it has published for the file, tree-sitter's syntax
+// errors (source "tree-sitter") until then
+const publishedDiagnostics = new Map(); // path -> { fromServer, version, diagnostics }
+let diagnosticsListener = null;
+
+function uriPath(uri) {
+  try {
+    return decodeURIComponent(uri.replace(/^file:\/\//, ''));
+  } catch (e) {
+    return uri;
+  }
+}
+
+// Keep a PublishDiagnosticsParams unless it is tree-sitter's for a file a
+// language server already reports on; tell the editor when it is kept
+function recordDiagnostics(params) {
+  if (!params?.uri) return;
+  const path = uriPath(params.uri);
+  const fromServer = params.source !== 'tree-sitter';
+  if (!fromServer && publishedDiagnostics.get(path)?.fromServer) return;
+  publishedDiagnostics.set(path, { fromServer, version: params.version, diagnostics: params.diagnostics || [] });
+  document.dispatchEvent(new CustomEvent('diagnostics-published', { detail: { path } }));
+}
+
+// One listener for the app, however often the editor is recreated
+function listenForDiagnostics() {
+  if (diagnosticsListener) return;
+  diagnosticsListener = listenToLspMessages(({ event, payload }) => {
+    if (event !== 'lsp_message') return;
+    let message;
+    try {
+      message = typeof payload === 'string' ? JSON.parse(payload) : payload;
+    } catch (e) {
+      return;
+    }
+    if (message?.method === 'textDocument/publishDiagnostics') {
+      recordDiagnostics(message.params);
+    }
+  }).catch(error => {
+    console.warn('[EDITOR] Diagnostics will not be shown:', error);
+    diagnosticsListener = null;
+  });
+}
+
+// The backend's columns count UTF-8 bytes, CodeMirror's count UTF-16 units
+function byteColumn(lineText, offset) {
+  return new TextEncoder().encode(lineText.slice(0, offset)).length;
+}
+
+function offsetForByteColumn(lineText, column) {
+  let bytes = 0;
+  for (let i = 0; i < lineText.length; i++) {
+    if (bytes >= column) return i;
+    const code = lineText.codePointAt(i);
+    bytes += code < 0x80 ? 1 : code < 0x800 ? 2 : code < 0x10000 ? 3 : 4;
+    if (code >= 0x10000) i++;
+  }
+  return lineText.length;
+}
 
 class CodeMirrorEditor {
   constructor(container, extensions = []) {
@@ -30,6 +148,15 @@ class CodeMirrorEditor {
     this.content = '';
     this.editor = null;
     this.view = null;
+    this.languageDetection = Promise.resolve('plaintext');
+    // The backend keeps a parse tree per document, which the outline, go to
+    // definition and occurrence highlighting read; edits are sent as they happen
+    this.syncedDocument = null; // { path, version }
+    this.documentSync = Promise.resolve();
+    this.occurrencesTimeout = null;
+    this.handleDiagnosticsPublished = this.handleDiagnosticsPublished.bind(this);
+    listenForDiagnostics();
+    document.addEventListener('diagnostics-published', this.handleDiagnosticsPublished);
     
     // Compartments for reconfigurable extensions
     this.themeCompartment = new Compartment();
@@ -95,6 +222,12 @@ class CodeMirrorEditor {
           mousedown: (event, view) => {
             // Get the position where the click occurred
             const pos = view.posAtCoords({ x: event.clientX, y: event.clientY });
+            // Ctrl/Cmd+Click goes to the definition of the name clicked
+            if (pos != null && event.button === 0 && (event.ctrlKey || event.metaKey) && this.currentFile?.path) {
+              this.requestDefinition(pos);
+              event.preventDefault();
+              return true;
+            }
             if (pos != null) {
               // Check if the click is beyond the document length
               const docLength = view.state.doc.length;
@@ -135,6 +268,12 @@ class CodeMirrorEditor {
         // Search highlighting
         highlightSelectionMatches(),
         
+        // Occurrences of the symbol under the cursor
+        occurrencesField,
+        
+        // Syntax errors, or a language server's diagnostics
+        lintGutter(),
+        
         // Key bindings
         keymap.of([
           ...closeBracketsKeymap,
@@ -157,15 +296,32 @@ class CodeMirrorEditor {
               this.currentFile.content = this.content;
             }
             
+            // Edits in document order, last first, so each one's offsets (UTF-16)
+            // still hold after the ones before it are applied. A wholesale
+            // setContent() is not an edit of the current file: `changes` is null.
+            let changes = null;
+            if (!this._settingContent) {
+              changes = [];
+              update.changes.iterChanges((fromA, toA, fromB, toB, inserted) => {
+                changes.unshift({ from: fromA, to: toA, text: inserted.toString() });
+              });
+            }
+            this.sendDocumentChanges(this.currentFile?.path, changes);
+
             // Dispatch content change event for diagnostics
             document.dispatchEvent(new CustomEvent('editor-content-changed', {
               detail: {
                 filePath: this.currentFile?.path,
-                content: this.content
+                content: this.content,
+                changes
               }
             }));
           }
           
+          if (update.docChanged || update.selectionSet) {
+            this.scheduleOccurrences();
+          }
+          
           // Note: Cursor scrolling is handled automatically by CodeMirror 6
           // Manual scrolling can cause issues, so we let CodeMirror handle it
         }),
@@ -219,13 +375,18 @@ class CodeMirrorEditor {
     this.content = validContent;
     
     if (this.view) {
-      this.view.dispatch({
-        changes: {
-          from: 0,
-          to: this.view.state.doc.length,
-          insert: validContent
-        }
-      });
+      this._settingContent = true;
+      try {
+        this.view.dispatch({
+          changes: {
+            from: 0,
+            to: this.view.state.doc.length,
+            insert: validContent
+          }
+        });
+      } finally {
+        this._settingContent = false;
+      }
     }
   }
   
@@ -241,16 +402,231 @@ class CodeMirrorEditor {
   setCurrentFile(file) {
     this.currentFile = file;
     
-    // Set language based on file extension
+    // Set language based on file extension, then refine it with the backend's
+    // detection (settings, modelines, shebangs) once that resolves
     if (this.view) {
       const language = this.getLanguageFromFileName(file.name);
       this.setLanguage(language);
+      this.languageDetection = getLanguageForFile(file.path || file.name, this.getDetectionSample());
+      this.languageDetection.then(languageId => {
+        const mode = CODEMIRROR_MODES[languageId];
+        // Keep the extension's mode when CodeMirror has none for the detected language
+        if (this.currentFile === file && mode && mode !== language) {
+          this.setLanguage(mode);
+        }
+      });
+      this.syncDocument().catch(error => {
+        console.warn('[EDITOR] Failed to open document in the backend:', error);
+      });
+      this.applyDiagnostics();
     }
     
     // Set focus
     this.focus();
   }
   
+  // The start and end of the document, where shebangs, modelines and file
+  // signatures are; detection needs no more than that
+  getDetectionSample() {
+    const doc = this.view.state.doc;
+    if (doc.length <= 2 * DETECTION_SAMPLE_CHARS) {
+      return doc.toString();
+    }
+    return doc.sliceString(0, DETECTION_SAMPLE_CHARS) + '\n' + doc.sliceString(doc.length - DETECTION_SAMPLE_CHARS);
+  }
+  
+  // Open the current file's document in the backend unless it is already in
+  // sync. Resolves once the backend has it, re-opening it if a queued edit was
+  // rejected in the meantime.
+  async syncDocument() {
+    const file = this.currentFile;
+    if (!file?.path) return;
+    const languageId = await this.languageDetection;
+    for (let attempt = 0; attempt < 2 && this.currentFile === file; attempt++) {
+      if (this.syncedDocument?.path !== file.path) {
+        this.openDocument(file.path, languageId);
+      }
+      await this.documentSync;
+      if (this.syncedDocument?.path === file.path) return;
+    }
+  }
+  
+  openDocument(filePath, languageId) {
+    const synced = { path: filePath, version: 0 };
+    const sourceCode = this.getContent();
+    this.syncedDocument = synced;
+    this.documentSync = this.documentSync
+      .catch(() => {})
+      .then(() => window.__TAURI__.core.invoke('open_document', {
+        file_path: filePath,
+        language_id: languageId,
+        source_code: sourceCode,
+        version: 0
+      }))
+      .then(() => {
+        // Published on open as well, but the event may have been missed while
+        // the listener was being set up
+        getSyntaxDiagnostics(filePath).then(recordDiagnostics).catch(() => {});
+      })
+      .catch(error => {
+        if (this.syncedDocument === synced) this.syncedDocument = null;
+        throw error;
+      });
+  }
+  
+  // Forward an edit to the backend's copy of the document. Anything that is not a
+  // plain edit of the synced document (file switch, setContent) drops the copy,
+  // and the next syncDocument() re-opens it with the full text.
+  sendDocumentChanges(filePath, changes) {
+    const synced = this.syncedDocument;
+    if (!synced || synced.path !== filePath || !changes) {
+      this.syncedDocument = null;
+      return;
+    }
+    if (changes.length === 0) return;
+    
+    const version = ++synced.version;
+    this.documentSync = this.documentSync
+      .then(() => window.__TAURI__.core.invoke('edit_document', { file_path: filePath, version, changes }))
+      .catch(error => {
+        console.warn('[EDITOR] Document edit rejected, re-syncing:', error);
+        if (this.syncedDocument === synced) this.syncedDocument = null;
+      });
+  }
+  
+  // Drop the backend's parse tree when a file is closed
+  closeDocument(filePath) {
+    if (!filePath) return;
+    if (this.syncedDocument?.path === filePath) this.syncedDocument = null;
+    publishedDiagnostics.delete(filePath);
+    this.documentSync = this.documentSync
+      .catch(() => {})
+      .then(() => window.__TAURI__.core.invoke('close_document', { file_path: filePath }))
+      .catch(() => {});
+  }
+  
+  handleDiagnosticsPublished(event) {
+    if (event.detail.path === this.currentFile?.path) {
+      this.applyDiagnostics();
+    }
+  }
+  
+  // Show the current file's published diagnostics in the lint layer
+  applyDiagnostics() {
+    if (!this.view) return;
+    const path = this.currentFile?.path;
+    const published = path ? publishedDiagnostics.get(path) : null;
+    // Syntax errors for an older version are about to be replaced
+    const synced = this.syncedDocument;
+    if (published && !published.fromServer && synced?.path === path && published.version !== synced.version) {
+      return;
+    }
+    
+    const doc = this.view.state.doc;
+    const offset = ({ line, character }) => {
+      if (line >= doc.lines) return doc.length;
+      const docLine = doc.line(line + 1);
+      return docLine.from + Math.min(character, docLine.length);
+    };
+    const diagnostics = (published?.diagnostics || []).map(diagnostic => {
+      const from = offset(diagnostic.range.start);
+      return {
+        from,
+        to: Math.max(from, offset(diagnostic.range.end)),
+        severity: LINT_SEVERITIES[diagnostic.severity] || 'error',
+        source: diagnostic.source,
+        message: diagnostic.message
+      };
+    });
+    this.view.dispatch(setDiagnostics(this.view.state, diagnostics));
+  }
+  
+  // The 1-based line and byte column the backend uses for a document offset
+  backendPosition(pos) {
+    const line = this.view.state.doc.lineAt(pos);
+    return { lineNumber: line.number, column: byteColumn(line.text, pos - line.from) + 1 };
+  }
+  
+  // Document offsets of a range from the backend
+  rangeOffsets(range) {
+    const doc = this.view.state.doc;
+    if (range.start_line_number > doc.lines || range.end_line_number > doc.lines) return null;
+    const offset = (lineNumber, column) => {
+      const line = doc.line(lineNumber);
+      return line.from + offsetForByteColumn(line.text, column - 1);
+    };
+    return {
+      from: offset(range.start_line_number, range.start_column),
+      to: offset(range.end_line_number, range.end_column)
+    };
+  }
+  
+  // Select a range from the backend and scroll it into the middle of the view
+  selectRange(range) {
+    if (!this.view) return;
+    const offsets = this.rangeOffsets(range);
+    if (!offsets) return;
+    this.view.dispatch({
+      selection: { anchor: offsets.from, head: offsets.to },
+      effects: EditorView.scrollIntoView(offsets.from, { y: 'center' })
+    });
+    this.focus();
+  }
+  
+  // Ask for the definition of the name at `pos`; main.js resolves it, since the
+  // definition may be in another file
+  async requestDefinition(pos) {
+    const filePath = this.currentFile.path;
+    const position = this.backendPosition(pos);
+    try {
+      await this.syncDocument();
+    } catch (error) {
+      console.warn('[EDITOR] Cannot look up definition:', error);
+      return;
+    }
+    document.dispatchEvent(new CustomEvent('editor-go-to-definition', {
+      detail: { filePath, ...position }
+    }));
+  }
+  
+  scheduleOccurrences() {
+    clearTimeout(this.occurrencesTimeout);
+    this.occurrencesTimeout = setTimeout(() => this.updateOccurrences(), OCCURRENCES_DELAY_MS);
+  }
+  
+  // Highlight the occurrences of the symbol under the cursor
+  async updateOccurrences() {
+    if (!this.view) return;
+    const filePath = this.currentFile?.path;
+    const selection = this.view.state.selection.main;
+    const doc = this.view.state.doc;
+    const clear = () => this.view?.dispatch({ effects: setOccurrences.of([]) });
+    if (!filePath || !selection.empty) {
+      clear();
+      return;
+    }
+    
+    let locations = [];
+    try {
+      await this.syncDocument();
+      const { lineNumber, column } = this.backendPosition(selection.head);
+      locations = await findReferences(filePath, lineNumber, column, true);
+    } catch (error) {
+      // Logged by findReferences; leave the text unmarked
+    }
+    // Drop the answer if the cursor or text moved on while it was computed
+    if (!this.view || this.currentFile?.path !== filePath || this.view.state.doc !== doc
+        || this.view.state.selection.main.head !== selection.head) {
+      return;
+    }
+    const marks = locations
+      .map(location => ({ ...this.rangeOffsets(location.range), isDefinition: location.is_definition }))
+      .filter(mark => mark.from != null && mark.from < mark.to)
+      .sort((a, b) => a.from - b.from);
+    // A lone occurrence is just the cursor's own name
+    this.view.dispatch({ effects: setOccurrences.of(marks.length > 1 ? marks : []) });
+  }
+  
   // Set the language mode
   setLanguage(languageName) {
     if (!this.view) return;
@@ -567,6 +943,8 @@ class CodeMirrorEditor {
   
   // Destroy the editor
   destroy() {
+    clearTimeout(this.occurrencesTimeout);
+    document.removeEventListener('diagnostics-published', this.handleDiagnosticsPublished);
     if (this.view) {
       this.view.destroy();
       this.view = null;
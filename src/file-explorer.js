@@ -512,6 +512,7 @@ class FileExplorer {
         { label: 'Duplicate', action: () => this.duplicateFolder(file.path), disabled: isMultipleFiles },
         { label: 'Rename', action: () => this.renameFile(file.path), disabled: isMultipleFiles },
         { label: '---', action: null }, // Separator
+        { label: 'Find in Folder...', action: () => document.dispatchEvent(new CustomEvent('find-in-folder', { detail: { path: file.path } })) },
         { label: 'Open in Default App', action: () => this.openInDefaultApp(file.path) },
         { label: 'Copy Path', action: () => navigator.clipboard.writeText(file.path) },
         { label: 'Delete Folder', action: () => this.deleteFiles(targetFiles) }
@@ -1,5 +1,22 @@
 // file-system.js - Handles file system operations
 
+// Backend commands reject with { code, message, path?, osError?, exitCode? }.
+// Wrap that in a real Error but keep the stable code for callers to branch on.
+function toError(error) {
+  if (error instanceof Error) {
+    return error;
+  }
+  if (error && typeof error === 'object' && error.code) {
+    const wrapped = new Error(error.message);
+    wrapped.code = error.code;
+    wrapped.path = error.path;
+    wrapped.osError = error.osError;
+    wrapped.exitCode = error.exitCode;
+    return wrapped;
+  }
+  return new Error(error);
+}
+
 // Generate a unique ID
 function generateId() {
   return Math.random().toString(36).substring(2, 15) + Math.random().toString(36).substring(2, 15);
@@ -11,7 +28,7 @@ async function readFile(filePath) {
     return await window.__TAURI__.core.invoke("read_text_file", { file_path: filePath });
   } catch (error) {
     console.error("Failed to read file:", error);
-    throw new Error(error);
+    throw toError(error);
   }
 }
 
@@ -22,7 +39,7 @@ async function writeFile(filePath, content) {
     return true;
   } catch (error) {
     console.error("Failed to write file:", error);
-    throw new Error(error);
+    throw toError(error);
   }
 }
 
@@ -63,7 +80,7 @@ async function readDirectory(folderPath) {
     return [...folders, ...entries];
   } catch (error) {
     console.error("Failed to read directory:", error);
-    throw new Error(error);
+    throw toError(error);
   }
 }
 
@@ -105,7 +122,7 @@ async function getWorkspaceFiles(workspacePath) {
     return await window.__TAURI__.core.invoke("get_workspace_files", { workspace_path: workspacePath });
   } catch (error) {
     console.error("Failed to get workspace files:", error);
-    throw new Error(error);
+    throw toError(error);
   }
 }
 
@@ -116,7 +133,7 @@ async function deleteFile(filePath) {
     return true;
   } catch (error) {
     console.error("Failed to delete file:", error);
-    throw new Error(error);
+    throw toError(error);
   }
 }
 
@@ -127,7 +144,7 @@ async function deleteDirectory(dirPath) {
     return true;
   } catch (error) {
     console.error("Failed to delete directory:", error);
-    throw new Error(error);
+    throw toError(error);
   }
 }
 
@@ -138,7 +155,7 @@ async function createDirectory(dirPath) {
     return true;
   } catch (error) {
     console.error("Failed to create directory:", error);
-    throw new Error(error);
+    throw toError(error);
   }
 }
 
@@ -149,7 +166,7 @@ async function renameFile(oldPath, newPath) {
     return true;
   } catch (error) {
     console.error("Failed to rename file:", error);
-    throw new Error(error);
+    throw toError(error);
   }
 }
 
@@ -160,7 +177,7 @@ async function copyFile(sourcePath, destPath) {
     return true;
   } catch (error) {
     console.error("Failed to copy file:", error);
-    throw new Error(error);
+    throw toError(error);
   }
 }
 
@@ -171,7 +188,7 @@ async function moveFile(sourcePath, destPath) {
     return true;
   } catch (error) {
     console.error("Failed to move file:", error);
-    throw new Error(error);
+    throw toError(error);
   }
 }
 
@@ -182,7 +199,7 @@ async function clipboardCopy(filePath) {
     return true;
   } catch (error) {
     console.error("Failed to copy to clipboard:", error);
-    throw new Error(error);
+    throw toError(error);
   }
 }
 
@@ -192,7 +209,7 @@ async function clipboardCut(filePath) {
     return true;
   } catch (error) {
     console.error("Failed to cut to clipboard:", error);
-    throw new Error(error);
+    throw toError(error);
   }
 }
 
@@ -202,7 +219,7 @@ async function clipboardPaste(targetDir) {
     return result;
   } catch (error) {
     console.error("Failed to paste from clipboard:", error);
-    throw new Error(error);
+    throw toError(error);
   }
 }
 
@@ -211,7 +228,7 @@ async function clipboardGetStatus() {
     return await window.__TAURI__.core.invoke("clipboard_get_status");
   } catch (error) {
     console.error("Failed to get clipboard status:", error);
-    throw new Error(error);
+    throw toError(error);
   }
 }
 
@@ -221,18 +238,22 @@ async function clipboardClear() {
     return true;
   } catch (error) {
     console.error("Failed to clear clipboard:", error);
-    throw new Error(error);
+    throw toError(error);
   }
 }
 
-// Search file contents with pattern
+// Search file contents with pattern. options.filters narrows the files searched:
+// { include, exclude, openFiles, respectGitignore, scope }. options.matchOptions shapes
+// each match: { beforeContext, afterContext, maxMatchesPerFile }
 async function searchInFiles(workspacePath, query, options = {}) {
   try {
     const {
       useRegex = false,
       caseSensitive = false,
       wholeWord = false,
-      maxResults = 100
+      maxResults = 100,
+      filters = null,
+      matchOptions = null
     } = options;
     
     return await window.__TAURI__.core.invoke("search_in_files", {
@@ -241,11 +262,535 @@ async function searchInFiles(workspacePath, query, options = {}) {
       use_regex: useRegex,
       case_sensitive: caseSensitive,
       whole_word: wholeWord,
-      max_results: maxResults
+      max_results: maxResults,
+      filters,
+      match_options: matchOptions
     });
   } catch (error) {
     console.error("Failed to search in files:", error);
-    throw new Error(error);
+    throw toError(error);
+  }
+}
+
+// Stream a workspace search as a cancellable background job. Per-file batches go
+// to onResults as they arrive; resolves with the backend's final summary.
+async function startWorkspaceSearch(searchId, workspacePath, query, options = {}, onResults = () => {}) {
+  const {
+    useRegex = false,
+    caseSensitive = false,
+    wholeWord = false,
+    maxResults = 100,
+    filters = null,
+    matchOptions = null
+  } = options;
+  const { listen } = window.__TAURI__.event;
+
+  let resolveFinished;
+  const finished = new Promise(resolve => { resolveFinished = resolve; });
+
+  const unlistenResults = await listen("search_results", (event) => {
+    if (event.payload.searchId === searchId) {
+      onResults(event.payload.results);
+    }
+  });
+  const unlistenFinished = await listen("search_finished", (event) => {
+    if (event.payload.searchId !== searchId) return;
+    unlistenResults();
+    unlistenFinished();
+    resolveFinished(event.payload);
+  });
+
+  try {
+    await window.__TAURI__.core.invoke("start_workspace_search", {
+      search_id: searchId,
+      workspace_path: workspacePath,
+      query,
+      use_regex: useRegex,
+      case_sensitive: caseSensitive,
+      whole_word: wholeWord,
+      max_results: maxResults,
+      filters,
+      match_options: matchOptions
+    });
+  } catch (error) {
+    unlistenResults();
+    unlistenFinished();
+    console.error("Failed to start workspace search:", error);
+    throw toError(error);
+  }
+
+  return finished;
+}
+
+// Cancel a running workspace search; resolves to false if it already finished
+async function cancelWorkspaceSearch(searchId) {
+  try {
+    return await window.__TAURI__.core.invoke("cancel_workspace_search", { search_id: searchId });
+  } catch (error) {
+    console.error("Failed to cancel workspace search:", error);
+    throw toError(error);
+  }
+}
+
+// Search git history as a cancellable background job (cancel it with
+// cancelWorkspaceSearch). mode is "pickaxe" (added/removed lines), "message" or
+// "blobs" (every file version in revRange). Matching commits go to onCommits as
+// they are found; resolves with the final summary, whose error is set if git failed.
+async function startHistorySearch(searchId, repoPath, query, options = {}, onCommits = () => {}) {
+  const {
+    useRegex = false,
+    caseSensitive = false,
+    wholeWord = false,
+    mode = 'pickaxe',
+    revRange = null,
+    paths = [],
+    maxCommits = 200
+  } = options;
+  const { listen } = window.__TAURI__.event;
+
+  let resolveFinished;
+  const finished = new Promise(resolve => { resolveFinished = resolve; });
+
+  const unlistenResults = await listen("history_search_results", (event) => {
+    if (event.payload.searchId === searchId) {
+      onCommits(event.payload.commits);
+    }
+  });
+  const unlistenFinished = await listen("history_search_finished", (event) => {
+    if (event.payload.searchId !== searchId) return;
+    unlistenResults();
+    unlistenFinished();
+    resolveFinished(event.payload);
+  });
+
+  try {
+    await window.__TAURI__.core.invoke("start_history_search", {
+      search_id: searchId,
+      repo_path: repoPath,
+      query,
+      use_regex: useRegex,
+      case_sensitive: caseSensitive,
+      whole_word: wholeWord,
+      options: { mode, revRange, paths, maxCommits }
+    });
+  } catch (error) {
+    unlistenResults();
+    unlistenFinished();
+    console.error("Failed to start history search:", error);
+    throw toError(error);
+  }
+
+  return finished;
+}
+
+// Structural search with a tree-sitter query or a code pattern with $METAVARIABLES,
+// e.g. "$X.lock().unwrap()" for rust. Resolves with { results, filesScanned, matches, truncated }.
+async function structuralSearch(workspacePath, pattern, languageId, options = {}) {
+  try {
+    return await window.__TAURI__.core.invoke("structural_search", {
+      workspace_path: workspacePath,
+      pattern,
+      language_id: languageId,
+      max_results: options.maxResults || 1000,
+      filters: options.filters || null
+    });
+  } catch (error) {
+    console.error("Failed to run structural search:", error);
+    throw toError(error);
+  }
+}
+
+// TODO/FIXME comments in the workspace, grouped by "file", "tag" or "assignee".
+// Resolves with { groups: [{ key, items }], total, filesScanned }.
+async function scanTodos(workspacePath, groupBy = "file") {
+  try {
+    return await window.__TAURI__.core.invoke("scan_todos", {
+      workspace_path: workspacePath,
+      group_by: groupBy
+    });
+  } catch (error) {
+    console.error("Failed to scan TODOs:", error);
+    throw toError(error);
+  }
+}
+
+// Rescan one file after saving it; listeners of "todos_updated" get the new items
+async function refreshTodosForFile(workspacePath, filePath) {
+  try {
+    return await window.__TAURI__.core.invoke("refresh_todos_for_file", {
+      workspace_path: workspacePath,
+      file_path: filePath
+    });
+  } catch (error) {
+    console.error("Failed to refresh TODOs:", error);
+    throw toError(error);
+  }
+}
+
+// Start the background symbol index for the workspace, or catch it up with disk
+async function startSymbolIndex(workspacePath) {
+  try {
+    return await window.__TAURI__.core.invoke("start_symbol_index", {
+      workspace_path: workspacePath
+    });
+  } catch (error) {
+    console.error("Failed to start symbol index:", error);
+    throw toError(error);
+  }
+}
+
+async function stopSymbolIndex(workspacePath) {
+  try {
+    return await window.__TAURI__.core.invoke("stop_symbol_index", {
+      workspace_path: workspacePath
+    });
+  } catch (error) {
+    console.error("Failed to stop symbol index:", error);
+    throw toError(error);
+  }
+}
+
+// Fuzzy symbol search across the workspace; "Type.member" narrows by container
+async function queryWorkspaceSymbols(workspacePath, query, maxResults = 100) {
+  try {
+    return await window.__TAURI__.core.invoke("query_workspace_symbols", {
+      workspace_path: workspacePath,
+      query,
+      max_results: maxResults
+    });
+  } catch (error) {
+    console.error("Failed to query workspace symbols:", error);
+    throw toError(error);
+  }
+}
+
+async function updateSymbolIndexForFile(workspacePath, filePath) {
+  try {
+    return await window.__TAURI__.core.invoke("update_symbol_index_for_file", {
+      workspace_path: workspacePath,
+      file_path: filePath
+    });
+  } catch (error) {
+    console.error("Failed to update symbol index:", error);
+    throw toError(error);
+  }
+}
+
+async function getSymbolIndexStatus(workspacePath) {
+  try {
+    return await window.__TAURI__.core.invoke("get_symbol_index_status", {
+      workspace_path: workspacePath
+    });
+  } catch (error) {
+    console.error("Failed to get symbol index status:", error);
+    throw toError(error);
+  }
+}
+
+// Start the semantic index with the embedding endpoint from settings.semanticSearch
+async function startSemanticIndex(workspacePath) {
+  try {
+    return await window.__TAURI__.core.invoke("start_semantic_index", {
+      workspace_path: workspacePath
+    });
+  } catch (error) {
+    console.error("Failed to start semantic index:", error);
+    throw toError(error);
+  }
+}
+
+async function stopSemanticIndex(workspacePath, deleteFromDisk = false) {
+  try {
+    return await window.__TAURI__.core.invoke("stop_semantic_index", {
+      workspace_path: workspacePath,
+      delete_from_disk: deleteFromDisk
+    });
+  } catch (error) {
+    console.error("Failed to stop semantic index:", error);
+    throw toError(error);
+  }
+}
+
+// Code chunks closest in meaning to a natural-language query, best first
+async function semanticSearch(workspacePath, query, maxResults = 20) {
+  try {
+    return await window.__TAURI__.core.invoke("semantic_search", {
+      workspace_path: workspacePath,
+      query,
+      max_results: maxResults
+    });
+  } catch (error) {
+    console.error("Semantic search failed:", error);
+    throw toError(error);
+  }
+}
+
+async function updateSemanticIndexForFile(workspacePath, filePath) {
+  try {
+    return await window.__TAURI__.core.invoke("update_semantic_index_for_file", {
+      workspace_path: workspacePath,
+      file_path: filePath
+    });
+  } catch (error) {
+    console.error("Failed to update semantic index:", error);
+    throw toError(error);
+  }
+}
+
+async function getSemanticIndexStatus(workspacePath) {
+  try {
+    return await window.__TAURI__.core.invoke("get_semantic_index_status", {
+      workspace_path: workspacePath
+    });
+  } catch (error) {
+    console.error("Failed to get semantic index status:", error);
+    throw toError(error);
+  }
+}
+
+// Rescan the grammars directory under the app data dir and list the installed
+// grammars, with their ABI version and whether this build can load them.
+async function listInstalledGrammars() {
+  try {
+    return await window.__TAURI__.core.invoke("list_installed_grammars");
+  } catch (error) {
+    console.error("Failed to list installed grammars:", error);
+    throw toError(error);
+  }
+}
+
+// Tree-sitter highlight tokens for an open document (see outline.js for how
+// documents are opened), limited to the visible range when from/to are given.
+// Offsets are UTF-16, as in CodeMirror; each token has a capture name such as
+// "keyword" or "string.special" to map to a theme color.
+async function highlightDocument(filePath, from = null, to = null) {
+  try {
+    return await window.__TAURI__.core.invoke("highlight_document", {
+      file_path: filePath,
+      from,
+      to
+    });
+  } catch (error) {
+    console.error("Failed to highlight document:", error);
+    throw toError(error);
+  }
+}
+
+// Highlight tokens for code that is not open, e.g. previews and exports.
+async function highlightSource(sourceCode, languageId) {
+  try {
+    return await window.__TAURI__.core.invoke("highlight_source", {
+      source_code: sourceCode,
+      language_id: languageId
+    });
+  } catch (error) {
+    console.error("Failed to highlight source:", error);
+    throw toError(error);
+  }
+}
+
+// Language at a UTF-16 offset of an open document: the embedded language inside
+// a <script>, a code fence or a css`...` template, else the document's own.
+async function getLanguageAtPosition(filePath, offset) {
+  try {
+    return await window.__TAURI__.core.invoke("get_language_at_position", {
+      file_path: filePath,
+      offset
+    });
+  } catch (error) {
+    console.error("Failed to get language at position:", error);
+    throw toError(error);
+  }
+}
+
+// Folding ranges of an open document from its syntax tree: blocks, comment
+// runs ("comment") and import runs ("imports"), embedded code included.
+async function getFoldingRanges(filePath) {
+  try {
+    return await window.__TAURI__.core.invoke("get_folding_ranges", {
+      file_path: filePath
+    });
+  } catch (error) {
+    console.error("Failed to get folding ranges:", error);
+    throw toError(error);
+  }
+}
+
+// Ranges of the syntax nodes enclosing a 1-based position, innermost first, for
+// expand/shrink selection.
+async function getSelectionRanges(filePath, lineNumber, column) {
+  try {
+    return await window.__TAURI__.core.invoke("get_selection_ranges", {
+      file_path: filePath,
+      line_number: lineNumber,
+      column
+    });
+  } catch (error) {
+    console.error("Failed to get selection ranges:", error);
+    throw toError(error);
+  }
+}
+
+// Symbols enclosing a 1-based position of an open document, outermost first,
+// for the breadcrumb bar and sticky scroll. Cheap enough for every cursor move.
+async function getBreadcrumbs(filePath, lineNumber, column) {
+  try {
+    return await window.__TAURI__.core.invoke("get_breadcrumbs", {
+      file_path: filePath,
+      line_number: lineNumber,
+      column
+    });
+  } catch (error) {
+    console.error("Failed to get breadcrumbs:", error);
+    throw toError(error);
+  }
+}
+
+// Syntax errors of an open document as LSP PublishDiagnosticsParams. They are
+// also pushed as publishDiagnostics notifications on the lsp_message event after
+// every open and edit; both carry source "tree-sitter".
+async function getSyntaxDiagnostics(filePath) {
+  try {
+    return await window.__TAURI__.core.invoke("get_syntax_diagnostics", {
+      file_path: filePath
+    });
+  } catch (error) {
+    console.error("Failed to get syntax diagnostics:", error);
+    throw toError(error);
+  }
+}
+
+// Best-effort definition of the name at a 1-based position when no language
+// server runs. Each location has a confidence: "high" (resolved through scopes),
+// "medium" (same name in the file) or "low" (same name in the workspace index,
+// when workspacePath has one running).
+async function findDefinition(filePath, lineNumber, column, workspacePath = null) {
+  try {
+    return await window.__TAURI__.core.invoke("find_definition", {
+      file_path: filePath,
+      line_number: lineNumber,
+      column,
+      workspace_path: workspacePath
+    });
+  } catch (error) {
+    console.error("Failed to find definition:", error);
+    throw toError(error);
+  }
+}
+
+// Occurrences of the name at a 1-based position within the file, with the same
+// confidence levels; with the declaration included, also what to highlight.
+async function findReferences(filePath, lineNumber, column, includeDeclaration = true) {
+  try {
+    return await window.__TAURI__.core.invoke("find_references", {
+      file_path: filePath,
+      line_number: lineNumber,
+      column,
+      include_declaration: includeDeclaration
+    });
+  } catch (error) {
+    console.error("Failed to find references:", error);
+    throw toError(error);
+  }
+}
+
+// Build (or load) the workspace's trigram search index. Searches use it once it
+// is ready and fall back to a full scan while it is building or stale.
+async function enableSearchIndex(workspacePath, persist = true) {
+  try {
+    return await window.__TAURI__.core.invoke("enable_search_index", {
+      workspace_path: workspacePath,
+      persist
+    });
+  } catch (error) {
+    console.error("Failed to enable search index:", error);
+    throw toError(error);
+  }
+}
+
+async function disableSearchIndex(workspacePath, deleteFromDisk = false) {
+  try {
+    return await window.__TAURI__.core.invoke("disable_search_index", {
+      workspace_path: workspacePath,
+      delete_from_disk: deleteFromDisk
+    });
+  } catch (error) {
+    console.error("Failed to disable search index:", error);
+    throw toError(error);
+  }
+}
+
+// Resolves with { state, filesIndexed, trigrams, pendingChanges, memoryBytes, diskBytes, ... }
+async function getSearchIndexStatus(workspacePath) {
+  try {
+    return await window.__TAURI__.core.invoke("get_search_index_status", { workspace_path: workspacePath });
+  } catch (error) {
+    console.error("Failed to get search index status:", error);
+    throw toError(error);
+  }
+}
+
+// Compute a project-wide replace without writing anything. Resolves with the
+// preview ({ previewId, files, totalEdits, truncated, skippedFiles }) to show to
+// the user; skippedFiles lists matching files left out, each with a reason.
+async function previewReplace(workspacePath, query, replacement, options = {}) {
+  try {
+    const {
+      useRegex = false,
+      caseSensitive = false,
+      wholeWord = false,
+      preserveCase = false,
+      maxResults = 1000,
+      filters = null
+    } = options;
+
+    return await window.__TAURI__.core.invoke("preview_replace", {
+      workspace_path: workspacePath,
+      query,
+      replacement,
+      use_regex: useRegex,
+      case_sensitive: caseSensitive,
+      whole_word: wholeWord,
+      preserve_case: preserveCase,
+      max_results: maxResults,
+      filters
+    });
+  } catch (error) {
+    console.error("Failed to preview replace:", error);
+    throw toError(error);
+  }
+}
+
+// Apply a preview, skipping the edit ids in excludedEdits. Rejects with a
+// Conflict error (and writes nothing) if a file changed since the preview.
+async function applyReplace(previewId, excludedEdits = []) {
+  try {
+    return await window.__TAURI__.core.invoke("apply_replace", {
+      preview_id: previewId,
+      excluded: excludedEdits
+    });
+  } catch (error) {
+    console.error("Failed to apply replace:", error);
+    throw toError(error);
+  }
+}
+
+// Revert every file changed by one applyReplace call
+async function undoReplace(undoId) {
+  try {
+    return await window.__TAURI__.core.invoke("undo_replace", { undo_id: undoId });
+  } catch (error) {
+    console.error("Failed to undo replace:", error);
+    throw toError(error);
+  }
+}
+
+async function discardReplacePreview(previewId) {
+  try {
+    return await window.__TAURI__.core.invoke("discard_replace_preview", { preview_id: previewId });
+  } catch (error) {
+    console.error("Failed to discard replace preview:", error);
+    throw toError(error);
   }
 }
 
@@ -269,5 +814,38 @@ export {
   clipboardPaste,
   clipboardGetStatus,
   clipboardClear,
-  searchInFiles
+  searchInFiles,
+  startWorkspaceSearch,
+  cancelWorkspaceSearch,
+  startHistorySearch,
+  structuralSearch,
+  scanTodos,
+  refreshTodosForFile,
+  startSymbolIndex,
+  stopSymbolIndex,
+  queryWorkspaceSymbols,
+  updateSymbolIndexForFile,
+  getSymbolIndexStatus,
+  startSemanticIndex,
+  stopSemanticIndex,
+  semanticSearch,
+  updateSemanticIndexForFile,
+  getSemanticIndexStatus,
+  listInstalledGrammars,
+  highlightDocument,
+  highlightSource,
+  getLanguageAtPosition,
+  getFoldingRanges,
+  getSelectionRanges,
+  getBreadcrumbs,
+  getSyntaxDiagnostics,
+  findDefinition,
+  findReferences,
+  enableSearchIndex,
+  disableSearchIndex,
+  getSearchIndexStatus,
+  previewReplace,
+  applyReplace,
+  undoReplace,
+  discardReplacePreview
 };
\ No newline at end of file
@@ -431,7 +431,7 @@ class GitPanel {
       await this.checkGitRepository();
     } catch (error) {
       console.error('Error initializing git repository:', error);
-      this.showNotification('Failed to initialize git repository: ' + error, 'error');
+      this.showNotification('Failed to initialize git repository: ' + (error?.message ?? error), 'error');
     }
   }
 
@@ -448,7 +448,7 @@ class GitPanel {
       await this.refreshStatus();
     } catch (error) {
       console.error('Error staging file:', error);
-      this.showNotification('Failed to stage file: ' + error, 'error');
+      this.showNotification('Failed to stage file: ' + (error?.message ?? error), 'error');
     }
   }
 
@@ -465,7 +465,7 @@ class GitPanel {
       await this.refreshStatus();
     } catch (error) {
       console.error('Error unstaging file:', error);
-      this.showNotification('Failed to unstage file: ' + error, 'error');
+      this.showNotification('Failed to unstage file: ' + (error?.message ?? error), 'error');
     }
   }
 
@@ -486,7 +486,7 @@ class GitPanel {
       await this.refreshStatus();
     } catch (error) {
       console.error('Error staging all files:', error);
-      this.showNotification('Failed to stage all files: ' + error, 'error');
+      this.showNotification('Failed to stage all files: ' + (error?.message ?? error), 'error');
     }
   }
 
@@ -507,7 +507,7 @@ class GitPanel {
       await this.refreshStatus();
     } catch (error) {
       console.error('Error unstaging all files:', error);
-      this.showNotification('Failed to unstage all files: ' + error, 'error');
+      this.showNotification('Failed to unstage all files: ' + (error?.message ?? error), 'error');
     }
   }
 
@@ -530,7 +530,7 @@ class GitPanel {
       await this.refreshStatus();
     } catch (error) {
       console.error('Error committing:', error);
-      this.showNotification('Failed to commit: ' + error, 'error');
+      this.showNotification('Failed to commit: ' + (error?.message ?? error), 'error');
     }
   }
 
@@ -561,7 +561,7 @@ class GitPanel {
       await this.refreshStatus();
     } catch (error) {
       console.error('Error committing and pushing:', error);
-      this.showNotification('Failed to commit and push: ' + error, 'error');
+      this.showNotification('Failed to commit and push: ' + (error?.message ?? error), 'error');
     }
   }
 
@@ -586,7 +586,7 @@ class GitPanel {
       await this.refreshStatus();
     } catch (error) {
       console.error('Error amending commit:', error);
-      this.showNotification('Failed to amend commit: ' + error, 'error');
+      this.showNotification('Failed to amend commit: ' + (error?.message ?? error), 'error');
     }
   }
 
@@ -622,7 +622,7 @@ class GitPanel {
       await this.refreshStatus();
     } catch (error) {
       console.error('Error syncing:', error);
-      this.showNotification('Failed to sync: ' + error, 'error');
+      this.showNotification('Failed to sync: ' + (error?.message ?? error), 'error');
     }
   }
 
@@ -634,7 +634,7 @@ class GitPanel {
         await window.fileExplorer.openFileByPath(fullPath);
       } catch (error) {
         console.error('Error opening file:', error);
-        this.showNotification('Failed to open file: ' + error, 'error');
+        this.showNotification('Failed to open file: ' + (error?.message ?? error), 'error');
       }
     }
   }
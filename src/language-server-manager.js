@@ -1,6 +1,8 @@
 // Language Server Manager - Simplified version that provides server configurations
 // Based on https://microsoft.github.io/language-server-protocol/implementors/servers/
 
+import { getLanguageForFile } from './languages.js';
+
 class LanguageServerManager {
   constructor() {
     this.serverConfigs = new Map();
@@ -223,6 +225,12 @@ class LanguageServerManager {
   getAvailableServers(languageId) {
     return this.serverConfigs.get(languageId) || [];
   }
+  
+  // Get server configurations for a file, resolving its language via the shared registry
+  async getServerConfigsForFile(filePath) {
+    const languageId = await getLanguageForFile(filePath);
+    return this.getServerConfigs(languageId);
+  }
 }
 
 export default LanguageServerManager;
\ No newline at end of file
@@ -0,0 +1,33 @@
+// languages.js - Shared language ids from the backend language registry
+//
+// Search results, the outline, language servers and highlighting all resolve
+// languages through the backend so that `files.associations` in settings,
+// modelines and shebangs apply everywhere.
+
+let registryPromise = null;
+
+// All registry entries: [{ id, name, extensions, filenames }]
+export function getLanguageRegistry() {
+  if (!registryPromise) {
+    registryPromise = window.__TAURI__.core.invoke("get_language_registry").catch(error => {
+      console.error("Failed to load language registry:", error);
+      registryPromise = null;
+      return [];
+    });
+  }
+  return registryPromise;
+}
+
+// Language id for a file path or name; 'plaintext' when the file is unknown.
+// `content` is the text of an open buffer; without it the backend reads the
+// start of the file to look for a shebang or modeline.
+export async function getLanguageForFile(filePath, content = null) {
+  if (!filePath) return 'plaintext';
+  try {
+    const detection = await window.__TAURI__.core.invoke("detect_language", { file_path: filePath, content });
+    return detection?.languageId || 'plaintext';
+  } catch (error) {
+    console.error("Failed to resolve language for", filePath, error);
+    return 'plaintext';
+  }
+}
@@ -15,6 +15,9 @@ export class LSPClient {
     this.requestId = 1;
     this.responseHandlers = new Map();
     this.notificationHandlers = new Map();
+    // Documents the server has published diagnostics for; tree-sitter syntax
+    // errors for those are dropped so they don't replace the server's
+    this.serverDiagnosticUris = new Set();
     
     // Set up default notification handlers
     this.onNotification('textDocument/publishDiagnostics', this.handlePublishDiagnostics.bind(this));
@@ -181,6 +184,7 @@ export class LSPClient {
       }
     });
     this.openDocuments.delete(uri);
+    this.serverDiagnosticUris.delete(uri);
   }
 
   async documentSymbol(uri) {
@@ -221,8 +225,17 @@ export class LSPClient {
         } else {
           console.log(`[LSPClient] ${this.serverInfo.name} no handler found for ID:`, data.id);
         }
+      } else if (data.method === 'textDocument/publishDiagnostics' && data.params?.source === 'tree-sitter') {
+        // Syntax errors from the backend's parse trees: shown until the server
+        // publishes its own diagnostics for the document, dropped afterwards
+        if (!this.serverDiagnosticUris.has(data.params.uri)) {
+          this.handlePublishDiagnostics(data.params);
+        }
       } else if (data.method) {
         // This is a notification
+        if (data.method === 'textDocument/publishDiagnostics') {
+          this.serverDiagnosticUris.add(data.params.uri);
+        }
         const handler = this.notificationHandlers.get(data.method);
         if (handler) {
           handler(data.params);
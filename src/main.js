@@ -9,7 +9,7 @@ import { AiPanel } from './ai-panel.js';
 import { pendingEditsField } from './edit-manager.js';
 
 import DraggablePanes from './draggable-panes.js';
-import { getWorkspaceFiles, searchInFiles, fileExists, writeFile as fsWriteFile, readFile as fsReadFile } from './file-system.js';
+import { getWorkspaceFiles, startWorkspaceSearch, cancelWorkspaceSearch, enableSearchIndex, refreshTodosForFile, startSymbolIndex, queryWorkspaceSymbols, updateSymbolIndexForFile, startSemanticIndex, updateSemanticIndexForFile, findDefinition, fileExists, writeFile as fsWriteFile, readFile as fsReadFile } from './file-system.js';
 import { writeTextFile, shutdownAllLanguageServers } from './tauri-helpers.js';
 import OutlinePanel from './outline.js';
 import GitPanel from './git-panel.js';
@@ -89,6 +89,7 @@ let availableCommands = [
   { id: 'toggle-terminal', name: 'Toggle Terminal', action: () => setBottomPanel(currentBottomPanel === 'terminal' ? null : 'terminal') },
   { id: 'toggle-git-panel', name: 'Toggle Git Panel', action: () => setLeftPanel(currentLeftPanel === 'git-panel' ? 'project-panel' : 'git-panel') },
   { id: 'search-files', name: 'Search in Files', action: () => openSearch() },
+  { id: 'go-to-symbol', name: 'Go to Symbol in Workspace', action: () => openSymbolPalette() },
   { id: 'close-tab', name: 'Close Tab', action: () => tabManager?.closeTab(tabManager.activeTabId) },
   { id: 'close-other-tabs', name: 'Close Other Tabs', action: () => tabManager?.closeOtherTabs() },
   { id: 'close-all-tabs', name: 'Close All Tabs', action: () => tabManager?.closeAllTabs() },
@@ -403,6 +404,12 @@ function setupCommandPalette() {
       openCommandPalette();
       return;
     }
+    // Ctrl/Cmd + T to go to a symbol anywhere in the workspace
+    if ((e.ctrlKey || e.metaKey) && !e.shiftKey && (e.key === 't' || e.key === 'T')) {
+      e.preventDefault();
+      openSymbolPalette();
+      return;
+    }
     // Ctrl/Cmd + P to open file palette (only if shift is NOT pressed)
     if ((e.ctrlKey || e.metaKey) && !e.shiftKey && (e.key === 'p' || e.key === 'P')) {
       e.preventDefault();
@@ -443,6 +450,8 @@ function setupCommandPalette() {
       filterFiles(query);
     } else if (mode === 'commands') {
       filterCommands(query);
+    } else if (mode === 'symbols') {
+      filterSymbols(e.target.value);
     }
   });
 }
@@ -531,6 +540,7 @@ function initTabManager() {
     
     // Remove from legacy openTabs set
     openTabs.delete(tab.filePath);
+    editorInstance?.closeDocument(tab.filePath);
     
     // If no tabs left, show welcome screen
     if (tabManager.getAllTabs().length === 0) {
@@ -563,6 +573,11 @@ function initTabManager() {
       tabManager.updateTabContent(activeTab.id, e.detail.content);
     }
   });
+  
+  // Ctrl/Cmd+Click on a name in the editor
+  document.addEventListener('editor-go-to-definition', (e) => {
+    goToDefinition(e.detail);
+  });
 }
 
 // Legacy update tabs function (for backward compatibility)
@@ -735,6 +750,13 @@ async function saveCurrentFile() {
           }
         }
         
+        // Keep the TODO list current; a no-op until the workspace was scanned
+        if (fileExplorer?.rootFolder) {
+          refreshTodosForFile(fileExplorer.rootFolder, currentFilePath).catch(() => {});
+          updateSymbolIndexForFile(fileExplorer.rootFolder, currentFilePath).catch(() => {});
+          updateSemanticIndexForFile(fileExplorer.rootFolder, currentFilePath).catch(() => {});
+        }
+        
         // File saved silently - no notification needed
       } catch (saveErr) {
         console.error("Failed to save file:", saveErr);
@@ -831,6 +853,34 @@ async function openSettings() {
   }
 }
 
+// Large repositories can opt into the trigram index via settings.search.useIndex
+async function setupSearchIndex(workspacePath) {
+  const searchSettings = window.settings.search || {};
+  if (!workspacePath || !searchSettings.useIndex) return;
+  try {
+    await enableSearchIndex(workspacePath, searchSettings.persistIndex !== false);
+  } catch (err) {
+    console.error("Failed to enable search index:", err);
+  }
+}
+
+// Symbols for "Go to Symbol in Workspace" are indexed in the background
+function setupSymbolIndex(workspacePath) {
+  if (!workspacePath) return;
+  startSymbolIndex(workspacePath).catch(err => {
+    console.error("Failed to start symbol index:", err);
+  });
+}
+
+// Semantic search sends code to the configured embedding endpoint, so it is opt-in
+function setupSemanticIndex(workspacePath) {
+  const semanticSettings = window.settings.semanticSearch || {};
+  if (!workspacePath || !semanticSettings.enabled) return;
+  startSemanticIndex(workspacePath).catch(err => {
+    console.error("Failed to start semantic index:", err);
+  });
+}
+
 // Restore the last opened workspace
 async function restoreLastWorkspace() {
   try {
@@ -840,6 +890,9 @@ async function restoreLastWorkspace() {
       if (opened) {
         // Show project panel
         setLeftPanel("project-panel");
+        setupSearchIndex(fileExplorer.rootFolder);
+        setupSymbolIndex(fileExplorer.rootFolder);
+        setupSemanticIndex(fileExplorer.rootFolder);
       } else {
         // If failed to open, clear the invalid path
         window.settings.lastProject = null;
@@ -882,6 +935,10 @@ let searchState = {
   isRegex: false,
   caseSensitive: false,
   wholeWord: false,
+  includePattern: '',
+  excludePattern: '',
+  onlyOpenFiles: false,
+  respectGitignore: true,
   currentMode: 'files', // 'files' or 'content'
   searchTimeout: null,
   selectedIndex: 0,
@@ -905,6 +962,10 @@ function initializeFuzzySearch() {
   const wholeWordToggle = document.getElementById("search-whole-word-toggle");
   const filesTab = document.getElementById("search-files-tab");
   const contentTab = document.getElementById("search-content-tab");
+  const openFilesToggle = document.getElementById("search-open-files-toggle");
+  const gitignoreToggle = document.getElementById("search-gitignore-toggle");
+  const includeInput = document.getElementById("search-include-input");
+  const excludeInput = document.getElementById("search-exclude-input");
   
   console.log("Search elements found:", {
     searchInput: !!searchInput,
@@ -949,6 +1010,29 @@ function initializeFuzzySearch() {
     debouncedSearch();
   });
   
+  openFilesToggle.addEventListener('click', () => {
+    searchState.onlyOpenFiles = !searchState.onlyOpenFiles;
+    openFilesToggle.classList.toggle('active', searchState.onlyOpenFiles);
+    debouncedSearch();
+  });
+  
+  gitignoreToggle.addEventListener('click', () => {
+    searchState.respectGitignore = !searchState.respectGitignore;
+    gitignoreToggle.classList.toggle('active', searchState.respectGitignore);
+    debouncedSearch();
+  });
+  
+  // Include/exclude globs only affect content search
+  includeInput.addEventListener('input', (e) => {
+    searchState.includePattern = e.target.value;
+    debouncedSearch();
+  });
+  
+  excludeInput.addEventListener('input', (e) => {
+    searchState.excludePattern = e.target.value;
+    debouncedSearch();
+  });
+  
   // Tab switching
   filesTab.addEventListener('click', () => {
     console.log("Files tab clicked");
@@ -1018,6 +1102,10 @@ async function performSearch() {
       console.log("Searching content");
       // Search file contents
       results = await searchFileContents(query);
+      if (results === null) {
+        // Superseded by a newer search; that one will render its own results
+        return;
+      }
     }
     
     console.log("Search results:", results);
@@ -1080,36 +1168,93 @@ async function searchFiles(query) {
     }));
 }
 
+let activeContentSearchId = null;
+
+function getOpenFilePaths() {
+  if (!tabManager) return [];
+  return Array.from(tabManager.tabs.values())
+    .map(tab => tab.filePath)
+    .filter(Boolean);
+}
+
+// "Find in Folder" from the file explorer: scope content search via the include box,
+// the same way VS Code pre-fills "files to include" with ./folder
+function findInFolder(folderPath) {
+  const root = fileExplorer?.rootFolder;
+  if (!root) return;
+  
+  let relativePath = folderPath.startsWith(root) ? folderPath.substring(root.length) : folderPath;
+  relativePath = relativePath.replace(/^\/+/, '');
+  
+  openSearch();
+  searchState.includePattern = relativePath ? `./${relativePath}` : '';
+  document.getElementById("search-include-input").value = searchState.includePattern;
+  document.getElementById("search-content-tab").click();
+}
+
+document.addEventListener('find-in-folder', (e) => findInFolder(e.detail.path));
+
 async function searchFileContents(query) {
   if (!fileExplorer || !fileExplorer.rootFolder) {
     console.log("No file explorer or root folder for content search");
     return [];
   }
   
+  // Stale searches keep walking the tree unless we stop them explicitly
+  if (activeContentSearchId) {
+    cancelWorkspaceSearch(activeContentSearchId).catch(() => {});
+  }
+  const searchId = `content-${Date.now()}-${Math.random().toString(36).substring(2, 8)}`;
+  activeContentSearchId = searchId;
+  
   try {
     const searchOptions = {
       useRegex: searchState.isRegex,
       caseSensitive: searchState.caseSensitive,
       wholeWord: searchState.wholeWord,
-      maxResults: 100
+      maxResults: 100,
+      filters: {
+        include: searchState.includePattern,
+        exclude: searchState.excludePattern,
+        openFiles: searchState.onlyOpenFiles ? getOpenFilePaths() : null,
+        respectGitignore: searchState.respectGitignore
+      },
+      matchOptions: {
+        maxMatchesPerFile: 100
+      }
     };
     
-    console.log("Calling searchInFiles with:", {
+    console.log("Starting workspace search with:", {
+      searchId,
       workspacePath: fileExplorer.rootFolder,
       query,
       searchOptions
     });
     
-    const results = await searchInFiles(fileExplorer.rootFolder, query, searchOptions);
+    const results = [];
+    const summary = await startWorkspaceSearch(
+      searchId,
+      fileExplorer.rootFolder,
+      query,
+      searchOptions,
+      batch => results.push(...batch)
+    );
     
-    console.log("Raw search results from Tauri:", results);
+    console.log("Workspace search finished:", summary);
+    if (activeContentSearchId === searchId) {
+      activeContentSearchId = null;
+    }
+    if (summary.cancelled) {
+      return null;
+    }
     
     const mappedResults = results.map(result => ({
       type: 'content',
       path: result.path,
       name: result.name || result.path.split('/').pop(),
       relativePath: result.relativePath || result.path,
-      matches: result.matches || []
+      matches: result.matches || [],
+      totalMatches: result.totalMatches ?? (result.matches || []).length
     }));
     
     console.log("Mapped content search results:", mappedResults);
@@ -1217,13 +1362,25 @@ function createSearchResultElement(result, index) {
       
       const matchText = document.createElement('span');
       matchText.className = 'search-result-text';
-      matchText.innerHTML = highlightMatch(match.text, searchState.query);
+      if (match.endColumn) {
+        renderMatchRange(matchText, match);
+      } else {
+        matchText.innerHTML = highlightMatch(match.text, searchState.query);
+      }
       
       matchElement.appendChild(lineNumber);
       matchElement.appendChild(matchText);
       matchesContainer.appendChild(matchElement);
     });
     
+    const hiddenMatches = (result.totalMatches ?? result.matches.length) - Math.min(result.matches.length, 3);
+    if (hiddenMatches > 0) {
+      const more = document.createElement('div');
+      more.className = 'search-result-more';
+      more.textContent = `${hiddenMatches} more match${hiddenMatches === 1 ? '' : 'es'}`;
+      matchesContainer.appendChild(more);
+    }
+    
     element.appendChild(matchesContainer);
   }
   
@@ -1232,6 +1389,20 @@ function createSearchResultElement(result, index) {
   return element;
 }
 
+// Highlight the exact range the backend reported. Columns are UTF-16 based, so they
+// index straight into JS strings; multiline matches highlight to the end of the first line.
+function renderMatchRange(container, match) {
+  const firstLine = match.text.split('\n')[0];
+  const start = Math.max(0, match.column - 1);
+  const end = match.endLineNumber > match.lineNumber ? firstLine.length : Math.max(start, match.endColumn - 1);
+  
+  const highlight = document.createElement('span');
+  highlight.className = 'search-highlight';
+  highlight.textContent = firstLine.slice(start, end);
+  
+  container.append(firstLine.slice(0, start), highlight, firstLine.slice(end));
+}
+
 function highlightMatch(text, query) {
   if (!query || searchState.isRegex) {
     try {
@@ -1303,6 +1474,14 @@ async function openSearchResult(result) {
           lineNumber: firstMatch.lineNumber,
           column: firstMatch.column || 1
         });
+        if (firstMatch.endColumn) {
+          editorInstance.editor?.setSelection({
+            startLineNumber: firstMatch.lineNumber,
+            startColumn: firstMatch.column,
+            endLineNumber: firstMatch.endLineNumber,
+            endColumn: firstMatch.endColumn
+          });
+        }
         editorInstance.editor?.revealLineInCenter(firstMatch.lineNumber);
       }, 100);
     }
@@ -1420,6 +1599,9 @@ function initFileExplorer() {
       
       // Show project panel
       setLeftPanel("project-panel");
+      setupSearchIndex(fileExplorer.rootFolder);
+      setupSymbolIndex(fileExplorer.rootFolder);
+      setupSemanticIndex(fileExplorer.rootFolder);
       
       // Load workspace files for command palette
       try {
@@ -1500,6 +1682,129 @@ function openCommandPalette() {
   cmdInput.focus();
 }
 
+function openSymbolPalette() {
+  if (!fileExplorer || !fileExplorer.rootFolder) {
+    showNotification('No project opened', 'error');
+    return;
+  }
+  
+  const cmdPalette = document.getElementById("command-palette");
+  const cmdInput = document.getElementById("command-palette-input");
+  const cmdResults = document.getElementById("command-palette-results");
+  
+  cmdPalette.dataset.mode = 'symbols';
+  cmdInput.placeholder = 'Search symbols (Type.member narrows by container)...';
+  cmdInput.value = '';
+  cmdResults.innerHTML = '';
+  
+  cmdPalette.classList.remove("hidden");
+  cmdInput.focus();
+}
+
+// Only the answer to the latest keystroke is shown
+let symbolQueryId = 0;
+
+async function filterSymbols(query) {
+  const queryId = ++symbolQueryId;
+  if (!query.trim()) {
+    displaySymbols([]);
+    return;
+  }
+  
+  try {
+    const symbols = await queryWorkspaceSymbols(fileExplorer.rootFolder, query, 50);
+    if (queryId === symbolQueryId) {
+      displaySymbols(symbols);
+    }
+  } catch (err) {
+    console.error("Failed to query workspace symbols:", err);
+  }
+}
+
+function displaySymbols(symbols) {
+  const cmdResults = document.getElementById("command-palette-results");
+  cmdResults.innerHTML = '';
+  
+  symbols.forEach((symbol, index) => {
+    const item = document.createElement('div');
+    item.className = 'command-item';
+    if (index === 0) item.classList.add('selected');
+    
+    const icon = document.createElement('span');
+    icon.className = 'command-icon';
+    icon.innerHTML = outlinePanel ? outlinePanel.getSymbolIcon(symbol.kind) : '';
+    const name = document.createElement('span');
+    name.className = 'command-name';
+    name.textContent = symbol.containerName ? `${symbol.containerName}.${symbol.name}` : symbol.name;
+    const description = document.createElement('span');
+    description.className = 'command-description';
+    description.textContent = `${symbol.relativePath}:${symbol.selectionRange.start_line_number}`;
+    item.append(icon, name, description);
+    
+    item.addEventListener('click', () => openSymbolFromPalette(symbol));
+    cmdResults.appendChild(item);
+  });
+}
+
+async function openSymbolFromPalette(symbol) {
+  const cmdPalette = document.getElementById("command-palette");
+  cmdPalette.classList.add("hidden");
+  
+  try {
+    await fileExplorer.openFileByPath(symbol.path);
+    const range = symbol.selectionRange;
+    setTimeout(() => {
+      editorInstance?.editor?.setPosition({
+        lineNumber: range.start_line_number,
+        column: range.start_column
+      });
+      editorInstance?.editor?.revealLineInCenter(range.start_line_number);
+    }, 100);
+  } catch (err) {
+    console.error("Failed to open symbol:", err);
+    showNotification('Failed to open file', 'error');
+  }
+}
+
+// What to tell the user about a definition the backend only matched by name
+const APPROXIMATE_DEFINITION = {
+  medium: 'Approximate definition: matched by name in this file',
+  low: 'Approximate definition: matched by name in the workspace'
+};
+
+async function goToDefinition({ filePath, lineNumber, column }) {
+  let locations;
+  try {
+    locations = await findDefinition(filePath, lineNumber, column, fileExplorer?.rootFolder || null);
+  } catch (err) {
+    showNotification('Failed to find definition', 'error');
+    return;
+  }
+  
+  const [location] = locations;
+  if (!location) {
+    showNotification('No definition found', 'info');
+    return;
+  }
+  
+  try {
+    if (location.path !== currentFilePath) {
+      await fileExplorer.openFileByPath(location.path);
+      // Let the tab switch load the file into the editor
+      await new Promise(resolve => setTimeout(resolve, 100));
+    }
+    editorInstance?.selectRange(location.range);
+  } catch (err) {
+    console.error("Failed to open definition:", err);
+    showNotification('Failed to open file', 'error');
+    return;
+  }
+  
+  if (location.confidence !== 'high') {
+    showNotification(APPROXIMATE_DEFINITION[location.confidence], 'info');
+  }
+}
+
 function filterFiles(query) {
   if (!query) {
     displayFiles(workspaceFiles.slice(0, 20));
@@ -3,6 +3,18 @@
 // Outline panel now works with CodeMirror instead of Monaco
 
 import { invoke } from '@tauri-apps/api/core';
+import { getLanguageForFile } from './languages.js';
+
+// LSP SymbolKind names, in order (SymbolKind 1 is 'file'); the backend's kind strings use the same names
+const SYMBOL_KINDS = [
+  'file', 'module', 'namespace', 'package', 'class', 'method', 'property', 'field',
+  'constructor', 'enum', 'interface', 'function', 'variable', 'constant', 'string',
+  'number', 'boolean', 'array', 'object', 'key', 'null', 'enummember', 'struct',
+  'event', 'operator', 'typeparameter'
+];
+
+// LSP SymbolTag for deprecated symbols
+const SYMBOL_TAG_DEPRECATED = 1;
 
 class OutlinePanel {
   constructor() {
@@ -50,6 +62,7 @@ class OutlinePanel {
     this.updateOutline = this.updateOutline.bind(this);
     this.renderOutline = this.renderOutline.bind(this);
     this.setEditor = this.setEditor.bind(this);
+    this.handleContentChange = this.handleContentChange.bind(this);
 
     // Initialize Tree-sitter (async)
     this.initializeTreeSitter().catch(error => {
@@ -74,12 +87,12 @@ class OutlinePanel {
     // Set up listeners for content changes
     if (this.currentEditor) {
       // Listen to the editor-content-changed event that CodeMirror dispatches
-      document.addEventListener('editor-content-changed', this.handleContentChange.bind(this));
+      document.addEventListener('editor-content-changed', this.handleContentChange);
       
       // Initial update
       this.updateOutline();
     } else {
-      document.removeEventListener('editor-content-changed', this.handleContentChange.bind(this));
+      document.removeEventListener('editor-content-changed', this.handleContentChange);
       this.clearOutline();
     }
   }
@@ -107,9 +120,10 @@ class OutlinePanel {
     } */
 
     // Get data from CodeMirror editor
-    const fileName = this.currentEditor.currentFile?.name || 'untitled';
+    const currentFile = this.currentEditor.currentFile;
+    const filePath = currentFile?.path;
     const sourceCode = this.currentEditor.content || '';
-    const languageId = this.getLanguageFromFileName(fileName);
+    const languageId = await getLanguageForFile(currentFile?.path || currentFile?.name, sourceCode);
     const queries = []; // No longer using keyword queries - Tree-sitter handles everything
 
         try {
@@ -153,16 +167,25 @@ class OutlinePanel {
         console.log('[OUTLINE] Calling Tree-sitter with:', { 
           sourceCode: sourceCode.length + ' chars', 
           languageId, 
-          fileName 
+          filePath 
         });
         console.log('[OUTLINE] Source code preview:', sourceCode.substring(0, 200) + '...');
         
-        const symbols = await window.__TAURI__.core.invoke('parse_document_symbols', {
-          source_code: sourceCode,
-          language_id: languageId,
-          file_path: fileName,
-          queries: queries
-        });
+        let symbols;
+        if (filePath) {
+          // The editor keeps the backend's copy of the document; the outline
+          // is read from its cached tree
+          await this.currentEditor.syncDocument();
+          symbols = await window.__TAURI__.core.invoke('get_document_symbols', { file_path: filePath, queries });
+        } else {
+          // Untitled buffers are parsed from scratch
+          symbols = await window.__TAURI__.core.invoke('parse_document_symbols', {
+            source_code: sourceCode,
+            language_id: languageId,
+            file_path: '',
+            queries: queries
+          });
+        }
 
         console.log(`[OUTLINE] ✅ Tree-sitter parsed ${symbols.length} symbols for ${languageId}`);
         console.log('[OUTLINE] Tree-sitter symbols:', symbols);
@@ -187,14 +210,14 @@ class OutlinePanel {
       // Additional debug information
       if (error.message) {
         console.error('[OUTLINE] Error message analysis:', error.message);
-        if (error.message.includes('not found') || error.message.includes('undefined')) {
+        if (error.code === 'Unsupported') {
+          errorMessage = `Language "${languageId}" not supported.`;
+        } else if (error.message.includes('not found') || error.message.includes('undefined')) {
           console.error('[OUTLINE] This might be a missing command registration issue');
           errorMessage = 'Command registration issue. Tree-sitter not available.';
         } else if (error.message.includes('failed to parse') || error.message.includes('tree-sitter')) {
           console.error('[OUTLINE] This might be a tree-sitter library issue');
           errorMessage = 'Tree-sitter library error. Language not supported.';
-        } else if (error.message.includes('unsupported language')) {
-          errorMessage = `Language "${languageId}" not supported.`;
         } else {
           errorMessage = `Parse error: ${error.message}`;
         }
@@ -204,31 +227,6 @@ class OutlinePanel {
     }
   }
 
-  // Get language ID from filename
-  getLanguageFromFileName(fileName) {
-    const ext = fileName.split('.').pop()?.toLowerCase();
-    const languageMap = {
-      'js': 'javascript',
-      'jsx': 'javascript',
-      'ts': 'typescript',
-      'tsx': 'typescript',
-      'py': 'python',
-      'rs': 'rust',
-      'go': 'go',
-      'cpp': 'cpp',
-      'c': 'c',
-      'h': 'c',
-      'hpp': 'cpp',
-      'java': 'java',
-      'php': 'php',
-      'rb': 'ruby',
-      'html': 'html',
-      'css': 'css',
-      'json': 'json',
-      'md': 'markdown'
-    };
-    return languageMap[ext] || 'plaintext';
-  }
 
     getKeywordConfigForLanguage(languageId) {
     // Get current theme configuration
@@ -251,7 +249,7 @@ class OutlinePanel {
 
     const keywords = themes.dark.customKeywords;
 
-    if (languageId === 'javascript' || languageId === 'typescript') {
+    if (['javascript', 'javascriptreact', 'typescript', 'typescriptreact'].includes(languageId)) {
       return {
         keywords: [
           // Only include function and class declarations for typical outline behavior
@@ -380,7 +378,10 @@ class OutlinePanel {
     const li = document.createElement('li');
     li.className = 'outline-item';
     li.dataset.symbolName = symbol.name;
-    li.dataset.symbolKind = symbol.kind;
+    li.dataset.symbolKind = symbol.symbol_kind || symbol.kind;
+    if (symbol.tags?.includes(SYMBOL_TAG_DEPRECATED)) {
+      li.classList.add('deprecated');
+    }
 
     // Create item container
     const itemContainer = document.createElement('div');
@@ -423,6 +424,17 @@ class OutlinePanel {
     nameSpan.textContent = symbol.name;
     itemContainer.appendChild(nameSpan);
 
+    // Add signature or type, dimmed, as language servers send it
+    if (symbol.detail) {
+      const detailSpan = document.createElement('span');
+      detailSpan.className = 'outline-detail';
+      detailSpan.textContent = symbol.detail;
+      itemContainer.appendChild(detailSpan);
+    }
+    if (symbol.modifiers?.length) {
+      itemContainer.title = `${symbol.modifiers.join(' ')} ${symbol.name}`;
+    }
+
     // Add click handler to navigate to symbol
     itemContainer.addEventListener('click', () => {
       this.navigateToSymbol(symbol);
@@ -448,8 +460,10 @@ class OutlinePanel {
 
   // Get icon for symbol kind
   getSymbolIcon(kind) {
-    // Ensure kind is a string and lowercase
-    const normalizedKind = String(kind || '').toLowerCase();
+    // LSP sends the SymbolKind number; the backend the lowercase name
+    const normalizedKind = typeof kind === 'number'
+      ? SYMBOL_KINDS[kind - 1]
+      : String(kind || '').toLowerCase();
     return this.symbolIcons[normalizedKind] || this.symbolIcons['variable'];
   }
 
@@ -498,7 +512,7 @@ class OutlinePanel {
     this.currentEditor.focus();
 
     // Optionally select the symbol
-    if (symbol.selectionRange) {
+    if (symbol.selection_range) {
       this.currentEditor.setSelection({
         startLineNumber: symbol.selection_range.start_line_number,
         startColumn: symbol.selection_range.start_column,
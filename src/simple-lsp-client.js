@@ -15,6 +15,10 @@ export class SimpleLSPClient {
     this.pendingRequests = new Map();
     this.diagnosticsHandler = null;
     this.openDocuments = new Set();
+    // Syntax errors the backend publishes from its parse trees (source 'tree-sitter'),
+    // used only for documents the server has not sent diagnostics for
+    this.syntaxDiagnostics = new Map();
+    this.serverDiagnosticUris = new Set();
     
     console.log(`[SimpleLSP] Created client for ${serverInfo.name}`);
   }
@@ -121,9 +125,13 @@ export class SimpleLSPClient {
             pending.resolve(msg.result);
           }
         }
+      } else if (msg.method === 'textDocument/publishDiagnostics' && msg.params.source === 'tree-sitter') {
+        // The server's own diagnostics win; these only fill in until it has sent some
+        this.syntaxDiagnostics.set(msg.params.uri, msg.params.diagnostics || []);
       } else if (msg.method === 'textDocument/publishDiagnostics') {
         // Diagnostics notification
         console.log(`[SimpleLSP] ← diagnostics for ${msg.params.uri}`);
+        this.serverDiagnosticUris.add(msg.params.uri);
         if (this.diagnosticsHandler) {
           this.diagnosticsHandler(msg.params);
         }
@@ -162,6 +170,8 @@ export class SimpleLSPClient {
       textDocument: { uri: uri }
     });
     this.openDocuments.delete(uri);
+    this.serverDiagnosticUris.delete(uri);
+    this.syntaxDiagnostics.delete(uri);
   }
 
   // Simple diagnostics getter - just open a document and wait for results
@@ -174,7 +184,9 @@ export class SimpleLSPClient {
     
     return new Promise(async (resolve, reject) => {
       const timeout = setTimeout(() => {
-        resolve([]); // Return empty if no diagnostics received
+        this.diagnosticsHandler = null;
+        // Without an answer from the server, fall back to the syntax errors
+        resolve(this.serverDiagnosticUris.has(uri) ? [] : (this.syntaxDiagnostics.get(uri) || []));
       }, 5000);
 
       // Set up one-time diagnostics handler
@@ -225,7 +225,7 @@ class LocalTerminal {
         if (attempt === maxRetries) {
           // Final attempt failed
           if (this.xterm) {
-            this.xterm.write(`\r\n\x1b[31mFailed to start terminal after ${maxRetries} attempts: ${err}\x1b[0m\r\n`);
+            this.xterm.write(`\r\n\x1b[31mFailed to start terminal after ${maxRetries} attempts: ${err?.message ?? err}\x1b[0m\r\n`);
           }
         } else {
           // Wait before retrying
@@ -256,7 +256,7 @@ class LocalTerminal {
       console.error("Failed to write to terminal:", err);
       
       // If session not found, try to recreate it
-      if (err.toString().includes("Terminal session not found")) {
+      if (err?.code === 'SessionNotFound') {
         console.log(`Terminal session ${this.sessionId} not found, recreating...`);
         this.sessionCreated = false;
         this.initialPromptSent = false;
@@ -306,7 +306,7 @@ class LocalTerminal {
     } catch (err) {
       console.error("Failed to recreate terminal session:", err);
       if (this.xterm) {
-        this.xterm.write(`\r\n\x1b[31mFailed to recreate terminal: ${err}\x1b[0m\r\n`);
+        this.xterm.write(`\r\n\x1b[31mFailed to recreate terminal: ${err?.message ?? err}\x1b[0m\r\n`);
       }
     }
   }
@@ -370,7 +370,7 @@ class LocalTerminal {
       console.error("Failed to resize terminal:", err);
       
       // If session not found, try to recreate it
-      if (err.toString().includes("Terminal session not found")) {
+      if (err?.code === 'SessionNotFound') {
         console.log(`Terminal session ${this.sessionId} not found during resize, recreating...`);
         this.sessionCreated = false;
         this.initialPromptSent = false;
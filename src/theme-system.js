@@ -207,6 +207,14 @@ export function createCodeMirrorTheme(themeConfig) {
       outline: `1px solid ${syntax.keyword}`
     },
     
+    // Occurrences of the symbol under the cursor
+    '.cm-occurrence': {
+      backgroundColor: selectionMatch
+    },
+    '.cm-occurrence-definition': {
+      outline: `1px solid ${foreground}`
+    },
+    
     // Current line highlight
     '.cm-activeLine': {
       backgroundColor: lineHighlight